@@ -1,28 +1,147 @@
-use crate::{Impex, IntoImpex, WrapperSettings};
+use crate::{
+    CollectionImpex, DebugValue, DefaultChange, Impex, ImpexCompact, ImpexDefaults, ImpexMerge,
+    ImpexPath, IntoImpex, PathVisitor, VisitPath, WrapperSettings,
+    compact::{CompactSeq, CompactVecSeed},
+    rebase_implicit_value,
+};
 
 impl<TW: WrapperSettings, T: IntoImpex<TW>, const SIZE: usize> IntoImpex<TW> for [T; SIZE] {
-    type Impex = [T::Impex; SIZE];
+    type Impex = CollectionImpex<[T::Impex; SIZE]>;
 
     fn into_impex(self, is_explicit: bool) -> Self::Impex {
-        self.map(|v| v.into_impex(is_explicit))
+        CollectionImpex::new(self.map(|v| v.into_impex(is_explicit)), is_explicit)
     }
 }
 
-impl<TW, T: Impex<TW>, const SIZE: usize> Impex<TW> for [T; SIZE] {
+impl<TW, T: Impex<TW>, const SIZE: usize> Impex<TW> for CollectionImpex<[T; SIZE]> {
     type Value = [T::Value; SIZE];
 
     fn is_explicit(&self) -> bool {
-        self.iter().any(|x| x.is_explicit())
+        self.is_explicit_flag() || self.iter().any(|x| x.is_explicit())
     }
 
     fn into_value(self) -> Self::Value {
-        self.map(Impex::into_value)
+        self.into_inner().map(Impex::into_value)
     }
 
     fn set_impex(&mut self, v: Self::Value, is_explicit: bool) {
-        self.iter_mut().zip(v).for_each(|(target, value)| {
-            target.set_impex(value, is_explicit);
-        });
+        self.set_explicit_flag(is_explicit);
+        self.items_mut()
+            .iter_mut()
+            .zip(v)
+            .for_each(|(target, value)| {
+                target.set_impex(value, is_explicit);
+            });
+    }
+
+    fn fill_defaults(&mut self, default: Self) {
+        self.items_mut()
+            .iter_mut()
+            .zip(default.into_inner())
+            .for_each(|(value, default)| value.fill_defaults(default));
+    }
+}
+
+/// Arrays are compared as a whole, so they never end up partially explicit
+impl<TW: WrapperSettings, T: ImpexDefaults<TW>, const SIZE: usize> ImpexDefaults<TW>
+    for CollectionImpex<[T; SIZE]>
+{
+    fn value_eq(&self, other: &Self) -> bool {
+        self.iter().zip(other).all(|(a, b)| a.value_eq(b))
+    }
+
+    fn prune_defaults_with(&mut self, default: &Self) {
+        if self.value_eq(default) {
+            self.set_explicit_flag(false);
+            self.items_mut()
+                .iter_mut()
+                .zip(default)
+                .for_each(|(value, default)| value.prune_defaults_with(default));
+        }
+    }
+
+    fn fmt_value(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.iter().map(DebugValue::<TW, T>::new))
+            .finish()
+    }
+
+    fn rebase_defaults_at(
+        &mut self,
+        new_default: Self,
+        path: &ImpexPath,
+        changes: &mut Vec<DefaultChange>,
+    ) {
+        rebase_implicit_value(self, new_default, path, changes);
+    }
+}
+
+/// Arrays have a fixed length, so they are merged element by element
+impl<TW: WrapperSettings, T: ImpexMerge<TW>, const SIZE: usize> ImpexMerge<TW>
+    for CollectionImpex<[T; SIZE]>
+{
+    fn merge(&mut self, overlay: Self) {
+        self.set_explicit_flag(self.is_explicit_flag() || overlay.is_explicit_flag());
+        self.items_mut()
+            .iter_mut()
+            .zip(overlay.into_inner())
+            .for_each(|(value, overlay)| value.merge(overlay));
+    }
+}
+
+impl<TW: WrapperSettings, T: VisitPath<TW>, const SIZE: usize> VisitPath<TW>
+    for CollectionImpex<[T; SIZE]>
+where
+    Self: serde::de::DeserializeOwned,
+{
+    fn visit_path<V: PathVisitor<TW>>(&mut self, path: &[&str], visitor: &mut V) -> bool {
+        let Some((index, rest)) = path.split_first() else {
+            visitor.visit(self);
+            return true;
+        };
+        let found = index
+            .parse::<usize>()
+            .ok()
+            .and_then(|index| self.items_mut().get_mut(index))
+            .is_some_and(|value| value.visit_path(rest, visitor));
+        // Lists are saved as a whole, so the other elements need to be written as well
+        if found {
+            self.materialize_defaults();
+        }
+        found
+    }
+}
+
+/// Arrays are written as a whole and loaded as explicit
+impl<TW, T: ImpexCompact<TW>, const SIZE: usize> ImpexCompact<TW> for CollectionImpex<[T; SIZE]> {
+    fn serialize_compact<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(&CompactSeq::<_, TW>::new(self.iter()), serializer)
+    }
+
+    fn deserialize_compact<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        use serde::de::{DeserializeSeed, Error};
+        let items = CompactVecSeed::<T, TW>::new().deserialize(deserializer)?;
+        let length = items.len();
+        let items = items
+            .try_into()
+            .map_err(|_| D::Error::invalid_length(length, &SIZE.to_string().as_str()))?;
+        Ok(CollectionImpex::new(items, true))
+    }
+}
+
+#[cfg(feature = "schema")]
+impl<TW: WrapperSettings, T: crate::schema::ImpexSchema<TW>, const SIZE: usize>
+    crate::schema::ImpexSchema<TW> for CollectionImpex<[T; SIZE]>
+{
+    fn json_schema() -> crate::schema::Schema {
+        serde_json::json!({
+            "type": "array",
+            "items": T::json_schema(),
+            "minItems": SIZE,
+            "maxItems": SIZE,
+        })
     }
 }
 
@@ -0,0 +1,421 @@
+//! Reference documentation of all settings, enabled by the `catalog` feature.
+//!
+//! [SettingsCatalog::new] walks the default of an Impex type and lists every setting
+//! with its path, type, default and doc comment. Structs with named fields are sections,
+//! whose fields are listed below them. Every other value, like lists, maps or enums, is a single setting.
+//! [SettingsCatalog::to_markdown] renders the catalog as a table, e.g. from a build script.
+//! [SettingsCatalog::to_jsonc_example] and [SettingsCatalog::to_toml_example] generate a starting file
+//! with every setting commented out at its default.
+//! ```
+//! # use impex::{Impex, catalog::SettingsCatalog};
+//! /// Settings of the server
+//! #[derive(Default, Impex)]
+//! struct Config {
+//!     /// Number of worker threads
+//!     threads: u32,
+//!     limits: Limits,
+//! }
+//!
+//! /// Limits of a request
+//! #[derive(Default, Impex)]
+//! struct Limits {
+//!     size: Option<u64>,
+//! }
+//!
+//! let catalog = SettingsCatalog::new::<_, ConfigImpex>();
+//! assert_eq!(
+//!     "Settings of the server\n\
+//!      \n\
+//!      | Setting | Type | Default | Description |\n\
+//!      | --- | --- | --- | --- |\n\
+//!      | `threads` | `u32` | `0` | Number of worker threads |\n\
+//!      | `limits` | `Limits` |  | Limits of a request |\n\
+//!      | `limits.size` | `Option<u64>` | `null` |  |\n",
+//!     catalog.to_markdown()
+//! );
+//! ```
+
+use crate::{
+    CollectionImpex, ExtendListImpex, FieldMeta, Impex, ImpexMap, ImpexMeta, ImpexPath,
+    ImpexPrimitiveValue, KeyedListImpex, ListKey, MapImpex, OptionImpex, Patch, TupleElements,
+    TupleImpex, WrapperSettings,
+};
+
+/// Impex types which can list their settings.
+/// The default implementation lists the value as a single setting,
+/// structs with named fields generated by `#[derive(Impex)]` list their fields instead.
+pub trait ImpexCatalog<TW: WrapperSettings>: Impex<TW> + serde::Serialize {
+    /// Adds the settings of this value at `path`. The value is the default, with every part explicit.
+    /// `description` is the doc comment of the field holding the value.
+    fn add_settings(
+        &self,
+        path: &ImpexPath,
+        description: Option<&str>,
+        catalog: &mut SettingsCatalog,
+    ) {
+        catalog.add_setting(path, description, self);
+    }
+}
+
+/// A setting or a section of settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct Setting {
+    pub path: ImpexPath,
+    /// Name of the type without module paths, e.g. `Vec<String>`
+    pub type_name: String,
+    /// The default as JSON, `None` for sections
+    pub default: Option<String>,
+    pub description: Option<String>,
+    /// Metadata from `#[impex(meta(...))]` on the field
+    pub meta: Option<&'static FieldMeta>,
+}
+
+/// All settings of a type, in declaration order
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SettingsCatalog {
+    /// Doc comment of the type itself
+    pub description: Option<String>,
+    pub settings: Vec<Setting>,
+}
+
+impl SettingsCatalog {
+    /// Lists the settings of `T`, with the defaults taken from its `Default`
+    /// The metadata of the fields is looked up by their path, its description replaces the doc comment.
+    pub fn new<TW: WrapperSettings, T: ImpexCatalog<TW> + ImpexMeta<TW> + Default + Clone>() -> Self
+    {
+        let mut value = T::default();
+        value.materialize_defaults();
+        let mut catalog = Self::default();
+        value.add_settings(&ImpexPath::root(), None, &mut catalog);
+        for setting in &mut catalog.settings {
+            let path: Vec<_> = setting.path.segments().collect();
+            setting.meta = T::field_meta(&path);
+            if let Some(description) = setting.meta.and_then(|meta| meta.description) {
+                setting.description = Some(description.into());
+            }
+        }
+        catalog
+    }
+
+    /// Adds a single setting with `value` as its default
+    pub fn add_setting<TW, T: Impex<TW> + serde::Serialize + ?Sized>(
+        &mut self,
+        path: &ImpexPath,
+        description: Option<&str>,
+        value: &T,
+    ) {
+        self.add_setting_as::<TW, T>(path, description, serde_json::to_string(value).ok());
+    }
+
+    /// Adds a single setting of type `T` with a default which is already rendered as JSON
+    fn add_setting_as<TW, T: Impex<TW> + ?Sized>(
+        &mut self,
+        path: &ImpexPath,
+        description: Option<&str>,
+        default: Option<String>,
+    ) {
+        self.settings.push(Setting {
+            path: path.clone(),
+            type_name: short_type_name(std::any::type_name::<T::Value>()),
+            default,
+            description: description.map(Into::into),
+            meta: None,
+        });
+    }
+
+    /// Adds a struct, whose fields are added after it. The struct at the root only sets the description.
+    /// Used by `#[derive(Impex)]`.
+    pub fn add_section<TW, T: Impex<TW>>(&mut self, path: &ImpexPath, description: Option<&str>) {
+        let description = description.map(Into::into);
+        if path.is_root() {
+            self.description = description;
+            return;
+        }
+        self.settings.push(Setting {
+            path: path.clone(),
+            type_name: short_type_name(std::any::type_name::<T::Value>()),
+            default: None,
+            description,
+            meta: None,
+        });
+    }
+
+    /// Renders the description and a table of the settings
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::new();
+        if let Some(description) = &self.description {
+            markdown.push_str(description);
+            markdown.push_str("\n\n");
+        }
+        markdown.push_str("| Setting | Type | Default | Description |\n");
+        markdown.push_str("| --- | --- | --- | --- |\n");
+        for setting in &self.settings {
+            let default = setting
+                .default
+                .as_deref()
+                .map(code_cell)
+                .unwrap_or_default();
+            let description = setting
+                .description
+                .as_deref()
+                .map(|description| escape_cell(&description.replace('\n', " ")))
+                .unwrap_or_default();
+            markdown.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                code_cell(&setting.path.to_string()),
+                code_cell(&setting.type_name),
+                default,
+                description
+            ));
+        }
+        markdown
+    }
+}
+
+/// Examples listing every setting with its default, for new users to start from.
+/// Every key is commented out, so loading an example unchanged gives a value where everything is implicit.
+impl SettingsCatalog {
+    /// Renders a JSONC example with every setting at its default and doc comments above the keys.
+    /// Keys at the top level are commented out as a whole, including the structs they contain.
+    pub fn to_jsonc_example(&self) -> String {
+        let mut example = String::new();
+        if let Some(description) = &self.description {
+            push_comment(&mut example, "", "//", description);
+        }
+        example.push_str("{\n");
+        for node in self.tree() {
+            if let Some(description) = &node.setting.description {
+                push_comment(&mut example, "  ", "//", description);
+            }
+            let mut block = String::new();
+            node.push_jsonc(&mut block, "");
+            for line in block.lines() {
+                example.push_str("  // ");
+                example.push_str(line);
+                example.push('\n');
+            }
+        }
+        example.push_str("}\n");
+        example
+    }
+
+    /// Renders a TOML example with every setting at its default commented out and doc comments
+    /// above the keys. Structs become tables, which are written after the other keys of their parent.
+    /// TOML has no `null`, so settings without a default are only named.
+    #[cfg(feature = "toml")]
+    pub fn to_toml_example(&self) -> String {
+        let mut example = String::new();
+        if let Some(description) = &self.description {
+            push_comment(&mut example, "", "#", description);
+            example.push('\n');
+        }
+        push_toml_table(&mut example, &self.tree());
+        example
+    }
+
+    /// Nests the settings below their sections
+    fn tree(&self) -> Vec<SettingNode<'_>> {
+        fn insert<'a>(nodes: &mut Vec<SettingNode<'a>>, setting: &'a Setting, depth: usize) {
+            match nodes.last_mut() {
+                Some(parent) if depth > 1 && parent.setting.default.is_none() => {
+                    insert(&mut parent.children, setting, depth - 1)
+                }
+                _ => nodes.push(SettingNode {
+                    setting,
+                    children: Vec::new(),
+                }),
+            }
+        }
+
+        let mut nodes = Vec::new();
+        for setting in &self.settings {
+            insert(&mut nodes, setting, setting.path.segments().count());
+        }
+        nodes
+    }
+}
+
+/// A setting together with the settings of its fields, if it's a section
+struct SettingNode<'a> {
+    setting: &'a Setting,
+    children: Vec<SettingNode<'a>>,
+}
+
+impl SettingNode<'_> {
+    fn key(&self) -> &str {
+        self.setting.path.segments().last().unwrap_or_default()
+    }
+
+    fn push_jsonc(&self, example: &mut String, indent: &str) {
+        let key = serde_json::to_string(self.key()).expect("strings are valid JSON");
+        let Some(default) = &self.setting.default else {
+            example.push_str(&format!("{indent}{key}: {{\n"));
+            let inner = format!("{indent}  ");
+            for child in &self.children {
+                if let Some(description) = &child.setting.description {
+                    push_comment(example, &inner, "//", description);
+                }
+                child.push_jsonc(example, &inner);
+            }
+            example.push_str(&format!("{indent}}},\n"));
+            return;
+        };
+        example.push_str(&format!("{indent}{key}: {default},\n"));
+    }
+}
+
+/// Writes the keys of a table, followed by its sections as tables of their own
+#[cfg(feature = "toml")]
+fn push_toml_table(example: &mut String, nodes: &[SettingNode<'_>]) {
+    let (sections, settings): (Vec<_>, Vec<_>) = nodes
+        .iter()
+        .partition(|node| node.setting.default.is_none());
+    for node in settings {
+        if let Some(description) = &node.setting.description {
+            push_comment(example, "", "#", description);
+        }
+        let key = toml_edit::Key::new(node.key());
+        let key = key.display_repr();
+        let default = node.setting.default.as_deref().unwrap_or_default();
+        match toml_value(default) {
+            Some(value) => example.push_str(&format!("# {key} = {value}\n")),
+            None if default == "null" => {
+                example.push_str(&format!("# {key} is not set by default\n"))
+            }
+            None => example.push_str(&format!(
+                "# {key}: the default {default} can't be written in TOML\n"
+            )),
+        }
+    }
+    for node in sections {
+        example.push('\n');
+        if let Some(description) = &node.setting.description {
+            push_comment(example, "", "#", description);
+        }
+        let header: Vec<_> = node
+            .setting
+            .path
+            .segments()
+            .map(|segment| toml_edit::Key::new(segment).display_repr().into_owned())
+            .collect();
+        example.push_str(&format!("# [{}]\n", header.join(".")));
+        push_toml_table(example, &node.children);
+    }
+}
+
+/// Converts a default from JSON, fails for values containing `null`
+#[cfg(feature = "toml")]
+fn toml_value(json: &str) -> Option<toml_edit::Value> {
+    let value: serde_json::Value = serde_json::from_str(json).ok()?;
+    serde::Serialize::serialize(&value, toml_edit::ser::ValueSerializer::new()).ok()
+}
+
+fn push_comment(example: &mut String, indent: &str, marker: &str, text: &str) {
+    for line in text.lines() {
+        if line.is_empty() {
+            example.push_str(&format!("{indent}{marker}\n"));
+        } else {
+            example.push_str(&format!("{indent}{marker} {line}\n"));
+        }
+    }
+}
+
+fn code_cell(text: &str) -> String {
+    format!("`{}`", escape_cell(text))
+}
+
+/// Pipes would end the cell, even inside of code
+fn escape_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+/// Removes the module paths of a type name, e.g. `alloc::vec::Vec<alloc::string::String>`
+/// becomes `Vec<String>`
+fn short_type_name(name: &str) -> String {
+    let mut short = String::new();
+    let mut path = String::new();
+    for c in name.chars() {
+        if c.is_alphanumeric() || c == '_' || c == ':' {
+            path.push(c);
+            continue;
+        }
+        short.push_str(path.rsplit("::").next().unwrap_or_default());
+        path.clear();
+        short.push(c);
+    }
+    short.push_str(path.rsplit("::").next().unwrap_or_default());
+    short
+}
+
+impl<T, TW: WrapperSettings> ImpexCatalog<TW> for ImpexPrimitiveValue<T> where
+    Self: Impex<TW> + serde::Serialize
+{
+}
+impl<T, TW: WrapperSettings> ImpexCatalog<TW> for OptionImpex<T> where
+    Self: Impex<TW> + serde::Serialize
+{
+}
+impl<T, TW: WrapperSettings> ImpexCatalog<TW> for Patch<T> where Self: Impex<TW> + serde::Serialize {}
+impl<C, TW: WrapperSettings> ImpexCatalog<TW> for CollectionImpex<C> where
+    Self: Impex<TW> + serde::Serialize
+{
+}
+impl<T: TupleElements, TW: WrapperSettings> ImpexCatalog<TW> for TupleImpex<T, TW> where
+    Self: Impex<TW> + serde::Serialize
+{
+}
+impl<M: ImpexMap, TW: WrapperSettings> ImpexCatalog<TW> for MapImpex<M, TW> where
+    Self: Impex<TW> + serde::Serialize
+{
+}
+/// The default is listed with all of its elements, as the saved changes are empty
+impl<T, TW: WrapperSettings> ImpexCatalog<TW> for ExtendListImpex<T, TW>
+where
+    Self: Impex<TW> + serde::Serialize,
+    T: Impex<TW> + Clone + serde::Serialize,
+{
+    fn add_settings(
+        &self,
+        path: &ImpexPath,
+        description: Option<&str>,
+        catalog: &mut SettingsCatalog,
+    ) {
+        let elements: Vec<T> = self
+            .iter()
+            .cloned()
+            .map(|mut element| {
+                element.materialize_defaults();
+                element
+            })
+            .collect();
+        let default = serde_json::to_string(&elements).ok();
+        catalog.add_setting_as::<TW, Self>(path, description, default);
+    }
+}
+impl<K: ListKey<TW>, TW: WrapperSettings> ImpexCatalog<TW> for KeyedListImpex<K, TW> where
+    Self: Impex<TW> + serde::Serialize
+{
+}
+
+/// Pointers are transparent, so the settings of the pointee are listed
+macro_rules! impl_pointer_catalog {
+    ($ptr:ty) => {
+        impl<T: ImpexCatalog<TW> + Clone, TW: WrapperSettings> ImpexCatalog<TW> for $ptr
+        where
+            Self: Impex<TW> + serde::Serialize,
+        {
+            fn add_settings(
+                &self,
+                path: &ImpexPath,
+                description: Option<&str>,
+                catalog: &mut SettingsCatalog,
+            ) {
+                (**self).add_settings(path, description, catalog);
+            }
+        }
+    };
+}
+
+impl_pointer_catalog!(std::rc::Rc<T>);
+impl_pointer_catalog!(std::sync::Arc<T>);
+impl_pointer_catalog!(Box<T>);
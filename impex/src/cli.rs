@@ -0,0 +1,732 @@
+//! Overrides from command line arguments, enabled by the `cli` feature.
+//!
+//! Every leaf of an Impex type gets a flag named after its path, e.g. `--num-cores <N>` for
+//! `num_cores` or `--limits.max <N>` for `limits.max`. Any value can also be set by its path
+//! with `--set limits.max=4`. Values are parsed into the type at the path like environment
+//! variables: primitives from the text, composite values as JSON. They are set explicitly,
+//! so the result can be merged as an overlay layer.
+//!
+//! The parser works on plain arguments, arguments it doesn't know are returned to be handled
+//! by the application. With the `clap` feature, [clap_args] and [from_clap_matches] add the
+//! flags to a clap command instead.
+//! ```
+//! # use impex::{Impex, ImpexMerge, cli};
+//! #[derive(Default, Impex)]
+//! struct Limits {
+//!     max: u32,
+//! }
+//!
+//! #[derive(Default, Impex)]
+//! struct Config {
+//!     num_cores: u32,
+//!     verbose: bool,
+//!     limits: Limits,
+//! }
+//!
+//! let args = ["--num-cores", "4", "--verbose", "--set", "limits.max=8", "input.txt"];
+//! let args: Vec<String> = args.into_iter().map(String::from).collect();
+//! let overlay = cli::overlay::<_, ConfigImpex>(&args).unwrap();
+//! assert_eq!(["input.txt"], overlay.remaining.as_slice());
+//!
+//! let mut config = ConfigImpex::default();
+//! config.merge(overlay.value);
+//! assert_eq!(4, *config.num_cores);
+//! assert!(*config.verbose);
+//! assert_eq!(8, *config.limits.max);
+//! ```
+
+use std::fmt::Display;
+
+use serde::{Deserializer, Serialize, Serializer, de, ser};
+
+use crate::{
+    ImpexPath, PathVisitor, VisitPath, WrapperSettings,
+    extend::EXTEND_LIST_NAME,
+    text::{path_exists, set_from_text},
+};
+
+/// A generated flag, which overrides the leaf at `path`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CliFlag {
+    /// Name without the leading `--`, e.g. `limits.max`
+    pub name: String,
+    pub path: ImpexPath,
+    /// Kind of the value, e.g. `N` for integers or `JSON` for composite values
+    pub value_name: &'static str,
+    /// The default value as JSON
+    pub default: Option<String>,
+}
+
+impl CliFlag {
+    /// Boolean flags can be given without a value, which sets them to `true`
+    pub fn is_switch(&self) -> bool {
+        self.value_name == "BOOL"
+    }
+
+    pub fn help(&self) -> String {
+        match &self.default {
+            Some(default) => format!("Sets `{}` (default: {default})", self.path),
+            None => format!("Sets `{}`", self.path),
+        }
+    }
+}
+
+/// Values set by command line arguments, to be merged onto the loaded configuration
+#[derive(Debug, Clone)]
+pub struct CliOverlay<T> {
+    /// The default value with the arguments set explicitly
+    pub value: T,
+    /// Arguments which are not overrides, in their original order
+    pub remaining: Vec<String>,
+}
+
+/// Flags for the leaves of the default value of `T`.
+/// Enums get a flag for the whole value and flags for the fields of their default variant.
+/// Tuple structs get a flag for the whole value and flags for their elements.
+/// Lists with `#[impex(list = "extend")]` get a flag for all of their elements as JSON.
+/// Maps get flags for their default entries.
+/// A leaf named `set` can only be overridden with `--set`, as the flag is taken.
+pub fn flags<TW: WrapperSettings, T: VisitPath<TW> + Default + Serialize>() -> Vec<CliFlag> {
+    let mut value = T::default();
+    value.materialize_defaults();
+    let mut leaves = Vec::new();
+    // Leaves collected before a failing value are still used
+    let _ = value.serialize(Collector {
+        path: Vec::new(),
+        default: None,
+        leaves: &mut leaves,
+    });
+
+    let mut flags: Vec<CliFlag> = Vec::new();
+    for leaf in leaves {
+        let segments: Vec<_> = leaf.path.iter().map(String::as_str).collect();
+        if segments.is_empty() || !path_exists(&mut value, &segments) {
+            continue;
+        }
+        if leaf.skipped && !is_extend_list(&mut value, &segments) {
+            continue;
+        }
+        let name = flag_name(&leaf.path);
+        if name == "set" || flags.iter().any(|flag| flag.name == name) {
+            continue;
+        }
+        flags.push(CliFlag {
+            name,
+            path: leaf
+                .path
+                .into_iter()
+                .fold(ImpexPath::root(), |path, segment| path.join(segment)),
+            value_name: leaf.value_name,
+            default: leaf.default,
+        });
+    }
+    flags
+}
+
+/// Sets the values of the arguments explicitly in the default value of `T`
+pub fn overlay<TW: WrapperSettings, T: VisitPath<TW> + Default + Serialize>(
+    args: &[String],
+) -> Result<CliOverlay<T>, CliError> {
+    let mut value = T::default();
+    let remaining = apply(&mut value, args)?;
+    Ok(CliOverlay { value, remaining })
+}
+
+/// Sets the values of the arguments explicitly in `target`.
+/// Returns the arguments which are not overrides; everything after `--` is kept as well.
+pub fn apply<TW: WrapperSettings, T: VisitPath<TW> + Default + Serialize>(
+    target: &mut T,
+    args: &[String],
+) -> Result<Vec<String>, CliError> {
+    let flags = flags::<TW, T>();
+    let mut remaining = Vec::new();
+    let mut args = args.iter().peekable();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            remaining.push(arg.clone());
+            remaining.extend(args.cloned());
+            break;
+        }
+        let Some(name) = arg.strip_prefix("--") else {
+            remaining.push(arg.clone());
+            continue;
+        };
+        let (name, inline) = match name.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (name, None),
+        };
+
+        if name == "set" {
+            let assignment = inline
+                .or_else(|| args.next().map(String::as_str))
+                .ok_or_else(|| CliError::MissingValue(arg.clone()))?;
+            set_assignment(target, assignment)?;
+            continue;
+        }
+        let Some(flag) = flags.iter().find(|flag| flag.name == name) else {
+            remaining.push(arg.clone());
+            continue;
+        };
+        let value = match inline {
+            Some(value) => value,
+            None if flag.is_switch() => {
+                match args.next_if(|next| *next == "true" || *next == "false") {
+                    Some(value) => value.as_str(),
+                    None => "true",
+                }
+            }
+            None => args
+                .next()
+                .ok_or_else(|| CliError::MissingValue(arg.clone()))?,
+        };
+        set_at(target, &flag.path, value, arg)?;
+    }
+    Ok(remaining)
+}
+
+/// Sets a `path=value` assignment of `--set`
+fn set_assignment<TW: WrapperSettings, T: VisitPath<TW>>(
+    target: &mut T,
+    assignment: &str,
+) -> Result<(), CliError> {
+    let (path, value) = assignment
+        .split_once('=')
+        .ok_or_else(|| CliError::MissingValue(format!("--set {assignment}")))?;
+    set_at(target, &ImpexPath::from(path), value, assignment)
+}
+
+fn set_at<TW: WrapperSettings, T: VisitPath<TW>>(
+    target: &mut T,
+    path: &ImpexPath,
+    value: &str,
+    argument: &str,
+) -> Result<(), CliError> {
+    let segments: Vec<_> = path.segments().collect();
+    match set_from_text(target, &segments, value) {
+        Some(Ok(())) => Ok(()),
+        Some(Err(error)) => Err(CliError::InvalidValue {
+            argument: argument.to_owned(),
+            path: path.clone(),
+            error,
+        }),
+        None => Err(CliError::UnknownPath(path.clone())),
+    }
+}
+
+/// `num_cores` becomes `num-cores`, nested segments are separated by dots
+fn flag_name(path: &[String]) -> String {
+    path.iter()
+        .map(|segment| segment.to_lowercase().replace('_', "-"))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Returned if command line overrides can't be applied
+#[derive(Debug)]
+pub enum CliError {
+    /// A flag or `--set` without a value
+    MissingValue(String),
+    /// `--set` with a path which doesn't point to a value
+    UnknownPath(ImpexPath),
+    /// A value which can't be parsed into the type at its path
+    InvalidValue {
+        argument: String,
+        path: ImpexPath,
+        error: serde_json::Error,
+    },
+}
+
+impl Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CliError::MissingValue(argument) => write!(f, "missing value for `{argument}`"),
+            CliError::UnknownPath(path) => write!(f, "no value at path `{path}`"),
+            CliError::InvalidValue {
+                argument,
+                path,
+                error,
+            } => write!(f, "invalid value `{argument}` for `{path}`: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for CliError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CliError::InvalidValue { error, .. } => Some(error),
+            CliError::MissingValue(_) | CliError::UnknownPath(_) => None,
+        }
+    }
+}
+
+/// Arguments for the [flags] of `T` and `--set`, to be added to a clap command
+#[cfg(feature = "clap")]
+pub fn clap_args<TW: WrapperSettings, T: VisitPath<TW> + Default + Serialize>() -> Vec<clap::Arg> {
+    use clap::{Arg, ArgAction};
+
+    let set = Arg::new("set")
+        .long("set")
+        .value_name("PATH=VALUE")
+        .help("Sets the value at a path, e.g. `limits.max=4`")
+        .action(ArgAction::Append);
+    let flags = flags::<TW, T>().into_iter().map(|flag| {
+        let arg = Arg::new(flag.name.clone())
+            .long(flag.name.clone())
+            .value_name(flag.value_name)
+            .help(flag.help())
+            .action(ArgAction::Append);
+        if flag.is_switch() {
+            arg.num_args(0..=1).default_missing_value("true")
+        } else {
+            arg
+        }
+    });
+    std::iter::once(set).chain(flags).collect()
+}
+
+/// Sets the values of the arguments added by [clap_args] explicitly in the default value of `T`.
+/// They are applied in the order they were given.
+#[cfg(feature = "clap")]
+pub fn from_clap_matches<TW: WrapperSettings, T: VisitPath<TW> + Default + Serialize>(
+    matches: &clap::ArgMatches,
+) -> Result<T, CliError> {
+    fn given<'a>(matches: &'a clap::ArgMatches, id: &str) -> Vec<(usize, &'a String)> {
+        let (Some(indices), Ok(Some(values))) =
+            (matches.indices_of(id), matches.try_get_many::<String>(id))
+        else {
+            return Vec::new();
+        };
+        indices.zip(values).collect()
+    }
+
+    let flags = flags::<TW, T>();
+    let mut overrides: Vec<(usize, Option<&CliFlag>, &String)> = given(matches, "set")
+        .into_iter()
+        .map(|(index, value)| (index, None, value))
+        .collect();
+    for flag in &flags {
+        let values = given(matches, &flag.name);
+        overrides.extend(
+            values
+                .into_iter()
+                .map(|(index, value)| (index, Some(flag), value)),
+        );
+    }
+    overrides.sort_by_key(|(index, _, _)| *index);
+
+    let mut value = T::default();
+    for (_, flag, text) in overrides {
+        match flag {
+            Some(flag) => set_at(&mut value, &flag.path, text, &format!("--{}", flag.name))?,
+            None => set_assignment(&mut value, text)?,
+        }
+    }
+    Ok(value)
+}
+
+/// A leaf found by [Collector]
+struct Leaf {
+    path: Vec<String>,
+    value_name: &'static str,
+    default: Option<String>,
+    /// Implicit fields are skipped when serializing, only extend lists among them get a flag
+    skipped: bool,
+}
+
+/// Serializer collecting the paths of the leaves of a value
+struct Collector<'a> {
+    path: Vec<String>,
+    /// The serialized value as JSON
+    default: Option<String>,
+    leaves: &'a mut Vec<Leaf>,
+}
+
+impl Collector<'_> {
+    fn leaf(self, value_name: &'static str) -> Result<(), CollectError> {
+        self.leaves.push(Leaf {
+            path: self.path,
+            value_name,
+            default: self.default,
+            skipped: false,
+        });
+        Ok(())
+    }
+
+    fn child<T: Serialize + ?Sized>(
+        &mut self,
+        segment: String,
+        value: &T,
+    ) -> Result<(), CollectError> {
+        let mut path = self.path.clone();
+        path.push(segment);
+        value.serialize(Collector {
+            path,
+            default: serde_json::to_string(value).ok(),
+            leaves: self.leaves,
+        })
+    }
+
+    /// Extend lists without changes are implicit in the default, so they are skipped.
+    /// Their elements are set as a whole as JSON, like other lists.
+    fn skipped(&mut self, segment: String) {
+        let mut path = self.path.clone();
+        path.push(segment);
+        self.leaves.push(Leaf {
+            path,
+            value_name: "JSON",
+            default: None,
+            skipped: true,
+        });
+    }
+
+    /// Enums and tuple structs are a leaf themselves, so they can also be set as a whole as JSON
+    fn whole(mut self) -> Self {
+        self.leaves.push(Leaf {
+            path: self.path.clone(),
+            value_name: "JSON",
+            default: self.default.take(),
+            skipped: false,
+        });
+        self
+    }
+
+    /// Enums can be set to other variants as JSON, the fields of the variant are collected below it
+    fn variant(self, variant: &str) -> Self {
+        let mut collector = self.whole();
+        collector.path.push(variant.to_owned());
+        collector
+    }
+}
+
+macro_rules! collect_leaf {
+    ($($method:ident($ty:ty) => $value_name:literal),* $(,)?) => {
+        $(
+            fn $method(self, _v: $ty) -> Result<(), CollectError> {
+                self.leaf($value_name)
+            }
+        )*
+    };
+}
+
+impl<'a> Serializer for Collector<'a> {
+    type Ok = ();
+    type Error = CollectError;
+    type SerializeSeq = Ignore;
+    type SerializeTuple = Ignore;
+    type SerializeTupleStruct = Fields<'a>;
+    type SerializeTupleVariant = Fields<'a>;
+    type SerializeMap = Fields<'a>;
+    type SerializeStruct = Fields<'a>;
+    type SerializeStructVariant = Fields<'a>;
+
+    collect_leaf! {
+        serialize_bool(bool) => "BOOL",
+        serialize_i8(i8) => "N",
+        serialize_i16(i16) => "N",
+        serialize_i32(i32) => "N",
+        serialize_i64(i64) => "N",
+        serialize_i128(i128) => "N",
+        serialize_u8(u8) => "N",
+        serialize_u16(u16) => "N",
+        serialize_u32(u32) => "N",
+        serialize_u64(u64) => "N",
+        serialize_u128(u128) => "N",
+        serialize_f32(f32) => "NUM",
+        serialize_f64(f64) => "NUM",
+        serialize_char(char) => "TEXT",
+        serialize_str(&str) => "TEXT",
+        serialize_bytes(&[u8]) => "TEXT",
+    }
+
+    fn serialize_none(self) -> Result<(), CollectError> {
+        self.leaf("VALUE")
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), CollectError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), CollectError> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), CollectError> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+    ) -> Result<(), CollectError> {
+        self.leaf("VARIANT")
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), CollectError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), CollectError> {
+        self.variant(variant).child("0".into(), value)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Ignore, CollectError> {
+        self.leaf("JSON").map(|()| Ignore)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Ignore, CollectError> {
+        self.leaf("JSON").map(|()| Ignore)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Fields<'a>, CollectError> {
+        Ok(Fields::new(self.whole()))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Fields<'a>, CollectError> {
+        Ok(Fields::new(self.variant(variant)))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Fields<'a>, CollectError> {
+        Ok(Fields::new(self))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Fields<'a>, CollectError> {
+        Ok(Fields::new(self))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Fields<'a>, CollectError> {
+        Ok(Fields::new(self.variant(variant)))
+    }
+}
+
+/// Children of a struct, tuple struct, map or enum variant, which are collected with their key or index
+struct Fields<'a> {
+    collector: Collector<'a>,
+    index: usize,
+    key: Option<String>,
+}
+
+impl<'a> Fields<'a> {
+    fn new(collector: Collector<'a>) -> Self {
+        Self {
+            collector,
+            index: 0,
+            key: None,
+        }
+    }
+}
+
+impl ser::SerializeTupleStruct for Fields<'_> {
+    type Ok = ();
+    type Error = CollectError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CollectError> {
+        self.index += 1;
+        self.collector.child((self.index - 1).to_string(), value)
+    }
+
+    fn end(self) -> Result<(), CollectError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleVariant for Fields<'_> {
+    type Ok = ();
+    type Error = CollectError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CollectError> {
+        self.index += 1;
+        self.collector.child((self.index - 1).to_string(), value)
+    }
+
+    fn end(self) -> Result<(), CollectError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for Fields<'_> {
+    type Ok = ();
+    type Error = CollectError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), CollectError> {
+        self.collector.child(key.to_owned(), value)
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), CollectError> {
+        self.collector.skipped(key.to_owned());
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), CollectError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStructVariant for Fields<'_> {
+    type Ok = ();
+    type Error = CollectError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), CollectError> {
+        self.collector.child(key.to_owned(), value)
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), CollectError> {
+        self.collector.skipped(key.to_owned());
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), CollectError> {
+        Ok(())
+    }
+}
+
+/// Entries with string or number keys are collected, other keys can't be a path segment
+impl ser::SerializeMap for Fields<'_> {
+    type Ok = ();
+    type Error = CollectError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), CollectError> {
+        self.key = match serde_json::to_value(key) {
+            Ok(serde_json::Value::String(key)) => Some(key),
+            Ok(serde_json::Value::Number(key)) => Some(key.to_string()),
+            _ => None,
+        };
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CollectError> {
+        match self.key.take() {
+            Some(key) => self.collector.child(key, value),
+            None => Ok(()),
+        }
+    }
+
+    fn end(self) -> Result<(), CollectError> {
+        Ok(())
+    }
+}
+
+/// Whether the value at the path is an extend list, which reads its name when deserializing
+fn is_extend_list<TW: WrapperSettings, T: VisitPath<TW>>(value: &mut T, path: &[&str]) -> bool {
+    struct Probe(bool);
+    impl<TW: WrapperSettings> PathVisitor<TW> for Probe {
+        fn visit<T: VisitPath<TW>>(&mut self, _value: &mut T) {
+            let _ = T::deserialize(ProbeDeserializer(&mut self.0));
+        }
+    }
+
+    let mut probe = Probe(false);
+    value.visit_path(path, &mut probe);
+    probe.0
+}
+
+/// Deserializer failing on any value, recording if an extend list was requested
+struct ProbeDeserializer<'a>(&'a mut bool);
+
+impl<'de> Deserializer<'de> for ProbeDeserializer<'_> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(de::Error::custom("probing the type"))
+    }
+
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.0 = name == EXTEND_LIST_NAME;
+        self.deserialize_any(visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct seq tuple tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// Elements of lists and tuples, which are set as a whole
+struct Ignore;
+
+impl ser::SerializeSeq for Ignore {
+    type Ok = ();
+    type Error = CollectError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, _value: &T) -> Result<(), CollectError> {
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), CollectError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTuple for Ignore {
+    type Ok = ();
+    type Error = CollectError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, _value: &T) -> Result<(), CollectError> {
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), CollectError> {
+        Ok(())
+    }
+}
+
+/// Error of a value which fails to serialize while collecting leaves
+#[derive(Debug)]
+struct CollectError(String);
+
+impl Display for CollectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for CollectError {}
+
+impl ser::Error for CollectError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
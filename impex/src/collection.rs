@@ -0,0 +1,119 @@
+use crate::{DefaultWrapperSettings, Impex, ImpexDefaults, ImpexEdit, ImpexMerge};
+
+/// Impex wrapper for collections, e.g. `Vec` or arrays.
+/// The collection tracks its own explicitness, so an explicitly empty or replaced collection
+/// stays explicit even if none of its elements are.
+///
+/// Dereferences to the wrapped collection, so elements can be read as usual.
+/// There is no mutable access, changes go through [Impex::edit] or [Impex::set_explicit],
+/// which mark the collection explicit at the same time.
+/// Explicit lists are saved and merged as a whole. To remove single elements of the default
+/// while following its other changes, use `#[impex(list = "extend")]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CollectionImpex<C> {
+    items: C,
+    is_explicit: bool,
+}
+
+impl<C> CollectionImpex<C> {
+    pub(crate) fn new(items: C, is_explicit: bool) -> Self {
+        Self { items, is_explicit }
+    }
+
+    pub(crate) fn set_explicit_flag(&mut self, is_explicit: bool) {
+        self.is_explicit = is_explicit;
+    }
+
+    pub(crate) fn is_explicit_flag(&self) -> bool {
+        self.is_explicit
+    }
+
+    /// Mutable access for the library, which keeps the explicit flag consistent itself
+    pub(crate) fn items_mut(&mut self) -> &mut C {
+        &mut self.items
+    }
+
+    pub fn into_inner(self) -> C {
+        self.items
+    }
+}
+
+/// Inherent methods for DefaultWrapperSettings (most common case), so lists of primitives
+/// don't need annotations. With other settings, use the [Impex] trait.
+impl<C> CollectionImpex<C>
+where
+    Self: Impex<DefaultWrapperSettings>,
+{
+    pub fn is_explicit(&self) -> bool {
+        Impex::<DefaultWrapperSettings>::is_explicit(self)
+    }
+
+    pub fn is_implicit(&self) -> bool {
+        !self.is_explicit()
+    }
+
+    pub fn set_explicit(&mut self, items: <Self as Impex<DefaultWrapperSettings>>::Value) {
+        Impex::<DefaultWrapperSettings>::set_explicit(self, items);
+    }
+
+    pub fn set_implicit(&mut self, items: <Self as Impex<DefaultWrapperSettings>>::Value) {
+        Impex::<DefaultWrapperSettings>::set_implicit(self, items);
+    }
+
+    /// See [Impex::edit]
+    pub fn edit(&mut self) -> ImpexEdit<'_, DefaultWrapperSettings, Self>
+    where
+        Self: ImpexDefaults<DefaultWrapperSettings> + ImpexMerge<DefaultWrapperSettings> + Clone,
+    {
+        Impex::<DefaultWrapperSettings>::edit(self)
+    }
+}
+
+impl<C> std::ops::Deref for CollectionImpex<C> {
+    type Target = C;
+
+    fn deref(&self) -> &Self::Target {
+        &self.items
+    }
+}
+
+impl<'a, C> IntoIterator for &'a CollectionImpex<C>
+where
+    &'a C: IntoIterator,
+{
+    type Item = <&'a C as IntoIterator>::Item;
+    type IntoIter = <&'a C as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
+impl<C: serde::Serialize> serde::Serialize for CollectionImpex<C> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.items.serialize(serializer)
+    }
+}
+
+/// Collections present in the document are explicit, even if they are empty
+impl<'de, C: serde::Deserialize<'de>> serde::Deserialize<'de> for CollectionImpex<C> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        C::deserialize(deserializer).map(|items| Self::new(items, true))
+    }
+}
+
+#[cfg(feature = "visitor")]
+impl<T, C> crate::Visitor<T> for CollectionImpex<C>
+where
+    C: crate::Visitor<T>,
+{
+    fn visit(&mut self, ctx: &mut T) {
+        self.items.visit(ctx);
+    }
+}
@@ -0,0 +1,353 @@
+//! Compact serialization for formats with a fixed layout, like bincode or postcard.
+//!
+//! The regular serde impls leave out implicit values, which needs a self-describing format.
+//! The compact form writes a presence bitmap for every struct instead, followed by only its explicit values.
+//! Reading it back restores the same explicit and implicit state.
+//!
+//! Use [Compact] to serialize a value, or `#[serde(with = "impex::compact")]` on a field.
+//! ```
+//! # use impex::{Compact, Impex};
+//! #[derive(Default, Impex)]
+//! struct Config {
+//!     threads: u32,
+//!     name: String,
+//! }
+//!
+//! let mut config: ConfigImpex = Default::default();
+//! config.threads.set_explicit(4);
+//! let bytes = bincode::serialize(&Compact::new(&config)).unwrap();
+//! let loaded: Compact<ConfigImpex> = bincode::deserialize(&bytes).unwrap();
+//! assert!(loaded.threads.is_explicit());
+//! assert!(loaded.name.is_implicit());
+//! ```
+
+use std::marker::PhantomData;
+
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{DeserializeSeed, Expected, SeqAccess},
+    ser::SerializeTuple,
+};
+
+use crate::{DefaultWrapperSettings, Impex, IntoImpex, WrapperSettings};
+
+/// Serialization of Impex types in the compact form, see [crate::compact]
+pub trait ImpexCompact<TW>: Impex<TW> + Sized {
+    /// Writes the value, which is treated as explicit.
+    /// Structs leave out their implicit fields and write a presence bitmap first.
+    fn serialize_compact<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>;
+
+    /// Reads a value written by [ImpexCompact::serialize_compact].
+    /// Values which were left out are implicit and taken from the default.
+    fn deserialize_compact<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>;
+}
+
+/// Wrapper to serialize a reference and deserialize a value in the compact form
+pub struct Compact<T, TW = DefaultWrapperSettings> {
+    value: T,
+    _phantom: PhantomData<TW>,
+}
+
+impl<T, TW> Compact<T, TW> {
+    pub fn new(value: T) -> Self {
+        Self {
+            value,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T, TW> std::ops::Deref for Compact<T, TW> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<T: ImpexCompact<TW>, TW> Serialize for Compact<&T, TW> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.value.serialize_compact(serializer)
+    }
+}
+
+impl<'de, T: ImpexCompact<TW>, TW> Deserialize<'de> for Compact<T, TW> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize_compact(deserializer).map(Self::new)
+    }
+}
+
+/// Serializes a field in the compact form, for `#[serde(with = "impex::compact")]`
+pub fn serialize<T: ImpexCompact<TW>, TW, S: Serializer>(
+    value: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    value.serialize_compact(serializer)
+}
+
+/// Deserializes a field in the compact form, for `#[serde(with = "impex::compact")]`
+pub fn deserialize<'de, T: ImpexCompact<TW>, TW, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<T, D::Error> {
+    T::deserialize_compact(deserializer)
+}
+
+/// Reads a value in the compact form, e.g. as element of a sequence
+#[doc(hidden)]
+pub struct CompactSeed<T, TW = DefaultWrapperSettings>(PhantomData<(T, TW)>);
+
+impl<T, TW> CompactSeed<T, TW> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T, TW> Default for CompactSeed<T, TW> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'de, T: ImpexCompact<TW>, TW> DeserializeSeed<'de> for CompactSeed<T, TW> {
+    type Value = T;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize_compact(deserializer)
+    }
+}
+
+/// Reads the next element of a sequence in the compact form, which must be present
+#[doc(hidden)]
+pub fn next_element<'de, A: SeqAccess<'de>, T: ImpexCompact<TW>, TW>(
+    seq: &mut A,
+    index: &mut usize,
+    expected: &dyn Expected,
+) -> Result<T, A::Error> {
+    let element = seq
+        .next_element_seed(CompactSeed::<T, TW>::new())?
+        .ok_or_else(|| serde::de::Error::invalid_length(*index, expected))?;
+    *index += 1;
+    Ok(element)
+}
+
+/// Makes a value read in the compact form implicit, e.g. a field of an enum variant missing in the bitmap
+#[doc(hidden)]
+pub fn into_implicit<V: IntoImpex<TW>, TW: WrapperSettings>(value: V::Impex) -> V::Impex {
+    value.into_value().into_implicit()
+}
+
+/// Bitmap of the explicit fields of a struct, written as `N` bits rounded up to whole bytes
+#[doc(hidden)]
+pub struct Presence<const N: usize>([bool; N]);
+
+impl<const N: usize> Presence<N> {
+    pub fn new(bits: [bool; N]) -> Self {
+        Self(bits)
+    }
+
+    pub fn is_present(&self, index: usize) -> bool {
+        self.0[index]
+    }
+
+    /// Number of fields which are present
+    pub fn count(&self) -> usize {
+        self.0.iter().filter(|x| **x).count()
+    }
+}
+
+impl<const N: usize> Serialize for Presence<N> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut tuple = serializer.serialize_tuple(N.div_ceil(8))?;
+        for bits in self.0.chunks(8) {
+            let byte = bits
+                .iter()
+                .enumerate()
+                .fold(0u8, |byte, (index, bit)| byte | (u8::from(*bit) << index));
+            tuple.serialize_element(&byte)?;
+        }
+        tuple.end()
+    }
+}
+
+impl<'de, const N: usize> Deserialize<'de> for Presence<N> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct PresenceVisitor<const N: usize>;
+
+        impl<'de, const N: usize> serde::de::Visitor<'de> for PresenceVisitor<N> {
+            type Value = Presence<N>;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(f, "a presence bitmap of {N} bits")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut bits = [false; N];
+                for (index, chunk) in bits.chunks_mut(8).enumerate() {
+                    let byte: u8 = seq
+                        .next_element()?
+                        .ok_or_else(|| serde::de::Error::invalid_length(index, &self))?;
+                    for (bit, value) in chunk.iter_mut().enumerate() {
+                        *value = byte & (1 << bit) != 0;
+                    }
+                }
+                Ok(Presence(bits))
+            }
+        }
+
+        deserializer.deserialize_tuple(N.div_ceil(8), PresenceVisitor::<N>)
+    }
+}
+
+/// Writes the elements of a list in the compact form
+pub(crate) struct CompactSeq<I, TW>(std::cell::Cell<Option<I>>, PhantomData<TW>);
+
+impl<I, TW> CompactSeq<I, TW> {
+    pub(crate) fn new(elements: I) -> Self {
+        Self(std::cell::Cell::new(Some(elements)), PhantomData)
+    }
+}
+
+impl<'a, I: Iterator<Item = &'a T>, T: ImpexCompact<TW> + 'a, TW> Serialize for CompactSeq<I, TW> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let elements = self.0.take().expect("CompactSeq is serialized only once");
+        serializer.collect_seq(elements.map(Compact::<_, TW>::new))
+    }
+}
+
+/// Reads the elements of a list written by [CompactSeq]
+pub(crate) struct CompactVecSeed<T, TW>(PhantomData<(T, TW)>);
+
+impl<T, TW> CompactVecSeed<T, TW> {
+    pub(crate) fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<'de, T: ImpexCompact<TW>, TW> DeserializeSeed<'de> for CompactVecSeed<T, TW> {
+    type Value = Vec<T>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, T: ImpexCompact<TW>, TW> serde::de::Visitor<'de> for CompactVecSeed<T, TW> {
+    type Value = Vec<T>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a sequence")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut elements = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(element) = seq.next_element_seed(CompactSeed::<T, TW>::new())? {
+            elements.push(element);
+        }
+        Ok(elements)
+    }
+}
+
+/// Reads an optional value in the compact form, where `None` is written as none
+pub(crate) struct CompactOptionSeed<T, TW>(PhantomData<(T, TW)>);
+
+impl<T, TW> CompactOptionSeed<T, TW> {
+    pub(crate) fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<'de, T: ImpexCompact<TW>, TW> DeserializeSeed<'de> for CompactOptionSeed<T, TW> {
+    type Value = Option<T>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_option(self)
+    }
+}
+
+impl<'de, T: ImpexCompact<TW>, TW> serde::de::Visitor<'de> for CompactOptionSeed<T, TW> {
+    type Value = Option<T>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("an optional value")
+    }
+
+    fn visit_none<E: serde::de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_unit<E: serde::de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        T::deserialize_compact(deserializer).map(Some)
+    }
+}
+
+/// Reads the entries of a map in the compact form, where `None` values are tombstones
+pub(crate) struct CompactEntriesSeed<K, V, TW>(PhantomData<(K, V, TW)>);
+
+impl<K, V, TW> CompactEntriesSeed<K, V, TW> {
+    pub(crate) fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<'de, K: Deserialize<'de>, V: ImpexCompact<TW>, TW> DeserializeSeed<'de>
+    for CompactEntriesSeed<K, V, TW>
+{
+    type Value = Vec<(K, Option<V>)>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, K: Deserialize<'de>, V: ImpexCompact<TW>, TW> serde::de::Visitor<'de>
+    for CompactEntriesSeed<K, V, TW>
+{
+    type Value = Vec<(K, Option<V>)>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a map")
+    }
+
+    fn visit_map<A: serde::de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut entries = Vec::with_capacity(map.size_hint().unwrap_or(0).min(4096));
+        while let Some(key) = map.next_key()? {
+            entries.push((key, map.next_value_seed(CompactOptionSeed::new())?));
+        }
+        Ok(entries)
+    }
+}
@@ -0,0 +1,138 @@
+use std::{fmt::Debug, marker::PhantomData};
+
+use crate::{Impex, ImpexPath, IntoImpex, WrapperSettings};
+
+/// Impex types which can compare their leaves with the leaves of a default value.
+/// Implementations are generated by `#[derive(Impex)]` as long as all leaves implement `PartialEq`.
+pub trait ImpexDefaults<TW: WrapperSettings>: Impex<TW> {
+    /// Compares the values only, ignoring if they are explicit or implicit
+    fn value_eq(&self, other: &Self) -> bool;
+
+    /// Marks every explicit leaf implicit, if its value equals the corresponding leaf in `default`
+    fn prune_defaults_with(&mut self, default: &Self);
+
+    /// Marks every explicit leaf implicit, if it equals `Default`.
+    /// Values which were set back to their default therefore follow future changes of the default again.
+    fn prune_defaults(&mut self)
+    where
+        Self: Default,
+    {
+        self.prune_defaults_with(&Self::default());
+    }
+
+    /// Creates an Impex from a fully expanded value.
+    /// Only the leaves which differ from `Default` are marked explicit.
+    fn from_value_inferred(value: Self::Value) -> Self
+    where
+        Self: Default + Sized,
+        Self::Value: IntoImpex<TW, Impex = Self>,
+    {
+        let mut result = value.into_explicit();
+        result.prune_defaults_with(&Self::default());
+        result
+    }
+
+    /// Formats the value, ignoring if it is explicit or implicit
+    fn fmt_value(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result;
+
+    /// Replaces every implicit leaf with the corresponding leaf of `new_default`, keeping explicit leaves.
+    /// Every implicit value which actually changed is added to `changes`.
+    fn rebase_defaults_at(
+        &mut self,
+        new_default: Self,
+        path: &ImpexPath,
+        changes: &mut Vec<DefaultChange>,
+    ) where
+        Self: Sized;
+
+    /// Replaces every implicit leaf with the corresponding value of `new_default`.
+    /// Returns the implicit values which actually changed, e.g. to log them.
+    fn rebase_defaults(&mut self, new_default: Self::Value) -> Vec<DefaultChange>
+    where
+        Self: Sized,
+        Self::Value: IntoImpex<TW, Impex = Self>,
+    {
+        let mut changes = Vec::new();
+        self.rebase_defaults_at(
+            new_default.into_implicit(),
+            &ImpexPath::root(),
+            &mut changes,
+        );
+        changes
+    }
+}
+
+/// Replaces an implicit value as a whole, if it differs from `new_default`.
+/// Used for values which can't be rebased leaf by leaf, e.g. lists or different enum variants.
+pub fn rebase_implicit_value<TW: WrapperSettings, T: ImpexDefaults<TW>>(
+    value: &mut T,
+    new_default: T,
+    path: &ImpexPath,
+    changes: &mut Vec<DefaultChange>,
+) {
+    if value.is_implicit() && !value.value_eq(&new_default) {
+        changes.push(DefaultChange::new::<TW, T>(path, value, &new_default));
+        *value = new_default;
+    }
+}
+
+/// Implicit value which changed when rebasing onto a new default
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DefaultChange {
+    pub path: ImpexPath,
+    pub old: String,
+    pub new: String,
+}
+
+impl DefaultChange {
+    pub fn new<TW: WrapperSettings, T: ImpexDefaults<TW>>(
+        path: &ImpexPath,
+        old: &T,
+        new: &T,
+    ) -> Self {
+        Self {
+            path: path.clone(),
+            old: format!("{:?}", DebugValue::<TW, T>::new(old)),
+            new: format!("{:?}", DebugValue::<TW, T>::new(new)),
+        }
+    }
+}
+
+impl std::fmt::Display for DefaultChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "default for {} changed from {} to {}",
+            self.path, self.old, self.new
+        )
+    }
+}
+
+/// Formats the value of an Impex with `Debug`, ignoring if it is explicit or implicit
+pub struct DebugValue<'a, TW, T>(&'a T, PhantomData<TW>);
+
+impl<'a, TW, T> DebugValue<'a, TW, T> {
+    pub fn new(value: &'a T) -> Self {
+        Self(value, PhantomData)
+    }
+}
+
+impl<TW: WrapperSettings, T: ImpexDefaults<TW>> Debug for DebugValue<'_, TW, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt_value(f)
+    }
+}
+
+/// Deserializes a (fully expanded) document and infers explicitness by comparing it with `Default`.
+/// Every value which equals its default becomes implicit and is therefore omitted on the next save.
+///
+/// Can be used with `#[serde(deserialize_with = "impex::deserialize_inferred")]`
+pub fn deserialize_inferred<'de, TW: WrapperSettings, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: ImpexDefaults<TW> + serde::Deserialize<'de> + Default,
+    D: serde::Deserializer<'de>,
+{
+    let mut result = T::deserialize(deserializer)?;
+    result.prune_defaults_with(&T::default());
+    Ok(result)
+}
@@ -0,0 +1,58 @@
+use std::marker::PhantomData;
+
+use crate::{ImpexDefaults, ImpexMerge, WrapperSettings};
+
+/// Guard returned by [crate::Impex::edit], giving mutable access to the value.
+/// When the guard is dropped, only the leaves whose value changed are marked explicit,
+/// the others keep their previous state. Lists are compared as a whole, like in [ImpexDefaults].
+pub struct ImpexEdit<'a, TW: WrapperSettings, I: ImpexDefaults<TW> + ImpexMerge<TW> + Clone> {
+    target: &'a mut I,
+    value: Option<I::Value>,
+    is_changed: bool,
+    _phantom: PhantomData<TW>,
+}
+
+impl<'a, TW: WrapperSettings, I: ImpexDefaults<TW> + ImpexMerge<TW> + Clone> ImpexEdit<'a, TW, I> {
+    pub(crate) fn new(target: &'a mut I) -> Self {
+        let value = target.clone().into_value();
+        Self {
+            target,
+            value: Some(value),
+            is_changed: false,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<TW: WrapperSettings, I: ImpexDefaults<TW> + ImpexMerge<TW> + Clone> std::ops::Deref
+    for ImpexEdit<'_, TW, I>
+{
+    type Target = I::Value;
+
+    fn deref(&self) -> &Self::Target {
+        self.value.as_ref().expect("Value is only taken on drop")
+    }
+}
+
+impl<TW: WrapperSettings, I: ImpexDefaults<TW> + ImpexMerge<TW> + Clone> std::ops::DerefMut
+    for ImpexEdit<'_, TW, I>
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.is_changed = true;
+        self.value.as_mut().expect("Value is only taken on drop")
+    }
+}
+
+impl<TW: WrapperSettings, I: ImpexDefaults<TW> + ImpexMerge<TW> + Clone> Drop
+    for ImpexEdit<'_, TW, I>
+{
+    fn drop(&mut self) {
+        if let (true, Some(value)) = (self.is_changed, self.value.take()) {
+            // Leaves which are unchanged become implicit in the overlay, so the merge skips them
+            let mut changed = self.target.clone();
+            changed.set_explicit(value);
+            changed.prune_defaults_with(self.target);
+            self.target.merge(changed);
+        }
+    }
+}
@@ -0,0 +1,200 @@
+//! Overrides from environment variables, enabled by the `env` feature.
+//!
+//! [EnvSource] maps variables like `APP__NUM_CORES=4` or `APP__ENUM_CONFIG__BAR__1=43`
+//! onto paths of an Impex type: the prefix is stripped and the rest is split at the separator.
+//! Segments match field and variant names regardless of case, so `NUM_CORES` finds `num_cores`
+//! and `BAR` finds the variant `Bar`.
+//!
+//! Values are parsed into the type at the path, so `4` is a number for a `u32` field and a string
+//! for a `String` field. Composite values like lists, structs or maps are decoded as JSON.
+//! Lists with `#[impex(list = "extend")]` can only be set as a whole, e.g. `APP__PATHS=["/opt"]`,
+//! their elements have no paths.
+//! Every variable sets its value explicitly, so the result can be merged as an overlay layer.
+//! ```
+//! # use impex::{Impex, ImpexMerge, env::EnvSource};
+//! #[derive(Default, Impex)]
+//! struct Config {
+//!     threads: u32,
+//!     name: String,
+//!     tags: Vec<String>,
+//! }
+//!
+//! let source = EnvSource::from_vars(
+//!     "APP",
+//!     [("APP__THREADS", "4"), ("APP__TAGS", r#"["a","b"]"#), ("APP__TYPO", "1")],
+//! );
+//! let overlay = source.overlay::<_, ConfigImpex>().unwrap();
+//! assert_eq!(["APP__TYPO"], overlay.unmapped.as_slice());
+//!
+//! let mut config = ConfigImpex::default();
+//! config.merge(overlay.value);
+//! assert_eq!(4, *config.threads);
+//! assert!(config.name.is_implicit());
+//! ```
+
+use std::{collections::BTreeMap, fmt::Display};
+
+use crate::{
+    ImpexPath, VisitPath, WrapperSettings,
+    text::{path_exists, set_from_text},
+};
+
+/// Environment variables with a common prefix, e.g. `APP__NUM_CORES`
+#[derive(Debug, Clone)]
+pub struct EnvSource {
+    prefix: String,
+    separator: String,
+    vars: BTreeMap<String, String>,
+}
+
+/// Values set by an [EnvSource], to be merged onto the loaded configuration
+#[derive(Debug, Clone)]
+pub struct EnvOverlay<T> {
+    /// The default value with the variables set explicitly
+    pub value: T,
+    /// Variables with the prefix which don't match a path
+    pub unmapped: Vec<String>,
+}
+
+impl EnvSource {
+    /// Reads the variables of the process, variables which are not valid unicode are ignored
+    pub fn new(prefix: impl Into<String>) -> Self {
+        let vars = std::env::vars_os()
+            .filter_map(|(key, value)| Some((key.into_string().ok()?, value.into_string().ok()?)));
+        Self::from_vars(prefix, vars)
+    }
+
+    /// Uses the given variables instead of the environment of the process
+    pub fn from_vars<K: Into<String>, V: Into<String>>(
+        prefix: impl Into<String>,
+        vars: impl IntoIterator<Item = (K, V)>,
+    ) -> Self {
+        Self {
+            prefix: prefix.into(),
+            separator: "__".into(),
+            vars: vars
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        }
+    }
+
+    /// Separator between the prefix and the segments of the path, `__` by default
+    pub fn with_separator(mut self, separator: impl Into<String>) -> Self {
+        self.separator = separator.into();
+        self
+    }
+
+    /// Sets the values of the variables explicitly in the default value of `T`.
+    /// Paths are resolved against the default, so they can only reach the variants
+    /// and entries it contains.
+    pub fn overlay<TW: WrapperSettings, T: VisitPath<TW> + Default>(
+        &self,
+    ) -> Result<EnvOverlay<T>, EnvError> {
+        let mut value = T::default();
+        let unmapped = self.apply(&mut value)?;
+        Ok(EnvOverlay { value, unmapped })
+    }
+
+    /// Sets the values of the variables explicitly in `target`.
+    /// Returns the variables with the prefix which don't match a path.
+    pub fn apply<TW: WrapperSettings, T: VisitPath<TW>>(
+        &self,
+        target: &mut T,
+    ) -> Result<Vec<String>, EnvError> {
+        let mut unmapped = Vec::new();
+        // Sorted by name, so values of parents are set before values of their children
+        for (name, text) in &self.vars {
+            let Some(segments) = self.segments(name) else {
+                continue;
+            };
+            let Some(path) = resolve_path(target, &segments) else {
+                unmapped.push(name.clone());
+                continue;
+            };
+            let path_segments: Vec<_> = path.segments().collect();
+            if let Some(Err(error)) = set_from_text(target, &path_segments, text) {
+                return Err(EnvError {
+                    variable: name.clone(),
+                    path,
+                    error,
+                });
+            }
+        }
+        Ok(unmapped)
+    }
+
+    /// Segments of a variable with the prefix, empty segments are kept to be reported as unmapped
+    fn segments<'a>(&self, name: &'a str) -> Option<Vec<&'a str>> {
+        let rest = name
+            .strip_prefix(self.prefix.as_str())?
+            .strip_prefix(self.separator.as_str())?;
+        Some(rest.split(self.separator.as_str()).collect())
+    }
+}
+
+/// Resolves the segments one by one, trying the segment in snake_case, PascalCase and as given
+fn resolve_path<TW: WrapperSettings, T: VisitPath<TW>>(
+    target: &mut T,
+    segments: &[&str],
+) -> Option<ImpexPath> {
+    let mut resolved: Vec<String> = Vec::new();
+    for segment in segments {
+        if segment.is_empty() {
+            return None;
+        }
+        let candidates = [
+            segment.to_lowercase(),
+            pascal_case(segment),
+            segment.to_string(),
+        ];
+        let found = candidates.into_iter().find(|candidate| {
+            let mut path: Vec<&str> = resolved.iter().map(String::as_str).collect();
+            path.push(candidate);
+            path_exists(target, &path)
+        })?;
+        resolved.push(found);
+    }
+    Some(
+        resolved
+            .into_iter()
+            .fold(ImpexPath::root(), |path, segment| path.join(segment)),
+    )
+}
+
+fn pascal_case(segment: &str) -> String {
+    segment
+        .split('_')
+        .flat_map(|word| {
+            let mut chars = word.chars();
+            let first = chars.next().map(|c| c.to_ascii_uppercase());
+            first
+                .into_iter()
+                .chain(chars.map(|c| c.to_ascii_lowercase()))
+        })
+        .collect()
+}
+
+/// Returned if the value of a variable can't be parsed into the type at its path
+#[derive(Debug)]
+pub struct EnvError {
+    pub variable: String,
+    pub path: ImpexPath,
+    pub error: serde_json::Error,
+}
+
+impl Display for EnvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid value of `{}` for `{}`: {}",
+            self.variable, self.path, self.error
+        )
+    }
+}
+
+impl std::error::Error for EnvError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
@@ -0,0 +1,613 @@
+use std::{collections::BTreeMap, marker::PhantomData};
+
+use crate::{
+    DebugValue, DefaultChange, DefaultWrapperSettings, Impex, ImpexCompact, ImpexDefaults,
+    ImpexMerge, ImpexPath, IntoImpex, PathVisitor, VisitPath, WrapperSettings,
+    compact::{CompactSeq, CompactVecSeed},
+};
+
+const PREPEND_KEY: &str = "^";
+const APPEND_KEY: &str = "+";
+const REMOVE_KEY: &str = "-";
+/// Name of the newtype struct read when deserializing,
+/// so the command line flags can find lists which are skipped in the default
+pub(crate) const EXTEND_LIST_NAME: &str = "ExtendListImpex";
+
+/// Impex wrapper for lists which extend the default instead of replacing it,
+/// created by `#[impex(list = "extend")]` on a `Vec` field.
+/// The list records its changes relative to the default, which are saved as
+/// `{"^": [prepended..], "+": [appended..], "-": [removed..]}`.
+/// When loading, the changes are applied to the current default in [Impex::fill_defaults],
+/// so new default elements of later releases still show up.
+///
+/// The list is explicit only if it records changes, so it stays implicit if it is
+/// set to the current default explicitly.
+/// Use [ExtendListImpex::push_explicit], [ExtendListImpex::prepend_explicit]
+/// and [ExtendListImpex::remove_explicit] to change it.
+pub struct ExtendListImpex<T, TW = DefaultWrapperSettings> {
+    /// Prepended elements, followed by the default elements and the appended ones
+    items: Vec<T>,
+    prepended: usize,
+    appended: usize,
+    removed: Vec<T>,
+    /// Set if the list replaces the changes of the list it is merged onto, e.g. if it was
+    /// loaded from all of its elements or in the overlay of [Impex::edit],
+    /// which contains the changes of the edited list
+    replaces: bool,
+    _phantom: PhantomData<TW>,
+}
+
+impl<T, TW> ExtendListImpex<T, TW> {
+    fn from_default(items: Vec<T>) -> Self {
+        Self {
+            items,
+            prepended: 0,
+            appended: 0,
+            removed: Vec::new(),
+            replaces: false,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn into_inner(self) -> Vec<T> {
+        self.items
+    }
+
+    /// Elements added in front of the default elements
+    pub fn prepended(&self) -> &[T] {
+        &self.items[..self.prepended]
+    }
+
+    /// Elements added after the default elements
+    pub fn appended(&self) -> &[T] {
+        &self.items[self.items.len() - self.appended..]
+    }
+
+    /// Elements of the default which were removed
+    pub fn removed(&self) -> &[T] {
+        &self.removed
+    }
+
+    /// Creates a list of loaded changes, the default elements are added by [Impex::fill_defaults]
+    fn from_changes(mut items: Vec<T>, appended: Vec<T>, removed: Vec<T>) -> Self {
+        let prepended = items.len();
+        let appended_len = appended.len();
+        items.extend(appended);
+        Self {
+            items,
+            prepended,
+            appended: appended_len,
+            removed,
+            replaces: false,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Splits the list into the prepended, default and appended elements
+    fn into_parts(mut self) -> (Vec<T>, Vec<T>, Vec<T>) {
+        let appended = self.items.split_off(self.items.len() - self.appended);
+        let default = self.items.split_off(self.prepended);
+        (self.items, default, appended)
+    }
+}
+
+/// Elements are compared by their value, as the list can't be changed by index
+impl<T: Impex<TW> + Clone, TW: WrapperSettings> ExtendListImpex<T, TW>
+where
+    T::Value: IntoImpex<TW, Impex = T> + PartialEq,
+{
+    /// Creates a list with `items` as its default elements.
+    /// `is_explicit` is ignored, as the list is only explicit if it records changes.
+    pub fn new(items: Vec<T::Value>, is_explicit: bool) -> Self {
+        let _ = is_explicit;
+        Self::from_default(items.into_iter().map(|x| x.into_implicit()).collect())
+    }
+
+    /// Appends an element after the default elements
+    pub fn push_explicit(&mut self, value: T::Value) {
+        self.push(value.into_explicit());
+    }
+
+    /// Adds an element in front of the default elements
+    pub fn prepend_explicit(&mut self, value: T::Value) {
+        self.prepend(value.into_explicit());
+    }
+
+    /// Removes every element equal to `value`.
+    /// Removed default elements are recorded, so they stay removed.
+    pub fn remove_explicit(&mut self, value: T::Value) {
+        self.remove(value.into_explicit());
+    }
+
+    fn same_value(a: &T, b: &T) -> bool {
+        a.clone().into_value() == b.clone().into_value()
+    }
+
+    fn push(&mut self, element: T) {
+        self.items.push(element);
+        self.appended += 1;
+    }
+
+    fn prepend(&mut self, element: T) {
+        self.items.insert(self.prepended, element);
+        self.prepended += 1;
+    }
+
+    fn remove(&mut self, element: T) {
+        let mut index = 0;
+        let mut removed_default = false;
+        while index < self.items.len() {
+            if !Self::same_value(&self.items[index], &element) {
+                index += 1;
+                continue;
+            }
+            if index < self.prepended {
+                self.prepended -= 1;
+            } else if index >= self.items.len() - self.appended {
+                self.appended -= 1;
+            } else {
+                removed_default = true;
+            }
+            self.items.remove(index);
+        }
+        if removed_default {
+            self.remove_default(element);
+        }
+    }
+
+    fn remove_default(&mut self, element: T) {
+        if !self.removed.iter().any(|x| Self::same_value(x, &element)) {
+            self.removed.push(element.into_value().into_explicit());
+        }
+    }
+
+    /// Finds a range of `items` which the default elements produce,
+    /// if the default elements with other values are removed.
+    /// The items are aligned with the longest common subsequence of both lists. The longest run
+    /// of aligned items is kept, until none of them shares its value with a default element
+    /// outside of the run, as removing that element would remove them as well.
+    /// Removed elements of the default can be restored, but only once,
+    /// as their position and count in the default is not known until the next load.
+    fn align(items: &[T], default: &[T], restorable: &[T]) -> (usize, usize) {
+        let values =
+            |list: &[T]| -> Vec<T::Value> { list.iter().map(|x| x.clone().into_value()).collect() };
+        let (items, default, restorable) = (values(items), values(default), values(restorable));
+        // Default elements with the same value share the index of the first one
+        let classes: Vec<usize> = default
+            .iter()
+            .map(|x| default.iter().position(|y| y == x).unwrap_or_default())
+            .collect();
+
+        // lengths[i * width + j] is the length of the common subsequence of items[i..] and default[j..]
+        let width = default.len() + 1;
+        let mut lengths = vec![0; (items.len() + 1) * width];
+        for i in (0..items.len()).rev() {
+            for j in (0..default.len()).rev() {
+                lengths[i * width + j] = if items[i] == default[j] {
+                    lengths[(i + 1) * width + j + 1] + 1
+                } else {
+                    lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+                };
+            }
+        }
+        let mut aligned = vec![None; items.len()];
+        let (mut i, mut j) = (0, 0);
+        while i < items.len() && j < default.len() {
+            if items[i] == default[j] {
+                aligned[i] = Some(j);
+                i += 1;
+                j += 1;
+            } else if lengths[i * width + j + 1] >= lengths[(i + 1) * width + j] {
+                j += 1;
+            } else {
+                i += 1;
+            }
+        }
+        let mut restored: Vec<&T::Value> = Vec::new();
+        let is_restored: Vec<bool> = items
+            .iter()
+            .map(|x| {
+                let is_restored =
+                    !default.contains(x) && restorable.contains(x) && !restored.contains(&x);
+                if is_restored {
+                    restored.push(x);
+                }
+                is_restored
+            })
+            .collect();
+
+        loop {
+            let mut best = (0, 0);
+            let mut start = 0;
+            for end in 0..=items.len() {
+                if end == items.len() || (aligned[end].is_none() && !is_restored[end]) {
+                    if end - start > best.1 - best.0 {
+                        best = (start, end);
+                    }
+                    start = end + 1;
+                }
+            }
+            let mut is_kept = vec![false; default.len()];
+            aligned[best.0..best.1]
+                .iter()
+                .flatten()
+                .for_each(|&j| is_kept[j] = true);
+            let mut is_removed = vec![false; default.len()];
+            (0..default.len())
+                .filter(|&j| !is_kept[j])
+                .for_each(|j| is_removed[classes[j]] = true);
+            let mut is_consistent = true;
+            for element in &mut aligned[best.0..best.1] {
+                if element.is_some_and(|j| is_removed[classes[j]]) {
+                    *element = None;
+                    is_consistent = false;
+                }
+            }
+            if is_consistent {
+                return best;
+            }
+        }
+    }
+
+    /// Applies the recorded changes to the default elements `default`
+    fn apply_to(&mut self, default: Vec<T>) {
+        let appended = self.items.split_off(self.items.len() - self.appended);
+        self.items.truncate(self.prepended);
+        self.items.extend(default.into_iter().filter(|x| {
+            !self
+                .removed
+                .iter()
+                .any(|removed| Self::same_value(removed, x))
+        }));
+        self.items.extend(appended);
+    }
+}
+
+impl<T: std::fmt::Debug, TW> std::fmt::Debug for ExtendListImpex<T, TW> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExtendListImpex")
+            .field("items", &self.items)
+            .field("prepended", &self.prepended)
+            .field("appended", &self.appended)
+            .field("removed", &self.removed)
+            .field("replaces", &self.replaces)
+            .finish()
+    }
+}
+
+impl<T: Clone, TW> Clone for ExtendListImpex<T, TW> {
+    fn clone(&self) -> Self {
+        Self {
+            items: self.items.clone(),
+            prepended: self.prepended,
+            appended: self.appended,
+            removed: self.removed.clone(),
+            replaces: self.replaces,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: PartialEq, TW> PartialEq for ExtendListImpex<T, TW> {
+    fn eq(&self, other: &Self) -> bool {
+        self.items == other.items
+            && self.prepended == other.prepended
+            && self.appended == other.appended
+            && self.removed == other.removed
+            && self.replaces == other.replaces
+    }
+}
+
+impl<T: Eq, TW> Eq for ExtendListImpex<T, TW> {}
+
+impl<T, TW> Default for ExtendListImpex<T, TW> {
+    fn default() -> Self {
+        Self::from_default(Vec::new())
+    }
+}
+
+/// Only gives read access, changes need to be recorded with the `*_explicit` methods
+impl<T, TW> std::ops::Deref for ExtendListImpex<T, TW> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        &self.items
+    }
+}
+
+impl<'a, T, TW> IntoIterator for &'a ExtendListImpex<T, TW> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter()
+    }
+}
+
+impl<T: Impex<TW> + Clone, TW: WrapperSettings> Impex<TW> for ExtendListImpex<T, TW>
+where
+    T::Value: IntoImpex<TW, Impex = T> + PartialEq,
+{
+    type Value = Vec<T::Value>;
+
+    fn is_explicit(&self) -> bool {
+        self.prepended > 0 || self.appended > 0 || !self.removed.is_empty()
+    }
+
+    fn into_value(self) -> Self::Value {
+        self.items.into_iter().map(|x| x.into_value()).collect()
+    }
+
+    /// Setting the list explicitly records the difference to the current default elements.
+    /// The longest part of the new list which can be left to the default is kept,
+    /// the elements in front of it are prepended and the ones after it appended.
+    fn set_impex(&mut self, v: Self::Value, is_explicit: bool) {
+        let new = Self::new(v, false);
+        if !is_explicit {
+            *self = new;
+            return;
+        }
+
+        let restorable = std::mem::take(&mut self.removed);
+        let (_, default, _) = std::mem::take(self).into_parts();
+        let mut items = new.items;
+        let (start, end) = Self::align(&items, &default, &restorable);
+        let appended = items.split_off(end);
+        let kept = items.split_off(start);
+        *self = Self::from_default(kept);
+        for element in default.into_iter().chain(restorable) {
+            if !self.items.iter().any(|x| Self::same_value(x, &element)) {
+                self.remove_default(element);
+            }
+        }
+        for element in items {
+            self.prepend(element.into_value().into_explicit());
+        }
+        for element in appended {
+            self.push(element.into_value().into_explicit());
+        }
+    }
+
+    /// A list loaded from all of its elements records the difference to `default`
+    fn fill_defaults(&mut self, default: Self) {
+        if self.replaces {
+            let value = std::mem::take(self).into_value();
+            *self = default;
+            self.set_explicit(value);
+            self.replaces = true;
+            return;
+        }
+        let (_, default, _) = default.into_parts();
+        self.apply_to(default);
+    }
+}
+
+/// The recorded changes are kept, so only the default elements change
+impl<T: ImpexDefaults<TW> + Clone, TW: WrapperSettings> ImpexDefaults<TW> for ExtendListImpex<T, TW>
+where
+    T::Value: IntoImpex<TW, Impex = T> + PartialEq,
+{
+    fn value_eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().zip(other).all(|(a, b)| a.value_eq(b))
+    }
+
+    /// Removals of elements which are not in `default` are dropped, as there is nothing to remove.
+    /// If `default` records changes itself, they can't be subtracted from the own ones,
+    /// so the list replaces them when it is merged.
+    fn prune_defaults_with(&mut self, default: &Self) {
+        if self.value_eq(default) {
+            let (_, default, _) = std::mem::take(self).into_parts();
+            *self = Self::from_default(default);
+            return;
+        }
+        self.replaces = default.is_explicit();
+        let default = &default.items[default.prepended..default.items.len() - default.appended];
+        self.removed
+            .retain(|x| default.iter().any(|y| y.value_eq(x)));
+    }
+
+    fn fmt_value(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.iter().map(DebugValue::<TW, T>::new))
+            .finish()
+    }
+
+    fn rebase_defaults_at(
+        &mut self,
+        new_default: Self,
+        path: &ImpexPath,
+        changes: &mut Vec<DefaultChange>,
+    ) {
+        let old = format!("{:?}", DebugValue::<TW, Self>::new(self));
+        let (_, new_default, _) = new_default.into_parts();
+        self.apply_to(new_default);
+        let new = format!("{:?}", DebugValue::<TW, Self>::new(self));
+        if old != new {
+            changes.push(DefaultChange {
+                path: path.clone(),
+                old,
+                new,
+            });
+        }
+    }
+}
+
+/// The changes recorded by the overlay are applied on top of the own changes,
+/// unless the overlay replaces them
+impl<T: Impex<TW> + Clone, TW: WrapperSettings> ImpexMerge<TW> for ExtendListImpex<T, TW>
+where
+    T::Value: IntoImpex<TW, Impex = T> + PartialEq,
+{
+    fn merge(&mut self, mut overlay: Self) {
+        if overlay.replaces {
+            self.set_explicit(overlay.into_value());
+            self.replaces = true;
+            return;
+        }
+        let removed = std::mem::take(&mut overlay.removed);
+        let (prepended, _, appended) = overlay.into_parts();
+        for element in removed {
+            self.remove(element);
+        }
+        for element in prepended {
+            self.prepend(element);
+        }
+        for element in appended {
+            self.push(element);
+        }
+    }
+}
+
+/// Elements have no path, as changing one by index can't be recorded relative to the default
+impl<T: VisitPath<TW> + Clone, TW: WrapperSettings> VisitPath<TW> for ExtendListImpex<T, TW>
+where
+    T::Value: IntoImpex<TW, Impex = T> + PartialEq,
+{
+    fn visit_path<V: PathVisitor<TW>>(&mut self, path: &[&str], visitor: &mut V) -> bool {
+        if path.is_empty() {
+            visitor.visit(self);
+        }
+        path.is_empty()
+    }
+}
+
+impl<T: serde::Serialize, TW> serde::Serialize for ExtendListImpex<T, TW> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let changes = [
+            (PREPEND_KEY, self.prepended()),
+            (APPEND_KEY, self.appended()),
+            (REMOVE_KEY, self.removed()),
+        ];
+        serializer.collect_map(changes.into_iter().filter(|(_, x)| !x.is_empty()))
+    }
+}
+
+/// Loads only the changes, the default elements are added by [Impex::fill_defaults].
+/// A list of all elements is loaded like [Impex::set_explicit], replacing the changes
+/// of the list it is merged onto.
+impl<'de, T: serde::Deserialize<'de>, TW> serde::Deserialize<'de> for ExtendListImpex<T, TW> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(EXTEND_LIST_NAME, ChangesVisitor(PhantomData))
+    }
+}
+
+/// Reads the changes of an [ExtendListImpex] or all of its elements
+struct ChangesVisitor<T, TW>(PhantomData<(T, TW)>);
+
+impl<'de, T: serde::Deserialize<'de>, TW> serde::de::Visitor<'de> for ChangesVisitor<T, TW> {
+    type Value = ExtendListImpex<T, TW>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a map of the prepended, appended and removed elements or a list")
+    }
+
+    fn visit_newtype_struct<D: serde::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_map<A: serde::de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        use serde::Deserialize;
+        let mut changes = BTreeMap::<String, Vec<T>>::deserialize(
+            serde::de::value::MapAccessDeserializer::new(map),
+        )?;
+        let mut take = |key: &str| changes.remove(key).unwrap_or_default();
+        let prepended = take(PREPEND_KEY);
+        let appended = take(APPEND_KEY);
+        let removed = take(REMOVE_KEY);
+        if let Some(key) = changes.keys().next() {
+            return Err(serde::de::Error::unknown_field(
+                key,
+                &[PREPEND_KEY, APPEND_KEY, REMOVE_KEY],
+            ));
+        }
+        Ok(ExtendListImpex::from_changes(prepended, appended, removed))
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+        use serde::Deserialize;
+        let items = Vec::<T>::deserialize(serde::de::value::SeqAccessDeserializer::new(seq))?;
+        let mut list = ExtendListImpex::from_changes(Vec::new(), items, Vec::new());
+        list.replaces = true;
+        Ok(list)
+    }
+}
+
+/// The prepended, appended and removed elements are written as a tuple of three lists
+impl<T: ImpexCompact<TW> + Clone, TW: WrapperSettings> ImpexCompact<TW> for ExtendListImpex<T, TW>
+where
+    T::Value: IntoImpex<TW, Impex = T> + PartialEq,
+{
+    fn serialize_compact<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeTuple;
+        let mut tuple = serializer.serialize_tuple(3)?;
+        for changes in [self.prepended(), self.appended(), self.removed()] {
+            tuple.serialize_element(&CompactSeq::<_, TW>::new(changes.iter()))?;
+        }
+        tuple.end()
+    }
+
+    fn deserialize_compact<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        deserializer.deserialize_tuple(3, CompactChangesVisitor(PhantomData))
+    }
+}
+
+/// Reads the changes of an [ExtendListImpex] in the compact form
+struct CompactChangesVisitor<T, TW>(PhantomData<(T, TW)>);
+
+impl<'de, T: ImpexCompact<TW>, TW> serde::de::Visitor<'de> for CompactChangesVisitor<T, TW> {
+    type Value = ExtendListImpex<T, TW>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("the prepended, appended and removed elements")
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut next = |index| {
+            seq.next_element_seed(CompactVecSeed::<T, TW>::new())?
+                .ok_or_else(|| serde::de::Error::invalid_length(index, &self))
+        };
+        let prepended = next(0)?;
+        let appended = next(1)?;
+        let removed = next(2)?;
+        Ok(ExtendListImpex::from_changes(prepended, appended, removed))
+    }
+}
+
+/// The changes are written as an object of lists, the default elements are not part of it
+#[cfg(feature = "schema")]
+impl<T: crate::schema::ImpexSchema<TW> + Clone, TW: WrapperSettings> crate::schema::ImpexSchema<TW>
+    for ExtendListImpex<T, TW>
+where
+    T::Value: IntoImpex<TW, Impex = T> + PartialEq,
+{
+    fn json_schema() -> crate::schema::Schema {
+        let list = serde_json::json!({ "type": "array", "items": T::json_schema() });
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                PREPEND_KEY: list,
+                APPEND_KEY: list,
+                REMOVE_KEY: list,
+            },
+            "additionalProperties": false,
+        })
+    }
+}
+
+#[cfg(feature = "visitor")]
+impl<T, U: crate::Visitor<T>, TW> crate::Visitor<T> for ExtendListImpex<U, TW> {
+    fn visit(&mut self, ctx: &mut T) {
+        self.items.iter_mut().for_each(|x| x.visit(ctx));
+    }
+}
@@ -0,0 +1,791 @@
+//! Comment- and layout-preserving round trips of JSONC and JSON5 documents, enabled by the `jsonc` feature.
+//!
+//! [JsoncDocument] keeps the original text next to the loaded value.
+//! Saving patches only the changes into the text:
+//! - changed values are replaced in place
+//! - keys whose values became implicit are removed, together with the comments above them
+//! - new keys are appended to their object, matching its indentation
+//!
+//! Comments, key order, quoting and formatting of everything else are kept as they were.
+//! ```
+//! # use impex::{Impex, jsonc::JsoncDocument};
+//! #[derive(Default, Impex)]
+//! struct Config {
+//!     threads: u32,
+//!     name: String,
+//! }
+//!
+//! let text = "{\n  // Worker threads\n  threads: 4, // tuned\n}\n";
+//! let mut document = JsoncDocument::<ConfigImpex>::parse(text).unwrap();
+//! document.name.set_explicit("worker".into());
+//! assert_eq!(
+//!     "{\n  // Worker threads\n  threads: 4, // tuned\n  \"name\": \"worker\",\n}\n",
+//!     document.save().unwrap()
+//! );
+//! ```
+
+use std::{fmt::Display, ops::Range};
+
+use serde::{Serialize, de::DeserializeOwned};
+
+/// A JSONC or JSON5 document together with the value loaded from it
+#[derive(Debug, Clone)]
+pub struct JsoncDocument<T> {
+    text: String,
+    value: T,
+}
+
+impl<T: Serialize + DeserializeOwned> JsoncDocument<T> {
+    /// Parses the document and loads the value from it
+    pub fn parse(text: &str) -> Result<Self, JsoncError> {
+        let root = Parser::new(text).parse_document()?;
+        let value = T::deserialize(root.to_json()).map_err(JsoncError::Load)?;
+        Ok(Self {
+            text: text.to_owned(),
+            value,
+        })
+    }
+
+    /// Patches the explicit values into the text, keeping its comments and formatting
+    pub fn update(&mut self) -> Result<(), JsoncError> {
+        let saved = serde_json::to_string(&self.value).map_err(JsoncError::Save)?;
+        let saved_root = Parser::new(&saved)
+            .parse_document()
+            .expect("serde_json writes valid JSON");
+        let root = Parser::new(&self.text)
+            .parse_document()
+            .expect("the text was parsed before");
+
+        let mut editor = Editor::new(&self.text, &saved);
+        editor.update_value(&root, &saved_root, "  ");
+        self.text = editor.apply();
+        Ok(())
+    }
+
+    /// Updates the text and returns it
+    pub fn save(&mut self) -> Result<String, JsoncError> {
+        self.update()?;
+        Ok(self.text.clone())
+    }
+}
+
+impl<T> JsoncDocument<T> {
+    /// The text as it was parsed or last updated
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> std::ops::Deref for JsoncDocument<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<T> std::ops::DerefMut for JsoncDocument<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
+
+/// Returned if a JSONC document can't be parsed, loaded or saved
+#[derive(Debug)]
+pub enum JsoncError {
+    Parse {
+        message: &'static str,
+        line: usize,
+        column: usize,
+    },
+    Load(serde_json::Error),
+    Save(serde_json::Error),
+}
+
+impl Display for JsoncError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JsoncError::Parse {
+                message,
+                line,
+                column,
+            } => write!(f, "invalid JSONC at {line}:{column}: {message}"),
+            JsoncError::Load(error) => write!(f, "failed to load JSONC: {error}"),
+            JsoncError::Save(error) => write!(f, "failed to save JSONC: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for JsoncError {}
+
+/// A parsed value with its position in the text
+struct Node {
+    span: Range<usize>,
+    kind: Kind,
+}
+
+enum Kind {
+    Null,
+    Bool(bool),
+    Number(serde_json::Number),
+    String(String),
+    Array(Vec<Node>),
+    Object(Vec<Member>),
+}
+
+struct Member {
+    key: String,
+    key_start: usize,
+    value: Node,
+    comma: Option<usize>,
+}
+
+impl Node {
+    fn to_json(&self) -> serde_json::Value {
+        match &self.kind {
+            Kind::Null => serde_json::Value::Null,
+            Kind::Bool(x) => serde_json::Value::Bool(*x),
+            Kind::Number(x) => serde_json::Value::Number(x.clone()),
+            Kind::String(x) => serde_json::Value::String(x.clone()),
+            Kind::Array(elements) => elements.iter().map(Node::to_json).collect(),
+            Kind::Object(members) => members
+                .iter()
+                .map(|member| (member.key.clone(), member.value.to_json()))
+                .collect::<serde_json::Map<_, _>>()
+                .into(),
+        }
+    }
+}
+
+/// Compares values independent of their formatting
+fn value_eq(a: &Kind, b: &Kind) -> bool {
+    match (a, b) {
+        (Kind::Null, Kind::Null) => true,
+        (Kind::Bool(a), Kind::Bool(b)) => a == b,
+        (Kind::Number(a), Kind::Number(b)) => {
+            a == b || ((a.is_f64() || b.is_f64()) && a.as_f64() == b.as_f64())
+        }
+        (Kind::String(a), Kind::String(b)) => a == b,
+        (Kind::Array(a), Kind::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| value_eq(&a.kind, &b.kind))
+        }
+        (Kind::Object(a), Kind::Object(b)) => {
+            a.len() == b.len()
+                && a.iter().all(|a| {
+                    b.iter()
+                        .any(|b| a.key == b.key && value_eq(&a.value.kind, &b.value.kind))
+                })
+        }
+        _ => false,
+    }
+}
+
+/// Parser for JSON with comments and the JSON5 extensions, which keeps the positions of the values
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { text, pos: 0 }
+    }
+
+    fn error(&self, message: &'static str) -> JsoncError {
+        let before = &self.text[..self.pos];
+        let line_start = before.rfind('\n').map_or(0, |x| x + 1);
+        JsoncError::Parse {
+            message,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn expect(&mut self, c: char, message: &'static str) -> Result<(), JsoncError> {
+        if self.peek() != Some(c) {
+            return Err(self.error(message));
+        }
+        self.bump();
+        Ok(())
+    }
+
+    fn skip_trivia(&mut self) -> Result<(), JsoncError> {
+        loop {
+            let rest = &self.text[self.pos..];
+            if rest.starts_with("//") {
+                self.pos += rest.find('\n').unwrap_or(rest.len());
+            } else if let Some(comment) = rest.strip_prefix("/*") {
+                let end = comment
+                    .find("*/")
+                    .ok_or_else(|| self.error("unterminated comment"))?;
+                self.pos += end + 4;
+            } else if self.peek().is_some_and(char::is_whitespace) {
+                self.bump();
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn parse_document(&mut self) -> Result<Node, JsoncError> {
+        self.skip_trivia()?;
+        let root = self.parse_value()?;
+        self.skip_trivia()?;
+        if self.pos < self.text.len() {
+            return Err(self.error("trailing characters"));
+        }
+        Ok(root)
+    }
+
+    fn parse_value(&mut self) -> Result<Node, JsoncError> {
+        let start = self.pos;
+        let kind = match self.peek() {
+            Some('{') => self.parse_object()?,
+            Some('[') => self.parse_array()?,
+            Some('"' | '\'') => Kind::String(self.parse_string()?),
+            Some(c) if matches!(c, '-' | '+' | '.') || c.is_ascii_digit() => {
+                Kind::Number(self.parse_number()?)
+            }
+            Some(c) if is_identifier_start(c) => match self.parse_identifier() {
+                "null" => Kind::Null,
+                "true" => Kind::Bool(true),
+                "false" => Kind::Bool(false),
+                "Infinity" | "NaN" => {
+                    self.pos = start;
+                    return Err(self.error("non-finite numbers are not supported"));
+                }
+                _ => {
+                    self.pos = start;
+                    return Err(self.error("expected a value"));
+                }
+            },
+            _ => return Err(self.error("expected a value")),
+        };
+        Ok(Node {
+            span: start..self.pos,
+            kind,
+        })
+    }
+
+    fn parse_object(&mut self) -> Result<Kind, JsoncError> {
+        self.bump();
+        let mut members: Vec<Member> = Vec::new();
+        loop {
+            self.skip_trivia()?;
+            if self.peek() == Some('}') {
+                self.bump();
+                return Ok(Kind::Object(members));
+            }
+            if members.last().is_some_and(|member| member.comma.is_none()) {
+                return Err(self.error("expected `,` or `}`"));
+            }
+
+            let key_start = self.pos;
+            let key = match self.peek() {
+                Some('"' | '\'') => self.parse_string()?,
+                Some(c) if is_identifier_start(c) => self.parse_identifier().to_owned(),
+                _ => return Err(self.error("expected a key")),
+            };
+            self.skip_trivia()?;
+            self.expect(':', "expected `:`")?;
+            self.skip_trivia()?;
+            let value = self.parse_value()?;
+            self.skip_trivia()?;
+            let comma = (self.peek() == Some(',')).then_some(self.pos);
+            if comma.is_some() {
+                self.bump();
+            }
+            members.push(Member {
+                key,
+                key_start,
+                value,
+                comma,
+            });
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<Kind, JsoncError> {
+        self.bump();
+        let mut elements = Vec::new();
+        let mut has_comma = true;
+        loop {
+            self.skip_trivia()?;
+            if self.peek() == Some(']') {
+                self.bump();
+                return Ok(Kind::Array(elements));
+            }
+            if !has_comma {
+                return Err(self.error("expected `,` or `]`"));
+            }
+            elements.push(self.parse_value()?);
+            self.skip_trivia()?;
+            has_comma = self.peek() == Some(',');
+            if has_comma {
+                self.bump();
+            }
+        }
+    }
+
+    fn parse_identifier(&mut self) -> &'a str {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| is_identifier_start(c) || c.is_alphanumeric())
+        {
+            self.bump();
+        }
+        &self.text[start..self.pos]
+    }
+
+    fn parse_string(&mut self) -> Result<String, JsoncError> {
+        let quote = self.bump();
+        let mut value = String::new();
+        loop {
+            match self.bump() {
+                None | Some('\n') => return Err(self.error("unterminated string")),
+                Some(c) if Some(c) == quote => return Ok(value),
+                Some('\\') => match self.bump() {
+                    Some('b') => value.push('\u{8}'),
+                    Some('f') => value.push('\u{c}'),
+                    Some('n') => value.push('\n'),
+                    Some('r') => value.push('\r'),
+                    Some('t') => value.push('\t'),
+                    Some('v') => value.push('\u{b}'),
+                    Some('0') => value.push('\0'),
+                    Some('x') => {
+                        let code = self.parse_hex(2)?;
+                        value.push(char::from_u32(code).expect("two hex digits are a char"));
+                    }
+                    Some('u') => value.push(self.parse_unicode_escape()?),
+                    // Line continuations
+                    Some('\n' | '\u{2028}' | '\u{2029}') => {}
+                    Some('\r') => {
+                        if self.peek() == Some('\n') {
+                            self.bump();
+                        }
+                    }
+                    Some(c) => value.push(c),
+                    None => return Err(self.error("unterminated string")),
+                },
+                Some(c) => value.push(c),
+            }
+        }
+    }
+
+    fn parse_hex(&mut self, digits: usize) -> Result<u32, JsoncError> {
+        let hex = self
+            .text
+            .get(self.pos..self.pos + digits)
+            .filter(|x| x.chars().all(|c| c.is_ascii_hexdigit()))
+            .ok_or_else(|| self.error("invalid escape"))?;
+        self.pos += digits;
+        Ok(u32::from_str_radix(hex, 16).expect("hex digits were checked"))
+    }
+
+    fn parse_unicode_escape(&mut self) -> Result<char, JsoncError> {
+        let high = self.parse_hex(4)?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if !self.text[self.pos..].starts_with("\\u") {
+                return Err(self.error("unpaired surrogate"));
+            }
+            self.pos += 2;
+            let low = self.parse_hex(4)?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.error("unpaired surrogate"));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+        char::from_u32(code).ok_or_else(|| self.error("unpaired surrogate"))
+    }
+
+    fn parse_number(&mut self) -> Result<serde_json::Number, JsoncError> {
+        let start = self.pos;
+        let negative = self.peek() == Some('-');
+        if matches!(self.peek(), Some('-' | '+')) {
+            self.bump();
+        }
+        if self.peek().is_some_and(is_identifier_start) {
+            let identifier = self.parse_identifier();
+            self.pos = start;
+            return Err(match identifier {
+                "Infinity" | "NaN" => self.error("non-finite numbers are not supported"),
+                _ => self.error("invalid number"),
+            });
+        }
+
+        let rest = &self.text[self.pos..];
+        if rest.starts_with("0x") || rest.starts_with("0X") {
+            self.pos += 2;
+            let digits_start = self.pos;
+            while self.peek().is_some_and(|c| c.is_ascii_hexdigit()) {
+                self.bump();
+            }
+            let value = u64::from_str_radix(&self.text[digits_start..self.pos], 16)
+                .map_err(|_| self.error("invalid number"))?;
+            return if negative {
+                0i64.checked_sub_unsigned(value)
+                    .map(Into::into)
+                    .ok_or_else(|| self.error("number out of range"))
+            } else {
+                Ok(value.into())
+            };
+        }
+
+        let mut has_digits = false;
+        let mut is_float = false;
+        while let Some(c) = self.peek() {
+            match c {
+                '0'..='9' => has_digits = true,
+                '.' => is_float = true,
+                'e' | 'E' => {
+                    is_float = true;
+                    self.bump();
+                    if matches!(self.peek(), Some('-' | '+')) {
+                        self.bump();
+                    }
+                    continue;
+                }
+                _ => break,
+            }
+            self.bump();
+        }
+        let literal = self.text[start..self.pos].trim_start_matches('+');
+        if !has_digits {
+            return Err(self.error("invalid number"));
+        }
+        if !is_float {
+            if let Ok(value) = literal.parse::<u64>() {
+                return Ok(value.into());
+            }
+            if let Ok(value) = literal.parse::<i64>() {
+                return Ok(value.into());
+            }
+        }
+        literal
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .ok_or_else(|| self.error("invalid number"))
+    }
+}
+
+fn is_identifier_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_' || c == '$'
+}
+
+/// Collects replacements of text ranges to patch a document with a saved value
+struct Editor<'a> {
+    text: &'a str,
+    saved: &'a str,
+    newline: &'static str,
+    edits: Vec<(Range<usize>, String)>,
+}
+
+impl<'a> Editor<'a> {
+    fn new(text: &'a str, saved: &'a str) -> Self {
+        Self {
+            text,
+            saved,
+            newline: if text.contains("\r\n") { "\r\n" } else { "\n" },
+            edits: Vec::new(),
+        }
+    }
+
+    fn apply(mut self) -> String {
+        self.edits.sort_by_key(|(range, _)| range.start);
+        let mut result = String::with_capacity(self.text.len());
+        let mut cursor = 0;
+        for (range, replacement) in &self.edits {
+            result.push_str(&self.text[cursor..range.start.max(cursor)]);
+            result.push_str(replacement);
+            cursor = range.end.max(cursor);
+        }
+        result.push_str(&self.text[cursor..]);
+        result
+    }
+
+    fn replace(&mut self, range: Range<usize>, replacement: String) {
+        self.edits.push((range, replacement));
+    }
+
+    fn update_value(&mut self, node: &Node, saved: &Node, unit: &str) {
+        match (&node.kind, &saved.kind) {
+            (Kind::Object(members), Kind::Object(saved_members)) => {
+                self.update_object(node, members, saved, saved_members, unit)
+            }
+            (Kind::Array(elements), Kind::Array(saved_elements))
+                if elements.len() == saved_elements.len() =>
+            {
+                for (element, saved) in elements.iter().zip(saved_elements) {
+                    self.update_value(element, saved, unit);
+                }
+            }
+            _ if value_eq(&node.kind, &saved.kind) => {}
+            _ => {
+                let indent = line_indent(self.text, node.span.start);
+                let replacement = self.format(saved, indent, unit);
+                self.replace(node.span.clone(), replacement);
+            }
+        }
+    }
+
+    fn update_object(
+        &mut self,
+        node: &Node,
+        members: &[Member],
+        saved: &Node,
+        saved_members: &[Member],
+        unit: &str,
+    ) {
+        let saved_value = |key: &str| {
+            saved_members
+                .iter()
+                .find(|member| member.key == key)
+                .map(|member| &member.value)
+        };
+        let added: Vec<_> = saved_members
+            .iter()
+            .filter(|saved| !members.iter().any(|member| member.key == saved.key))
+            .collect();
+        let Some(last_kept) = members
+            .iter()
+            .rposition(|member| saved_value(&member.key).is_some())
+        else {
+            if !members.is_empty() || !added.is_empty() {
+                let indent = line_indent(self.text, node.span.start);
+                let replacement = self.format(saved, indent, unit);
+                self.replace(node.span.clone(), replacement);
+            }
+            return;
+        };
+
+        let multiline = self
+            .member_start(node.span.start, members[0].key_start)
+            .is_some();
+        let indent = line_indent(self.text, members[last_kept].key_start);
+        let unit = match indent.strip_prefix(line_indent(self.text, node.span.start)) {
+            Some(inner) if multiline && !inner.is_empty() => inner,
+            _ => unit,
+        };
+        let separators: Vec<_> = members
+            .iter()
+            .enumerate()
+            .map(|(index, _)| match index {
+                0 => node.span.start,
+                _ => members[index - 1]
+                    .comma
+                    .expect("members are separated by commas"),
+            })
+            .collect();
+        // The start of the line of members which start their own line
+        let line_starts: Vec<_> = members
+            .iter()
+            .zip(&separators)
+            .map(|(member, separator)| self.member_start(*separator, member.key_start))
+            .collect();
+
+        for (index, member) in members.iter().enumerate() {
+            match saved_value(&member.key) {
+                Some(saved) => self.update_value(&member.value, saved, unit),
+                None if index < last_kept => {
+                    let next = &members[index + 1];
+                    let removed = match (line_starts[index], line_starts[index + 1]) {
+                        (Some(start), Some(next_start)) => start..next_start,
+                        (_, None) => member.key_start..next.key_start,
+                        // Only the rest of the line is removed, so it still ends in a newline
+                        (None, Some(_)) => {
+                            let comma = member.comma.expect("members are separated by commas");
+                            separators[index] + 1..comma + 1
+                        }
+                    };
+                    self.replace(removed, String::new())
+                }
+                None => {}
+            }
+        }
+
+        let last = &members[last_kept];
+        let mut has_comma = last.comma.is_some();
+        if let Some(first_removed) = members.get(last_kept + 1) {
+            let removed = members.last().expect("members were removed");
+            let tail = match self.member_start(
+                last.comma.expect("members are separated by commas"),
+                first_removed.key_start,
+            ) {
+                Some(start) => start..self.member_end(removed, true),
+                None => {
+                    has_comma = false;
+                    last.value.span.end..self.member_end(removed, false)
+                }
+            };
+            self.replace(tail, String::new());
+        }
+
+        if multiline {
+            let trailing_comma = members.last().is_some_and(|member| member.comma.is_some());
+            match (has_comma, !added.is_empty() || trailing_comma) {
+                (true, false) => {
+                    let comma = last.comma.expect("the comma exists");
+                    self.replace(comma..comma + 1, String::new());
+                }
+                (false, true) => self.replace(last.value.span.end..last.value.span.end, ",".into()),
+                _ => {}
+            }
+            if !added.is_empty() {
+                let entries: Vec<_> = added
+                    .iter()
+                    .map(|member| {
+                        format!(
+                            "{}{indent}{}: {}",
+                            self.newline,
+                            quote_key(&member.key),
+                            self.format(&member.value, indent, unit)
+                        )
+                    })
+                    .collect();
+                let mut insertion = entries.join(",");
+                if trailing_comma {
+                    insertion.push(',');
+                }
+                let at = self.line_end(last.comma.map_or(last.value.span.end, |x| x + 1));
+                self.replace(at..at, insertion);
+            }
+        } else if !added.is_empty() {
+            let insertion: String = added
+                .iter()
+                .map(|member| {
+                    format!(
+                        ", {}: {}",
+                        quote_key(&member.key),
+                        self.format(&member.value, indent, unit)
+                    )
+                })
+                .collect();
+            let at = last.value.span.end;
+            self.replace(at..at, insertion);
+        }
+    }
+
+    /// Skips whitespace and comments up to the end of the line.
+    /// Returns the position reached and whether it is a newline.
+    fn skip_line_trivia(&self, mut pos: usize) -> (usize, bool) {
+        loop {
+            let rest = &self.text[pos..];
+            if rest.starts_with('\n') {
+                return (pos, true);
+            } else if rest.starts_with([' ', '\t', '\r']) {
+                pos += 1;
+            } else if rest.starts_with("//") {
+                pos += rest.find('\n').unwrap_or(rest.len());
+            } else if let Some(end) = rest
+                .strip_prefix("/*")
+                .and_then(|x| x.find("*/"))
+                .filter(|end| !rest[..end + 2].contains('\n'))
+            {
+                pos += end + 4;
+            } else {
+                return (pos, false);
+            }
+        }
+    }
+
+    /// The start of the line of a member, if it starts on a new line after the separator
+    fn member_start(&self, separator: usize, key_start: usize) -> Option<usize> {
+        match self.skip_line_trivia(separator + 1) {
+            (pos, true) if pos < key_start => Some(pos + 1),
+            _ => None,
+        }
+    }
+
+    /// The end of a member including its comma, and its comments and newline if `multiline`
+    fn member_end(&self, member: &Member, multiline: bool) -> usize {
+        let end = member.comma.map_or(member.value.span.end, |x| x + 1);
+        match self.skip_line_trivia(end) {
+            (pos, true) if multiline => pos + 1,
+            (pos, false) if multiline => pos,
+            _ => end,
+        }
+    }
+
+    /// The position before the newline ending the line at `pos`
+    fn line_end(&self, pos: usize) -> usize {
+        match self.skip_line_trivia(pos) {
+            (end, true) if end > pos && self.text[..end].ends_with('\r') => end - 1,
+            (end, true) => end,
+            _ => pos,
+        }
+    }
+
+    /// Formats a saved value, with its nested lines indented by `indent` and `unit`
+    fn format(&self, node: &Node, indent: &str, unit: &str) -> String {
+        let inner = format!("{indent}{unit}");
+        let lines = |elements: Vec<String>| {
+            let elements: Vec<_> = elements
+                .into_iter()
+                .map(|element| format!("{}{inner}{element}", self.newline))
+                .collect();
+            format!("{}{}{indent}", elements.join(","), self.newline)
+        };
+        match &node.kind {
+            Kind::Object(members) if !members.is_empty() => {
+                let members = members
+                    .iter()
+                    .map(|member| {
+                        format!(
+                            "{}: {}",
+                            quote_key(&member.key),
+                            self.format(&member.value, &inner, unit)
+                        )
+                    })
+                    .collect();
+                format!("{{{}}}", lines(members))
+            }
+            Kind::Array(elements)
+                if elements
+                    .iter()
+                    .any(|x| matches!(x.kind, Kind::Object(_) | Kind::Array(_))) =>
+            {
+                let elements = elements
+                    .iter()
+                    .map(|element| self.format(element, &inner, unit))
+                    .collect();
+                format!("[{}]", lines(elements))
+            }
+            Kind::Array(elements) => {
+                let elements: Vec<_> = elements
+                    .iter()
+                    .map(|element| &self.saved[element.span.clone()])
+                    .collect();
+                format!("[{}]", elements.join(", "))
+            }
+            _ => self.saved[node.span.clone()].to_owned(),
+        }
+    }
+}
+
+fn quote_key(key: &str) -> String {
+    serde_json::to_string(key).expect("strings can be serialized")
+}
+
+/// The leading whitespace of the line containing `pos`
+fn line_indent(text: &str, pos: usize) -> &str {
+    let line_start = text[..pos].rfind('\n').map_or(0, |x| x + 1);
+    let line = &text[line_start..];
+    &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+}
@@ -0,0 +1,344 @@
+use std::marker::PhantomData;
+
+use crate::{
+    DebugValue, DefaultChange, DefaultWrapperSettings, Impex, ImpexCompact, ImpexDefaults,
+    ImpexMerge, ImpexPath, IntoImpex, PathVisitor, VisitPath, WrapperSettings,
+    compact::{CompactSeq, CompactVecSeed},
+};
+
+/// Identifies the elements of a list field with `#[impex(merge_key = "name")]`.
+/// The derive implements it for a hidden marker type per field.
+pub trait ListKey<TW: WrapperSettings> {
+    type Element: Impex<TW>;
+
+    /// Whether both elements have the same value in their key field
+    fn same_key(a: &Self::Element, b: &Self::Element) -> bool;
+
+    /// Marks the key field explicit, so a saved element can be re-associated when loading
+    fn set_key_explicit(element: &mut Self::Element);
+}
+
+/// Impex wrapper for lists whose elements are identified by a key field,
+/// created by `#[impex(merge_key = "name")]` on a `Vec` field.
+/// Unlike plain lists, elements are tracked one by one:
+/// - elements of the default list which weren't touched stay implicit and are not saved
+/// - changed elements only save their explicit values and their key
+/// - overlays override the elements with the same key and append new ones
+///
+/// When loading, saved elements are re-associated with the default list by their key
+/// in [Impex::fill_defaults], so the list keeps the order of the default, followed by added elements.
+/// Elements of the default list can't be removed, as they are restored on load.
+/// There is no mutable access to the list, changes go through [Impex::edit],
+/// which saves the changed elements with their key.
+pub struct KeyedListImpex<K: ListKey<TW>, TW: WrapperSettings = DefaultWrapperSettings> {
+    items: Vec<K::Element>,
+    _phantom: PhantomData<K>,
+}
+
+impl<K: ListKey<TW>, TW: WrapperSettings> KeyedListImpex<K, TW> {
+    pub fn new(items: Vec<<K::Element as Impex<TW>>::Value>, is_explicit: bool) -> Self
+    where
+        <K::Element as Impex<TW>>::Value: IntoImpex<TW, Impex = K::Element>,
+    {
+        Self::from_items(
+            items
+                .into_iter()
+                .map(|x| x.into_impex(is_explicit))
+                .collect(),
+        )
+    }
+
+    fn from_items(items: Vec<K::Element>) -> Self {
+        Self {
+            items,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn into_inner(self) -> Vec<K::Element> {
+        self.items
+    }
+
+    fn position(&self, element: &K::Element) -> Option<usize> {
+        self.items.iter().position(|x| K::same_key(x, element))
+    }
+}
+
+impl<K: ListKey<TW>, TW: WrapperSettings> std::fmt::Debug for KeyedListImpex<K, TW>
+where
+    K::Element: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("KeyedListImpex").field(&self.items).finish()
+    }
+}
+
+// Implemented manually, as the derives would require the marker type to implement them too
+impl<K: ListKey<TW>, TW: WrapperSettings> Clone for KeyedListImpex<K, TW>
+where
+    K::Element: Clone,
+{
+    fn clone(&self) -> Self {
+        Self::from_items(self.items.clone())
+    }
+}
+
+impl<K: ListKey<TW>, TW: WrapperSettings> PartialEq for KeyedListImpex<K, TW>
+where
+    K::Element: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.items == other.items
+    }
+}
+
+impl<K: ListKey<TW>, TW: WrapperSettings> Eq for KeyedListImpex<K, TW> where K::Element: Eq {}
+
+impl<K: ListKey<TW>, TW: WrapperSettings> Default for KeyedListImpex<K, TW> {
+    fn default() -> Self {
+        Self::from_items(Vec::new())
+    }
+}
+
+impl<K: ListKey<TW>, TW: WrapperSettings> std::ops::Deref for KeyedListImpex<K, TW> {
+    type Target = Vec<K::Element>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.items
+    }
+}
+
+impl<'a, K: ListKey<TW>, TW: WrapperSettings> IntoIterator for &'a KeyedListImpex<K, TW> {
+    type Item = &'a K::Element;
+    type IntoIter = std::slice::Iter<'a, K::Element>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter()
+    }
+}
+
+impl<K: ListKey<TW>, TW: WrapperSettings> Impex<TW> for KeyedListImpex<K, TW>
+where
+    <K::Element as Impex<TW>>::Value: IntoImpex<TW, Impex = K::Element>,
+{
+    type Value = Vec<<K::Element as Impex<TW>>::Value>;
+
+    fn is_explicit(&self) -> bool {
+        self.items.iter().any(|x| x.is_explicit())
+    }
+
+    fn into_value(self) -> Self::Value {
+        self.items.into_iter().map(|x| x.into_value()).collect()
+    }
+
+    fn set_impex(&mut self, v: Self::Value, is_explicit: bool) {
+        *self = Self::new(v, is_explicit);
+    }
+
+    /// Re-associates the loaded elements with the default elements of the same key
+    fn fill_defaults(&mut self, default: Self) {
+        let mut loaded = std::mem::take(&mut self.items);
+        for default in default.items {
+            match loaded.iter().position(|x| K::same_key(x, &default)) {
+                Some(index) => {
+                    let mut element = loaded.remove(index);
+                    element.fill_defaults(default);
+                    self.items.push(element);
+                }
+                None => self.items.push(default),
+            }
+        }
+        self.items.extend(loaded);
+    }
+}
+
+/// Elements are pruned and rebased against the default element with the same key
+impl<K: ListKey<TW>, TW: WrapperSettings> ImpexDefaults<TW> for KeyedListImpex<K, TW>
+where
+    K::Element: ImpexDefaults<TW>,
+    <K::Element as Impex<TW>>::Value: IntoImpex<TW, Impex = K::Element>,
+{
+    fn value_eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().zip(other).all(|(a, b)| a.value_eq(b))
+    }
+
+    fn prune_defaults_with(&mut self, default: &Self) {
+        for element in self.items.iter_mut() {
+            if let Some(index) = default.position(element) {
+                element.prune_defaults_with(&default.items[index]);
+            }
+        }
+    }
+
+    fn fmt_value(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.iter().map(DebugValue::<TW, K::Element>::new))
+            .finish()
+    }
+
+    /// Implicit elements missing in the new default are removed,
+    /// new default elements are added in the order of the new default.
+    /// Removed elements are reported with their index in the old list, all others with their new one
+    fn rebase_defaults_at(
+        &mut self,
+        new_default: Self,
+        path: &ImpexPath,
+        changes: &mut Vec<DefaultChange>,
+    ) {
+        let mut old: Vec<_> = std::mem::take(&mut self.items)
+            .into_iter()
+            .map(Some)
+            .collect();
+        for new_default in new_default.items {
+            let index = self.items.len();
+            let old_index = old
+                .iter()
+                .position(|x| x.as_ref().is_some_and(|x| K::same_key(x, &new_default)));
+            match old_index.and_then(|old_index| old[old_index].take()) {
+                Some(mut element) => {
+                    element.rebase_defaults_at(new_default, &path.join(index.to_string()), changes);
+                    self.items.push(element);
+                }
+                None => {
+                    changes.push(DefaultChange {
+                        path: path.join(index.to_string()),
+                        old: "None".into(),
+                        new: format!(
+                            "Some({:?})",
+                            DebugValue::<TW, K::Element>::new(&new_default)
+                        ),
+                    });
+                    self.items.push(new_default);
+                }
+            }
+        }
+        for (old_index, element) in old.into_iter().enumerate() {
+            let Some(element) = element else {
+                continue;
+            };
+            if element.is_explicit() {
+                self.items.push(element);
+            } else {
+                changes.push(DefaultChange {
+                    path: path.join(old_index.to_string()),
+                    old: format!("Some({:?})", DebugValue::<TW, K::Element>::new(&element)),
+                    new: "None".into(),
+                });
+            }
+        }
+    }
+}
+
+/// Elements of the overlay are merged into the element with the same key or appended
+impl<K: ListKey<TW>, TW: WrapperSettings> ImpexMerge<TW> for KeyedListImpex<K, TW>
+where
+    K::Element: ImpexMerge<TW>,
+    <K::Element as Impex<TW>>::Value: IntoImpex<TW, Impex = K::Element>,
+{
+    fn merge(&mut self, overlay: Self) {
+        for element in overlay.items {
+            match self.position(&element) {
+                Some(index) => self.items[index].merge(element),
+                None => self.items.push(element),
+            }
+        }
+    }
+}
+
+impl<K: ListKey<TW>, TW: WrapperSettings> VisitPath<TW> for KeyedListImpex<K, TW>
+where
+    K::Element: VisitPath<TW>,
+    <K::Element as Impex<TW>>::Value: IntoImpex<TW, Impex = K::Element>,
+{
+    fn visit_path<V: PathVisitor<TW>>(&mut self, path: &[&str], visitor: &mut V) -> bool {
+        let Some((index, rest)) = path.split_first() else {
+            visitor.visit(self);
+            return true;
+        };
+        index
+            .parse::<usize>()
+            .ok()
+            .and_then(|index| self.items.get_mut(index))
+            .is_some_and(|value| value.visit_path(rest, visitor))
+    }
+}
+
+/// Only explicit elements are saved, always together with their key
+impl<K: ListKey<TW>, TW: WrapperSettings> serde::Serialize for KeyedListImpex<K, TW>
+where
+    K::Element: serde::Serialize + Clone,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_seq(self.items.iter().filter(|x| x.is_explicit()).map(|x| {
+            let mut x = x.clone();
+            K::set_key_explicit(&mut x);
+            x
+        }))
+    }
+}
+
+impl<'de, K: ListKey<TW>, TW: WrapperSettings> serde::Deserialize<'de> for KeyedListImpex<K, TW>
+where
+    K::Element: serde::Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Vec::deserialize(deserializer).map(Self::from_items)
+    }
+}
+
+/// Like the regular serialization, only explicit elements are written together with their key
+impl<K: ListKey<TW>, TW: WrapperSettings> ImpexCompact<TW> for KeyedListImpex<K, TW>
+where
+    K::Element: ImpexCompact<TW> + Clone,
+    <K::Element as Impex<TW>>::Value: IntoImpex<TW, Impex = K::Element>,
+{
+    fn serialize_compact<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let elements: Vec<_> = self
+            .items
+            .iter()
+            .filter(|x| x.is_explicit())
+            .map(|x| {
+                let mut x = x.clone();
+                K::set_key_explicit(&mut x);
+                x
+            })
+            .collect();
+        serde::Serialize::serialize(&CompactSeq::<_, TW>::new(elements.iter()), serializer)
+    }
+
+    fn deserialize_compact<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        use serde::de::DeserializeSeed;
+        CompactVecSeed::<K::Element, TW>::new()
+            .deserialize(deserializer)
+            .map(Self::from_items)
+    }
+}
+
+#[cfg(feature = "schema")]
+impl<K: ListKey<TW>, TW: WrapperSettings> crate::schema::ImpexSchema<TW> for KeyedListImpex<K, TW>
+where
+    K::Element: crate::schema::ImpexSchema<TW>,
+    <K::Element as Impex<TW>>::Value: IntoImpex<TW, Impex = K::Element>,
+{
+    fn json_schema() -> crate::schema::Schema {
+        serde_json::json!({ "type": "array", "items": K::Element::json_schema() })
+    }
+}
+
+#[cfg(feature = "visitor")]
+impl<T, K: ListKey<TW>, TW: WrapperSettings> crate::Visitor<T> for KeyedListImpex<K, TW>
+where
+    K::Element: crate::Visitor<T>,
+{
+    fn visit(&mut self, ctx: &mut T) {
+        self.items.iter_mut().for_each(|x| x.visit(ctx));
+    }
+}
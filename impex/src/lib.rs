@@ -2,12 +2,57 @@
 use std::fmt::Debug;
 
 mod array;
+#[cfg(feature = "catalog")]
+pub mod catalog;
+#[cfg(feature = "cli")]
+pub mod cli;
+mod collection;
+pub mod compact;
+mod defaults;
+mod edit;
+#[cfg(feature = "env")]
+pub mod env;
+mod extend;
+#[cfg(feature = "jsonc")]
+pub mod jsonc;
+mod keyed;
+mod map;
+mod merge;
+mod meta;
 mod option;
+mod order;
+mod patch;
+mod path;
+mod pointer;
 mod primitive;
+#[cfg(feature = "schema")]
+pub mod schema;
+#[cfg(any(feature = "env", feature = "cli"))]
+mod text;
+#[cfg(feature = "toml")]
+pub mod toml;
+mod tuple;
+mod unknown;
 mod vec;
 
+pub use collection::CollectionImpex;
+pub use compact::{Compact, ImpexCompact};
+pub use defaults::{
+    DebugValue, DefaultChange, ImpexDefaults, deserialize_inferred, rebase_implicit_value,
+};
+pub use edit::ImpexEdit;
+pub use extend::ExtendListImpex;
+pub use keyed::{KeyedListImpex, ListKey};
+pub use map::{ImpexMap, MapImpex};
+pub use merge::{ImpexMerge, merge_explicit_value};
+pub use meta::{FieldMeta, ImpexMeta, MetaValue};
 pub use option::OptionImpex;
+pub use order::{KeyOrder, OrderedKey};
+pub use patch::Patch;
+pub use path::{ImpexPath, PathVisitor, UnknownPathError, VisitPath};
 pub use primitive::*;
+pub use tuple::{TupleElements, TupleImpex};
+pub use unknown::{RawValue, UnknownFields};
 
 pub use impex_derive::Impex;
 
@@ -85,7 +130,40 @@ pub trait Impex<TW /* = DefaultWrapperSettings*/> {
         self.set_impex(v, true);
     }
     fn set_implicit(&mut self, v: Self::Value) {
-        self.set_impex(v, true);
+        self.set_impex(v, false);
+    }
+    /// Gives mutable access to the value. The leaves which get modified are marked explicit.
+    /// ```
+    /// # use impex::{Impex, IntoImpex, DefaultWrapperSettings};
+    /// let mut threads = IntoImpex::<DefaultWrapperSettings>::into_implicit(vec![42u32]);
+    /// threads.edit().push(4);
+    /// assert!(threads.iter().all(|x| x.is_explicit()));
+    /// ```
+    fn edit(&mut self) -> ImpexEdit<'_, TW, Self>
+    where
+        TW: WrapperSettings,
+        Self: ImpexDefaults<TW> + ImpexMerge<TW> + Clone + Sized,
+    {
+        ImpexEdit::new(self)
+    }
+    /// Marks every implicit value explicit, keeping its current value.
+    /// After the next save, the document fully describes the current behavior.
+    fn materialize_defaults(&mut self)
+    where
+        Self: Clone + Sized,
+    {
+        let value = self.clone().into_value();
+        self.set_impex(value, true);
+    }
+    /// Replaces every implicit part with the corresponding part of `default`, keeping explicit ones.
+    /// Generated types call this after deserializing, so values which are never saved,
+    /// like the untouched entries of a default map, are restored on load.
+    /// The default implementation keeps the value as is.
+    fn fill_defaults(&mut self, default: Self)
+    where
+        Self: Sized,
+    {
+        let _ = default;
     }
 }
 
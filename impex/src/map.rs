@@ -0,0 +1,462 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    hash::Hash,
+    marker::PhantomData,
+    str::FromStr,
+};
+
+use crate::{
+    Compact, DebugValue, DefaultChange, DefaultWrapperSettings, Impex, ImpexCompact, ImpexDefaults,
+    ImpexMerge, ImpexPath, IntoImpex, PathVisitor, VisitPath, WrapperSettings,
+    compact::CompactEntriesSeed,
+};
+
+/// Maps supported by [MapImpex]
+pub trait ImpexMap {
+    type Key;
+}
+
+impl<K, V> ImpexMap for HashMap<K, V> {
+    type Key = K;
+}
+
+impl<K, V> ImpexMap for BTreeMap<K, V> {
+    type Key = K;
+}
+
+/// Impex wrapper for maps, e.g. `HashMap` or `BTreeMap`.
+/// Every entry tracks its own explicitness:
+/// - entries of the default map which weren't touched stay implicit and are not saved
+/// - added entries are explicit
+/// - partially overridden entries only save their explicit values
+///
+/// Entries missing in a document are restored from the default by [Impex::fill_defaults],
+/// which generated types call after deserializing.
+/// To remove a default entry for good, [MapImpex::remove_explicit] leaves a tombstone,
+/// which is saved as `null` and keeps the entry from coming back.
+/// There is no mutable access to the map, changes go through [MapImpex::insert_explicit],
+/// [MapImpex::remove_explicit] or [Impex::edit], which leave tombstones as needed.
+///
+/// Entries are saved in the order of the document they were loaded from,
+/// entries which were not in the document follow in the order of the map.
+#[derive(Debug, Clone)]
+pub struct MapImpex<M: ImpexMap, TW = DefaultWrapperSettings> {
+    entries: M,
+    removed: Vec<M::Key>,
+    /// Keys in the order of the loaded document, which is not part of the value
+    order: Vec<M::Key>,
+    is_explicit: bool,
+    _phantom: PhantomData<TW>,
+}
+
+impl<M: ImpexMap + PartialEq, TW> PartialEq for MapImpex<M, TW>
+where
+    M::Key: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        // Tombstones are unique, but their order depends on the order of the removals
+        self.entries == other.entries
+            && self.removed.len() == other.removed.len()
+            && self.removed.iter().all(|key| other.removed.contains(key))
+            && self.is_explicit == other.is_explicit
+    }
+}
+
+impl<M: ImpexMap + Eq, TW> Eq for MapImpex<M, TW> where M::Key: Eq {}
+
+impl<M: ImpexMap, TW> MapImpex<M, TW> {
+    fn new(entries: M, is_explicit: bool) -> Self {
+        Self {
+            entries,
+            removed: Vec::new(),
+            order: Vec::new(),
+            is_explicit,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn into_inner(self) -> M {
+        self.entries
+    }
+
+    /// Keys which were removed explicitly and are saved as tombstones
+    pub fn removed_keys(&self) -> &[M::Key] {
+        &self.removed
+    }
+}
+
+impl<M: ImpexMap + Default, TW> Default for MapImpex<M, TW> {
+    fn default() -> Self {
+        Self::new(M::default(), false)
+    }
+}
+
+impl<M: ImpexMap, TW> std::ops::Deref for MapImpex<M, TW> {
+    type Target = M;
+
+    fn deref(&self) -> &Self::Target {
+        &self.entries
+    }
+}
+
+impl<'a, M: ImpexMap, TW> IntoIterator for &'a MapImpex<M, TW>
+where
+    &'a M: IntoIterator,
+{
+    type Item = <&'a M as IntoIterator>::Item;
+    type IntoIter = <&'a M as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+/// Visitor of the entries of a map in the order of the document, `null` values are tombstones
+struct EntriesVisitor<K, V>(PhantomData<(K, V)>);
+
+impl<'de, K: serde::Deserialize<'de>, V: serde::Deserialize<'de>> serde::de::Visitor<'de>
+    for EntriesVisitor<K, V>
+{
+    type Value = Vec<(K, Option<V>)>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a map")
+    }
+
+    fn visit_map<A: serde::de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut entries = Vec::with_capacity(map.size_hint().unwrap_or(0).min(4096));
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+        Ok(entries)
+    }
+}
+
+/// Implements the Impex traits for a map type, which differ in the bounds of the key only
+macro_rules! impl_map {
+    ($map:ident, $($key_bound:tt)+) => {
+        impl<K: $($key_bound)+, V, TW> MapImpex<$map<K, V>, TW> {
+            /// Removes an entry and leaves a tombstone, so the entry stays removed
+            /// even if it exists in the default
+            pub fn remove_explicit(&mut self, key: K) -> Option<V> {
+                let value = self.entries.remove(&key);
+                if !self.removed.contains(&key) {
+                    self.removed.push(key);
+                }
+                value
+            }
+
+            /// Inserts an entry, replacing a tombstone for the same key
+            pub fn insert_explicit(&mut self, key: K, value: V::Value) -> Option<V>
+            where
+                TW: WrapperSettings,
+                V: Impex<TW>,
+                V::Value: IntoImpex<TW, Impex = V>,
+            {
+                self.removed.retain(|removed| removed != &key);
+                self.entries.insert(key, value.into_explicit())
+            }
+
+            fn is_removed(&self, key: &K) -> bool {
+                self.removed.contains(key)
+            }
+
+            /// Creates an explicit map from loaded entries, `None` values are tombstones
+            fn from_loaded(loaded: Vec<(K, Option<V>)>) -> Self {
+                let mut result = Self::new($map::new(), true);
+                for (key, value) in loaded {
+                    result.order.push(key.clone());
+                    // The last entry of a duplicated key wins
+                    match value {
+                        Some(value) => {
+                            result.removed.retain(|removed| removed != &key);
+                            result.entries.insert(key, value);
+                        }
+                        None => {
+                            result.entries.remove(&key);
+                            if !result.removed.contains(&key) {
+                                result.removed.push(key);
+                            }
+                        }
+                    }
+                }
+                result
+            }
+
+            /// Explicit entries and tombstones to save, in the order of the loaded document
+            fn saved_entries(&self) -> Vec<(&K, Option<&V>)>
+            where
+                TW: WrapperSettings,
+                V: Impex<TW>,
+            {
+                let mut seen = $map::new();
+                let mut saved = Vec::new();
+                let keys = self.order.iter().chain(self.entries.keys()).chain(&self.removed);
+                for key in keys {
+                    if seen.insert(key, ()).is_some() {
+                        continue;
+                    }
+                    match self.entries.get(key) {
+                        Some(value) if value.is_explicit() => saved.push((key, Some(value))),
+                        Some(_) => {}
+                        None if self.removed.contains(key) => saved.push((key, None)),
+                        None => {}
+                    }
+                }
+                saved
+            }
+        }
+
+        impl<TW: WrapperSettings, K: $($key_bound)+, V: IntoImpex<TW>> IntoImpex<TW> for $map<K, V> {
+            type Impex = MapImpex<$map<K, V::Impex>, TW>;
+
+            fn into_impex(self, is_explicit: bool) -> Self::Impex {
+                let entries = self
+                    .into_iter()
+                    .map(|(key, value)| (key, value.into_impex(is_explicit)))
+                    .collect();
+                MapImpex::new(entries, is_explicit)
+            }
+        }
+
+        impl<TW: WrapperSettings, K: $($key_bound)+, V: Impex<TW>> Impex<TW> for MapImpex<$map<K, V>, TW>
+        where
+            V::Value: IntoImpex<TW, Impex = V>,
+        {
+            type Value = $map<K, V::Value>;
+
+            fn is_explicit(&self) -> bool {
+                self.is_explicit || !self.removed.is_empty() || self.values().any(|x| x.is_explicit())
+            }
+
+            fn into_value(self) -> Self::Value {
+                self.entries
+                    .into_iter()
+                    .map(|(key, value)| (key, value.into_value()))
+                    .collect()
+            }
+
+            /// Keys which are missing in `v` become tombstones if it is set explicitly,
+            /// so the map stays exactly `v`
+            fn set_impex(&mut self, v: Self::Value, is_explicit: bool) {
+                let mut removed = std::mem::take(&mut self.removed);
+                if is_explicit {
+                    removed.extend(self.entries.keys().filter(|key| !v.contains_key(*key)).cloned());
+                }
+                removed.retain(|key| !v.contains_key(key));
+                let order = std::mem::take(&mut self.order);
+                *self = v.into_impex(is_explicit);
+                self.removed = removed;
+                self.order = order;
+            }
+
+            /// Restores the default entries which are missing, e.g. because they were never saved.
+            /// Entries with a tombstone stay removed.
+            fn fill_defaults(&mut self, default: Self) {
+                for (key, default) in default.entries {
+                    match self.entries.get_mut(&key) {
+                        Some(value) => value.fill_defaults(default),
+                        None if self.removed.contains(&key) => {}
+                        None => {
+                            self.entries.insert(key, default);
+                        }
+                    }
+                }
+            }
+        }
+
+        /// Entries are compared, pruned and rebased by key
+        impl<TW: WrapperSettings, K: $($key_bound)+ + Display, V: ImpexDefaults<TW>> ImpexDefaults<TW>
+            for MapImpex<$map<K, V>, TW>
+        where
+            V::Value: IntoImpex<TW, Impex = V>,
+        {
+            fn value_eq(&self, other: &Self) -> bool {
+                self.len() == other.len()
+                    && self
+                        .iter()
+                        .all(|(key, a)| other.get(key).is_some_and(|b| a.value_eq(b)))
+            }
+
+            /// Tombstones for keys which are not in `default` are dropped, as there is nothing to remove
+            fn prune_defaults_with(&mut self, default: &Self) {
+                if self.value_eq(default) {
+                    self.is_explicit = false;
+                }
+                self.removed.retain(|key| default.contains_key(key));
+                for (key, value) in self.entries.iter_mut() {
+                    if let Some(default) = default.get(key) {
+                        value.prune_defaults_with(default);
+                    }
+                }
+            }
+
+            fn fmt_value(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_map()
+                    .entries(self.iter().map(|(key, value)| (key.to_string(), DebugValue::<TW, V>::new(value))))
+                    .finish()
+            }
+
+            fn rebase_defaults_at(
+                &mut self,
+                mut new_default: Self,
+                path: &ImpexPath,
+                changes: &mut Vec<DefaultChange>,
+            ) {
+                let removed: Vec<_> = self
+                    .iter()
+                    .filter(|(key, value)| value.is_implicit() && !new_default.contains_key(*key))
+                    .map(|(key, _)| key.clone())
+                    .collect();
+                for key in removed {
+                    let value = self.entries.remove(&key).expect("Key was found above");
+                    changes.push(DefaultChange {
+                        path: path.join(key.to_string()),
+                        old: format!("Some({:?})", DebugValue::<TW, V>::new(&value)),
+                        new: "None".into(),
+                    });
+                }
+
+                for (key, value) in self.entries.iter_mut() {
+                    if let Some(new_default) = new_default.entries.remove(key) {
+                        value.rebase_defaults_at(new_default, &path.join(key.to_string()), changes);
+                    }
+                }
+                for (key, new_default) in new_default.entries {
+                    if self.is_removed(&key) {
+                        continue;
+                    }
+                    changes.push(DefaultChange {
+                        path: path.join(key.to_string()),
+                        old: "None".into(),
+                        new: format!("Some({:?})", DebugValue::<TW, V>::new(&new_default)),
+                    });
+                    self.entries.insert(key, new_default);
+                }
+            }
+        }
+
+        /// Entries are merged by key, entries which only exist in the overlay are added.
+        /// Tombstones of the overlay remove the entry.
+        impl<TW: WrapperSettings, K: $($key_bound)+, V: ImpexMerge<TW>> ImpexMerge<TW> for MapImpex<$map<K, V>, TW>
+        where
+            V::Value: IntoImpex<TW, Impex = V>,
+        {
+            fn merge(&mut self, overlay: Self) {
+                self.is_explicit |= overlay.is_explicit;
+                for key in overlay.order {
+                    if !self.order.contains(&key) {
+                        self.order.push(key);
+                    }
+                }
+                for key in overlay.removed {
+                    self.remove_explicit(key);
+                }
+                for (key, overlay) in overlay.entries {
+                    self.removed.retain(|removed| removed != &key);
+                    match self.entries.get_mut(&key) {
+                        Some(value) => value.merge(overlay),
+                        None => {
+                            self.entries.insert(key, overlay);
+                        }
+                    }
+                }
+            }
+        }
+
+        /// Keys are used as path segments, so they need to be parsable from a string
+        impl<TW: WrapperSettings, K: $($key_bound)+ + FromStr + serde::de::DeserializeOwned, V: VisitPath<TW>> VisitPath<TW>
+            for MapImpex<$map<K, V>, TW>
+        where
+            V::Value: IntoImpex<TW, Impex = V>,
+        {
+            fn visit_path<P: PathVisitor<TW>>(&mut self, path: &[&str], visitor: &mut P) -> bool {
+                let Some((key, rest)) = path.split_first() else {
+                    visitor.visit(self);
+                    return true;
+                };
+                key.parse::<K>()
+                    .ok()
+                    .and_then(|key| self.entries.get_mut(&key))
+                    .is_some_and(|value| value.visit_path(rest, visitor))
+            }
+        }
+
+        /// Implicit entries are skipped, so untouched default entries are not saved.
+        /// Tombstones are saved as `null`.
+        impl<TW: WrapperSettings, K: $($key_bound)+ + serde::Serialize, V: Impex<TW> + serde::Serialize> serde::Serialize
+            for MapImpex<$map<K, V>, TW>
+        {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                serializer.collect_map(self.saved_entries())
+            }
+        }
+
+        /// Maps present in the document are explicit, even if they are empty.
+        /// `null` values are tombstones, so maps of `Option` can't contain explicit `None` values.
+        impl<'de, TW, K: $($key_bound)+ + serde::Deserialize<'de>, V: serde::Deserialize<'de>> serde::Deserialize<'de>
+            for MapImpex<$map<K, V>, TW>
+        {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                Ok(Self::from_loaded(deserializer.deserialize_map(EntriesVisitor(PhantomData))?))
+            }
+        }
+
+        /// Explicit entries and tombstones are written like the regular serialization
+        impl<TW: WrapperSettings, K: $($key_bound)+ + serde::Serialize + serde::de::DeserializeOwned, V: ImpexCompact<TW>> ImpexCompact<TW>
+            for MapImpex<$map<K, V>, TW>
+        where
+            V::Value: IntoImpex<TW, Impex = V>,
+        {
+            fn serialize_compact<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                // Collected first, as formats with a fixed layout need to know the length
+                let entries: Vec<_> = self
+                    .saved_entries()
+                    .into_iter()
+                    .map(|(key, value)| (key, value.map(Compact::<_, TW>::new)))
+                    .collect();
+                serializer.collect_map(entries)
+            }
+
+            fn deserialize_compact<'de, D: serde::Deserializer<'de>>(
+                deserializer: D,
+            ) -> Result<Self, D::Error> {
+                use serde::de::DeserializeSeed;
+                let loaded = CompactEntriesSeed::<K, V, TW>::new().deserialize(deserializer)?;
+                Ok(Self::from_loaded(loaded))
+            }
+        }
+
+        /// Removed entries are written as `null` tombstones
+        #[cfg(feature = "schema")]
+        impl<TW: WrapperSettings, K: $($key_bound)+, V: crate::schema::ImpexSchema<TW>>
+            crate::schema::ImpexSchema<TW> for MapImpex<$map<K, V>, TW>
+        where
+            V::Value: IntoImpex<TW, Impex = V>,
+        {
+            fn json_schema() -> crate::schema::Schema {
+                serde_json::json!({
+                    "type": "object",
+                    "additionalProperties": crate::schema::nullable(V::json_schema()),
+                })
+            }
+        }
+
+        #[cfg(feature = "visitor")]
+        impl<T, TW, K, V: crate::Visitor<T>> crate::Visitor<T> for MapImpex<$map<K, V>, TW> {
+            fn visit(&mut self, ctx: &mut T) {
+                self.entries.values_mut().for_each(|x| x.visit(ctx));
+            }
+        }
+    };
+}
+
+impl_map!(HashMap, Eq + Hash + Clone);
+impl_map!(BTreeMap, Ord + Clone);
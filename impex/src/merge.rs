@@ -0,0 +1,16 @@
+use crate::{Impex, WrapperSettings};
+
+/// Impex types which can be layered, e.g. a config file overlaid by a partial update.
+/// Implementations are generated by `#[derive(Impex)]`.
+pub trait ImpexMerge<TW: WrapperSettings>: Impex<TW> {
+    /// Overrides `self` with every explicit value of `overlay`. Implicit values of `overlay` are ignored.
+    fn merge(&mut self, overlay: Self);
+}
+
+/// Replaces `value` as a whole, if `overlay` is explicit.
+/// Used for values which can't be merged leaf by leaf, e.g. lists or different enum variants.
+pub fn merge_explicit_value<TW: WrapperSettings, T: Impex<TW>>(value: &mut T, overlay: T) {
+    if overlay.is_explicit() {
+        *value = overlay;
+    }
+}
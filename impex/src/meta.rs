@@ -0,0 +1,168 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use crate::{
+    CollectionImpex, ExtendListImpex, Impex, ImpexMap, ImpexPath, ImpexPrimitiveValue,
+    KeyedListImpex, ListKey, MapImpex, OptionImpex, Patch, TupleElements, TupleImpex,
+    WrapperSettings,
+};
+
+/// Metadata of a field for user interfaces, declared with `#[impex(meta(...))]`:
+/// ```
+/// # use impex::{Impex, ImpexMeta, MetaValue};
+/// #[derive(Default, Impex)]
+/// struct Config {
+///     /// Number of worker threads
+///     #[impex(meta(label = "Threads", min = 1, max = 64, unit = "cores", category = "Performance"))]
+///     threads: u32,
+///     #[impex(meta(allowed = ["fast", "small"], advanced))]
+///     mode: String,
+/// }
+///
+/// let threads = <ConfigImpex>::field_meta(&["threads"]).unwrap();
+/// assert_eq!(Some("Threads"), threads.label);
+/// assert_eq!(Some("Number of worker threads"), threads.description);
+/// assert_eq!(Some(64.0), threads.max);
+/// let mode = <ConfigImpex>::field_meta(&["mode"]).unwrap();
+/// assert!(mode.advanced);
+/// assert_eq!([MetaValue::Str("fast"), MetaValue::Str("small")], mode.allowed);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FieldMeta {
+    /// Name to display instead of the field name
+    pub label: Option<&'static str>,
+    /// Given by `description = "..."`, otherwise the doc comment of the field
+    pub description: Option<&'static str>,
+    pub unit: Option<&'static str>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub step: Option<f64>,
+    /// Values to choose from, empty if any value is allowed
+    pub allowed: &'static [MetaValue],
+    /// Hidden by default, set by the flag `advanced`
+    pub advanced: bool,
+    pub category: Option<&'static str>,
+}
+
+impl FieldMeta {
+    /// Metadata without any entries, used by `#[derive(Impex)]` for the entries which are not given
+    pub const EMPTY: Self = Self {
+        label: None,
+        description: None,
+        unit: None,
+        min: None,
+        max: None,
+        step: None,
+        allowed: &[],
+        advanced: false,
+        category: None,
+    };
+}
+
+/// A literal in `allowed = [...]`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MetaValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(&'static str),
+}
+
+/// Impex types which know the metadata of their fields.
+/// Implementations are generated by `#[derive(Impex)]`, other types forward to the values they contain.
+pub trait ImpexMeta<TW: WrapperSettings>: Impex<TW> {
+    /// Metadata of the field at `path`, with the same segments as [crate::VisitPath].
+    /// Elements of lists and maps share their metadata, so any index or key can be used.
+    fn field_meta(path: &[&str]) -> Option<&'static FieldMeta> {
+        let _ = path;
+        None
+    }
+
+    /// Adds the metadata of the fields below `path`.
+    /// Fields inside of lists and maps are skipped, as their paths depend on the value.
+    fn collect_field_meta(path: &ImpexPath, fields: &mut Vec<(ImpexPath, &'static FieldMeta)>) {
+        let _ = (path, fields);
+    }
+
+    /// Metadata of every field with `#[impex(meta(...))]`, in declaration order
+    fn fields_meta() -> Vec<(ImpexPath, &'static FieldMeta)>
+    where
+        Self: Sized,
+    {
+        let mut fields = Vec::new();
+        Self::collect_field_meta(&ImpexPath::root(), &mut fields);
+        fields
+    }
+}
+
+impl<T, TW: WrapperSettings> ImpexMeta<TW> for ImpexPrimitiveValue<T> where Self: Impex<TW> {}
+impl<T: TupleElements, TW: WrapperSettings> ImpexMeta<TW> for TupleImpex<T, TW> where Self: Impex<TW>
+{}
+
+/// Implements the metadata of a transparent wrapper, which has the paths of its value
+macro_rules! impl_transparent_meta {
+    ($wrapper:ty) => {
+        impl<T: ImpexMeta<TW>, TW: WrapperSettings> ImpexMeta<TW> for $wrapper
+        where
+            Self: Impex<TW>,
+        {
+            fn field_meta(path: &[&str]) -> Option<&'static FieldMeta> {
+                T::field_meta(path)
+            }
+
+            fn collect_field_meta(
+                path: &ImpexPath,
+                fields: &mut Vec<(ImpexPath, &'static FieldMeta)>,
+            ) {
+                T::collect_field_meta(path, fields);
+            }
+        }
+    };
+}
+
+impl_transparent_meta!(OptionImpex<T>);
+impl_transparent_meta!(Patch<T>);
+impl_transparent_meta!(std::rc::Rc<T>);
+impl_transparent_meta!(std::sync::Arc<T>);
+impl_transparent_meta!(Box<T>);
+
+/// Implements the metadata of a list, whose elements are reached by their index
+macro_rules! impl_list_meta {
+    ($list:ty, $element:ty, $($generics:tt)*) => {
+        impl<$($generics)*, TW: WrapperSettings> ImpexMeta<TW> for $list
+        where
+            Self: Impex<TW>,
+            $element: ImpexMeta<TW>,
+        {
+            fn field_meta(path: &[&str]) -> Option<&'static FieldMeta> {
+                let (index, rest) = path.split_first()?;
+                index.parse::<usize>().ok()?;
+                <$element>::field_meta(rest)
+            }
+        }
+    };
+}
+
+impl_list_meta!(CollectionImpex<Vec<T>>, T, T);
+impl_list_meta!(CollectionImpex<VecDeque<T>>, T, T);
+impl_list_meta!(CollectionImpex<[T; SIZE]>, T, T, const SIZE: usize);
+impl_list_meta!(ExtendListImpex<T, TW>, T, T);
+impl_list_meta!(KeyedListImpex<K, TW>, K::Element, K: ListKey<TW>);
+
+/// Entries of maps are reached by their key
+macro_rules! impl_map_meta {
+    ($map:ident) => {
+        impl<K, V: ImpexMeta<TW>, TW: WrapperSettings> ImpexMeta<TW> for MapImpex<$map<K, V>, TW>
+        where
+            Self: Impex<TW>,
+            $map<K, V>: ImpexMap,
+        {
+            fn field_meta(path: &[&str]) -> Option<&'static FieldMeta> {
+                let (_key, rest) = path.split_first()?;
+                V::field_meta(rest)
+            }
+        }
+    };
+}
+
+impl_map_meta!(HashMap);
+impl_map_meta!(BTreeMap);
@@ -1,8 +1,14 @@
-use crate::{Impex, IntoImpex, WrapperSettings};
+use crate::{
+    Compact, DebugValue, DefaultChange, Impex, ImpexCompact, ImpexDefaults, ImpexMerge, ImpexPath,
+    IntoImpex, Patch, PathVisitor, VisitPath, WrapperSettings, compact::CompactOptionSeed,
+    merge_explicit_value, rebase_implicit_value,
+};
 
 /// Impex wrapper for Option that tracks explicit/implicit state for None values.
 /// - `Some(value)`: explicit/implicit determined by inner value
 /// - `None`: can be explicit (JSON had `null`) or implicit (field was missing)
+///
+/// Use [Patch] for partial updates, which need to distinguish a missing field from `null` in any context.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OptionImpex<T> {
     Some(T),
@@ -40,20 +46,116 @@ impl<T> OptionImpex<T> {
             OptionImpex::None(_) => None,
         }
     }
+
+    pub fn as_deref(&self) -> Option<&T::Target>
+    where
+        T: std::ops::Deref,
+    {
+        self.as_ref().map(|x| x.deref())
+    }
+
+    pub fn iter(&self) -> std::option::IntoIter<&T> {
+        self.as_ref().into_iter()
+    }
+
+    pub fn iter_mut(&mut self) -> std::option::IntoIter<&mut T> {
+        self.as_mut().into_iter()
+    }
+
+    pub fn into_option(self) -> Option<T> {
+        match self {
+            OptionImpex::Some(x) => Some(x),
+            OptionImpex::None(_) => None,
+        }
+    }
+
+    /// Maps the contained value. None keeps its explicitness
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> OptionImpex<U> {
+        match self {
+            OptionImpex::Some(x) => OptionImpex::Some(f(x)),
+            OptionImpex::None(is_explicit) => OptionImpex::None(is_explicit),
+        }
+    }
+
+    pub fn unwrap_or(self, default: T) -> T {
+        self.into_option().unwrap_or(default)
+    }
+
+    pub fn unwrap_or_else(self, f: impl FnOnce() -> T) -> T {
+        self.into_option().unwrap_or_else(f)
+    }
+
+    /// Takes the value out, leaving an explicit None so the removal is persisted
+    pub fn take(&mut self) -> Option<T> {
+        std::mem::replace(self, Self::explicit_none()).into_option()
+    }
 }
 
-/// Inherent is_explicit/is_implicit for DefaultWrapperSettings (most common case)
-impl<T: Impex<crate::DefaultWrapperSettings>> OptionImpex<T> {
-    pub fn is_explicit(&self) -> bool {
+/// Methods which depend on the explicitness of the contained value, for any WrapperSettings
+impl<T> OptionImpex<T> {
+    pub fn is_explicit<TW>(&self) -> bool
+    where
+        T: Impex<TW>,
+    {
         match self {
             OptionImpex::Some(value) => value.is_explicit(),
             OptionImpex::None(is_explicit) => *is_explicit,
         }
     }
 
-    pub fn is_implicit(&self) -> bool {
+    pub fn is_implicit<TW>(&self) -> bool
+    where
+        T: Impex<TW>,
+    {
         !self.is_explicit()
     }
+
+    /// Sets an explicit value and returns the previous one
+    pub fn replace<TW: WrapperSettings>(&mut self, value: T::Value) -> Option<T>
+    where
+        T: Impex<TW>,
+        T::Value: IntoImpex<TW, Impex = T>,
+    {
+        std::mem::replace(self, OptionImpex::Some(value.into_explicit())).into_option()
+    }
+
+    /// Inserts an explicit value if it is None and returns a reference to the contained value
+    pub fn get_or_insert_with<TW: WrapperSettings>(
+        &mut self,
+        f: impl FnOnce() -> T::Value,
+    ) -> &mut T
+    where
+        T: Impex<TW>,
+        T::Value: IntoImpex<TW, Impex = T>,
+    {
+        if let OptionImpex::None(_) = self {
+            *self = OptionImpex::Some(f().into_explicit());
+        }
+        match self {
+            OptionImpex::Some(x) => x,
+            OptionImpex::None(_) => unreachable!("Value was inserted above"),
+        }
+    }
+
+    /// Applies a partial update: `Absent` keeps the current state, `Null` clears it explicitly
+    /// and `Set` is merged into the current value. Like in [ImpexMerge], an implicit `Null` is ignored.
+    pub fn apply_patch<TW: WrapperSettings>(&mut self, patch: Patch<T>)
+    where
+        T: ImpexMerge<TW>,
+    {
+        match (self, patch) {
+            (_, Patch::Absent | Patch::Null(false)) => {}
+            (this, Patch::Null(true)) => *this = OptionImpex::explicit_none(),
+            (OptionImpex::Some(value), Patch::Set(patch)) => value.merge(patch),
+            (this, Patch::Set(patch)) => *this = OptionImpex::Some(patch),
+        }
+    }
+}
+
+impl<T> From<OptionImpex<T>> for Option<T> {
+    fn from(value: OptionImpex<T>) -> Self {
+        value.into_option()
+    }
 }
 
 impl<T: Default> Default for OptionImpex<T> {
@@ -99,6 +201,93 @@ where
             None => OptionImpex::None(is_explicit),
         };
     }
+
+    fn fill_defaults(&mut self, default: Self) {
+        match (self, default) {
+            (OptionImpex::Some(value), OptionImpex::Some(default)) => value.fill_defaults(default),
+            (this, default) => {
+                if this.is_implicit() {
+                    *this = default;
+                }
+            }
+        }
+    }
+}
+
+impl<TW: WrapperSettings, T: ImpexDefaults<TW>> ImpexDefaults<TW> for OptionImpex<T>
+where
+    T::Value: IntoImpex<TW, Impex = T>,
+{
+    fn value_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (OptionImpex::Some(a), OptionImpex::Some(b)) => a.value_eq(b),
+            (OptionImpex::None(_), OptionImpex::None(_)) => true,
+            _ => false,
+        }
+    }
+
+    fn prune_defaults_with(&mut self, default: &Self) {
+        match (self, default) {
+            (OptionImpex::Some(value), OptionImpex::Some(default)) => {
+                value.prune_defaults_with(default)
+            }
+            (OptionImpex::None(is_explicit), OptionImpex::None(_)) => *is_explicit = false,
+            _ => {}
+        }
+    }
+
+    fn fmt_value(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OptionImpex::Some(value) => f
+                .debug_tuple("Some")
+                .field(&DebugValue::<TW, T>::new(value))
+                .finish(),
+            OptionImpex::None(_) => f.write_str("None"),
+        }
+    }
+
+    fn rebase_defaults_at(
+        &mut self,
+        new_default: Self,
+        path: &ImpexPath,
+        changes: &mut Vec<DefaultChange>,
+    ) {
+        match (self, new_default) {
+            (OptionImpex::Some(value), OptionImpex::Some(new_default)) => {
+                value.rebase_defaults_at(new_default, path, changes)
+            }
+            (this, new_default) => rebase_implicit_value(this, new_default, path, changes),
+        }
+    }
+}
+
+impl<TW: WrapperSettings, T: ImpexMerge<TW>> ImpexMerge<TW> for OptionImpex<T>
+where
+    T::Value: IntoImpex<TW, Impex = T>,
+{
+    fn merge(&mut self, overlay: Self) {
+        match (self, overlay) {
+            (OptionImpex::Some(value), OptionImpex::Some(overlay)) => value.merge(overlay),
+            (this, overlay) => merge_explicit_value(this, overlay),
+        }
+    }
+}
+
+/// Paths pass through `Some` transparently
+impl<TW: WrapperSettings, T: VisitPath<TW>> VisitPath<TW> for OptionImpex<T>
+where
+    T::Value: IntoImpex<TW, Impex = T>,
+{
+    fn visit_path<V: PathVisitor<TW>>(&mut self, path: &[&str], visitor: &mut V) -> bool {
+        match self {
+            _ if path.is_empty() => {
+                visitor.visit(self);
+                true
+            }
+            OptionImpex::Some(value) => value.visit_path(path, visitor),
+            OptionImpex::None(_) => false,
+        }
+    }
 }
 
 impl<T: serde::Serialize> serde::Serialize for OptionImpex<T> {
@@ -127,6 +316,43 @@ impl<'de, T: serde::de::Deserialize<'de>> serde::Deserialize<'de> for OptionImpe
     }
 }
 
+/// `None` is written as none, so it is loaded as explicit None like `null`
+impl<TW: WrapperSettings, T: ImpexCompact<TW>> ImpexCompact<TW> for OptionImpex<T>
+where
+    T::Value: IntoImpex<TW, Impex = T>,
+{
+    fn serialize_compact<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            OptionImpex::Some(value) => serializer.serialize_some(&Compact::<_, TW>::new(value)),
+            OptionImpex::None(_) => serializer.serialize_none(),
+        }
+    }
+
+    fn deserialize_compact<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        use serde::de::DeserializeSeed;
+        Ok(
+            match CompactOptionSeed::<T, TW>::new().deserialize(deserializer)? {
+                Some(value) => OptionImpex::Some(value),
+                None => OptionImpex::explicit_none(),
+            },
+        )
+    }
+}
+
+/// `null` is written for an explicit `None`
+#[cfg(feature = "schema")]
+impl<TW: WrapperSettings, T: crate::schema::ImpexSchema<TW>> crate::schema::ImpexSchema<TW>
+    for OptionImpex<T>
+where
+    T::Value: IntoImpex<TW, Impex = T>,
+{
+    fn json_schema() -> crate::schema::Schema {
+        crate::schema::nullable(T::json_schema())
+    }
+}
+
 #[cfg(feature = "visitor")]
 impl<T, U> crate::Visitor<T> for OptionImpex<U>
 where
@@ -0,0 +1,369 @@
+use serde::{
+    Deserialize, Deserializer,
+    de::{DeserializeSeed, MapAccess, SeqAccess, Visitor},
+};
+
+use crate::{RawValue, UnknownFields};
+
+/// Order of the keys of a struct in the document it was loaded from.
+/// Structs with `#[impex(preserve_order)]` record it when deserializing and write their fields
+/// in the same order, followed by new fields in declaration order. It is not part of the value,
+/// so it is ignored when comparing values.
+/// Other structs and the struct variants of enums are always written in declaration order.
+#[derive(Debug, Clone, Default)]
+pub struct KeyOrder(Vec<String>);
+
+/// A key to write, arranged by [KeyOrder::arrange]
+pub enum OrderedKey<'a> {
+    /// The field with this index in the declaration
+    Field(usize),
+    Unknown(&'a str, &'a RawValue),
+}
+
+impl KeyOrder {
+    /// Deserializes a value and records the order of the keys of its outermost map
+    pub fn deserialize<'de, T: Deserialize<'de>, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<(T, Self), D::Error> {
+        let mut keys = Vec::new();
+        let value = T::deserialize(RecordKeys {
+            inner: deserializer,
+            keys: &mut keys,
+        })?;
+        Ok((value, Self(keys)))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(String::as_str)
+    }
+
+    /// Arranges the fields and unknown keys of a struct in the recorded order.
+    /// Keys which weren't recorded follow, fields in declaration order and then unknown keys.
+    pub fn arrange<'a>(&self, fields: &[&str], unknown: &'a UnknownFields) -> Vec<OrderedKey<'a>> {
+        let mut written_fields = vec![false; fields.len()];
+        let mut written_unknown = vec![false; unknown.len()];
+        let mut keys = Vec::with_capacity(fields.len() + unknown.len());
+        for key in &self.0 {
+            if let Some(index) = fields.iter().position(|field| field == key) {
+                if !std::mem::replace(&mut written_fields[index], true) {
+                    keys.push(OrderedKey::Field(index));
+                }
+                continue;
+            }
+            let Some(index) = unknown.iter().position(|(k, _)| k == key) else {
+                continue;
+            };
+            if !std::mem::replace(&mut written_unknown[index], true) {
+                let (key, value) = unknown.iter().nth(index).expect("index was found above");
+                keys.push(OrderedKey::Unknown(key, value));
+            }
+        }
+        let fields = (0..fields.len())
+            .filter(|index| !written_fields[*index])
+            .map(OrderedKey::Field);
+        let unknown = unknown
+            .iter()
+            .zip(written_unknown)
+            .filter(|(_, written)| !written)
+            .map(|((key, value), _)| OrderedKey::Unknown(key, value));
+        keys.extend(fields.chain(unknown));
+        keys
+    }
+
+    /// Appends the keys of the overlay which are not recorded yet
+    pub fn merge(&mut self, overlay: Self) {
+        for key in overlay.0 {
+            if !self.0.contains(&key) {
+                self.0.push(key);
+            }
+        }
+    }
+}
+
+/// Deserializer recording the keys of the outermost map, while nested values are deserialized as usual
+struct RecordKeys<'a, D> {
+    inner: D,
+    keys: &'a mut Vec<String>,
+}
+
+macro_rules! forward_deserialize {
+    ($($method:ident($($arg:ident: $ty:ty),*)),* $(,)?) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Self::Error> {
+                self.inner.$method($($arg,)* visitor)
+            }
+        )*
+    };
+}
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for RecordKeys<'_, D> {
+    type Error = D::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.inner.deserialize_any(RecordMap {
+            inner: visitor,
+            keys: self.keys,
+        })
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.inner.deserialize_map(RecordMap {
+            inner: visitor,
+            keys: self.keys,
+        })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.inner.deserialize_struct(
+            name,
+            fields,
+            RecordMap {
+                inner: visitor,
+                keys: self.keys,
+            },
+        )
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+
+    forward_deserialize! {
+        deserialize_bool(),
+        deserialize_i8(),
+        deserialize_i16(),
+        deserialize_i32(),
+        deserialize_i64(),
+        deserialize_i128(),
+        deserialize_u8(),
+        deserialize_u16(),
+        deserialize_u32(),
+        deserialize_u64(),
+        deserialize_u128(),
+        deserialize_f32(),
+        deserialize_f64(),
+        deserialize_char(),
+        deserialize_str(),
+        deserialize_string(),
+        deserialize_bytes(),
+        deserialize_byte_buf(),
+        deserialize_option(),
+        deserialize_unit(),
+        deserialize_unit_struct(name: &'static str),
+        deserialize_newtype_struct(name: &'static str),
+        deserialize_seq(),
+        deserialize_tuple(len: usize),
+        deserialize_tuple_struct(name: &'static str, len: usize),
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]),
+        deserialize_identifier(),
+        deserialize_ignored_any(),
+    }
+}
+
+/// Visitor of the outermost map, which records the keys passed to the inner visitor
+struct RecordMap<'a, V> {
+    inner: V,
+    keys: &'a mut Vec<String>,
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for RecordMap<'_, V> {
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.inner.expecting(f)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        self.inner.visit_map(RecordMap {
+            inner: map,
+            keys: self.keys,
+        })
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+        self.inner.visit_seq(seq)
+    }
+}
+
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for RecordMap<'_, A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        self.inner.next_key_seed(RecordKey {
+            inner: seed,
+            keys: self.keys,
+        })
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.inner.next_value_seed(seed)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+/// Seed, deserializer and visitor of a key, which records string keys
+struct RecordKey<'a, T> {
+    inner: T,
+    keys: &'a mut Vec<String>,
+}
+
+impl<'de, T: DeserializeSeed<'de>> DeserializeSeed<'de> for RecordKey<'_, T> {
+    type Value = T::Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        self.inner.deserialize(RecordKey {
+            inner: deserializer,
+            keys: self.keys,
+        })
+    }
+}
+
+macro_rules! record_key_deserialize {
+    ($($method:ident($($arg:ident: $ty:ty),*)),* $(,)?) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Self::Error> {
+                self.inner.$method($($arg,)* RecordKey { inner: visitor, keys: self.keys })
+            }
+        )*
+    };
+}
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for RecordKey<'_, D> {
+    type Error = D::Error;
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+
+    record_key_deserialize! {
+        deserialize_any(),
+        deserialize_bool(),
+        deserialize_i8(),
+        deserialize_i16(),
+        deserialize_i32(),
+        deserialize_i64(),
+        deserialize_i128(),
+        deserialize_u8(),
+        deserialize_u16(),
+        deserialize_u32(),
+        deserialize_u64(),
+        deserialize_u128(),
+        deserialize_f32(),
+        deserialize_f64(),
+        deserialize_char(),
+        deserialize_str(),
+        deserialize_string(),
+        deserialize_bytes(),
+        deserialize_byte_buf(),
+        deserialize_option(),
+        deserialize_unit(),
+        deserialize_unit_struct(name: &'static str),
+        deserialize_newtype_struct(name: &'static str),
+        deserialize_seq(),
+        deserialize_tuple(len: usize),
+        deserialize_tuple_struct(name: &'static str, len: usize),
+        deserialize_map(),
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]),
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]),
+        deserialize_identifier(),
+        deserialize_ignored_any(),
+    }
+}
+
+macro_rules! forward_visit {
+    ($($method:ident($ty:ty)),* $(,)?) => {
+        $(
+            fn $method<E: serde::de::Error>(self, v: $ty) -> Result<Self::Value, E> {
+                self.inner.$method(v)
+            }
+        )*
+    };
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for RecordKey<'_, V> {
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.inner.expecting(f)
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        self.keys.push(v.to_owned());
+        self.inner.visit_str(v)
+    }
+
+    fn visit_borrowed_str<E: serde::de::Error>(self, v: &'de str) -> Result<Self::Value, E> {
+        self.keys.push(v.to_owned());
+        self.inner.visit_borrowed_str(v)
+    }
+
+    fn visit_string<E: serde::de::Error>(self, v: String) -> Result<Self::Value, E> {
+        self.keys.push(v.clone());
+        self.inner.visit_string(v)
+    }
+
+    forward_visit! {
+        visit_bool(bool),
+        visit_i8(i8),
+        visit_i16(i16),
+        visit_i32(i32),
+        visit_i64(i64),
+        visit_i128(i128),
+        visit_u8(u8),
+        visit_u16(u16),
+        visit_u32(u32),
+        visit_u64(u64),
+        visit_u128(u128),
+        visit_f32(f32),
+        visit_f64(f64),
+        visit_char(char),
+        visit_bytes(&[u8]),
+        visit_borrowed_bytes(&'de [u8]),
+        visit_byte_buf(Vec<u8>),
+    }
+
+    fn visit_none<E: serde::de::Error>(self) -> Result<Self::Value, E> {
+        self.inner.visit_none()
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        self.inner.visit_some(deserializer)
+    }
+
+    fn visit_unit<E: serde::de::Error>(self) -> Result<Self::Value, E> {
+        self.inner.visit_unit()
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        self.inner.visit_newtype_struct(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+        self.inner.visit_seq(seq)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        self.inner.visit_map(map)
+    }
+
+    fn visit_enum<A: serde::de::EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+        self.inner.visit_enum(data)
+    }
+}
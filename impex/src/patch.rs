@@ -0,0 +1,280 @@
+use crate::{
+    Compact, DebugValue, DefaultChange, DefaultWrapperSettings, Impex, ImpexCompact, ImpexDefaults,
+    ImpexMerge, ImpexPath, IntoImpex, OptionImpex, PathVisitor, VisitPath, WrapperSettings,
+    compact::CompactOptionSeed, merge_explicit_value, rebase_implicit_value,
+};
+
+/// Three-state sibling of [OptionImpex] for partial updates (PATCH semantics).
+/// - `Absent`: the field was missing, so the current value should not be touched
+/// - `Null`: the field was `null`, so the current value should be cleared
+/// - `Set(value)`: the field was set
+///
+/// Like `Vec`, the same type is used for the original and the Impex side.
+/// `Null` tracks if it was explicitly set, so a `Null` default isn't written back;
+/// on the original side, the flag is ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Patch<T> {
+    #[default]
+    Absent,
+    /// Null with bool tracking if it was explicitly set
+    Null(bool),
+    Set(T),
+}
+
+impl<T> Patch<T> {
+    pub fn explicit_null() -> Self {
+        Self::Null(true)
+    }
+
+    pub fn implicit_null() -> Self {
+        Self::Null(false)
+    }
+
+    pub fn is_absent(&self) -> bool {
+        matches!(self, Self::Absent)
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null(_))
+    }
+
+    pub fn as_ref(&self) -> Patch<&T> {
+        match self {
+            Patch::Absent => Patch::Absent,
+            Patch::Null(is_explicit) => Patch::Null(*is_explicit),
+            Patch::Set(value) => Patch::Set(value),
+        }
+    }
+
+    /// Converts into the value it would set, `None` for both `Absent` and `Null`
+    pub fn into_option(self) -> Option<T> {
+        match self {
+            Patch::Set(value) => Some(value),
+            Patch::Absent | Patch::Null(_) => None,
+        }
+    }
+}
+
+/// Inherent is_explicit/is_implicit for DefaultWrapperSettings (most common case)
+impl<T: Impex<DefaultWrapperSettings>> Patch<T>
+where
+    T::Value: IntoImpex<DefaultWrapperSettings, Impex = T>,
+{
+    pub fn is_explicit(&self) -> bool {
+        Impex::<DefaultWrapperSettings>::is_explicit(self)
+    }
+
+    pub fn is_implicit(&self) -> bool {
+        !self.is_explicit()
+    }
+}
+
+/// Implicit None becomes `Absent`, explicit None becomes `Null`
+impl<T> From<OptionImpex<T>> for Patch<T> {
+    fn from(value: OptionImpex<T>) -> Self {
+        match value {
+            OptionImpex::Some(value) => Patch::Set(value),
+            OptionImpex::None(false) => Patch::Absent,
+            OptionImpex::None(true) => Patch::explicit_null(),
+        }
+    }
+}
+
+impl<TW: WrapperSettings, T: IntoImpex<TW>> IntoImpex<TW> for Patch<T> {
+    type Impex = Patch<T::Impex>;
+
+    fn into_impex(self, is_explicit: bool) -> Self::Impex {
+        match self {
+            Patch::Absent => Patch::Absent,
+            Patch::Null(_) => Patch::Null(is_explicit),
+            Patch::Set(value) => Patch::Set(value.into_impex(is_explicit)),
+        }
+    }
+}
+
+impl<TW: WrapperSettings, T: Impex<TW>> Impex<TW> for Patch<T>
+where
+    T::Value: IntoImpex<TW, Impex = T>,
+{
+    type Value = Patch<T::Value>;
+
+    fn is_explicit(&self) -> bool {
+        match self {
+            Patch::Absent => false,
+            Patch::Null(is_explicit) => *is_explicit,
+            Patch::Set(value) => value.is_explicit(),
+        }
+    }
+
+    fn into_value(self) -> Self::Value {
+        match self {
+            Patch::Absent => Patch::Absent,
+            Patch::Null(is_explicit) => Patch::Null(is_explicit),
+            Patch::Set(value) => Patch::Set(value.into_value()),
+        }
+    }
+
+    fn set_impex(&mut self, v: Self::Value, is_explicit: bool) {
+        *self = v.into_impex(is_explicit);
+    }
+
+    fn fill_defaults(&mut self, default: Self) {
+        match (self, default) {
+            (Patch::Set(value), Patch::Set(default)) => value.fill_defaults(default),
+            (this, default) => {
+                if this.is_implicit() {
+                    *this = default;
+                }
+            }
+        }
+    }
+}
+
+impl<TW: WrapperSettings, T: ImpexMerge<TW>> ImpexMerge<TW> for Patch<T>
+where
+    T::Value: IntoImpex<TW, Impex = T>,
+{
+    fn merge(&mut self, overlay: Self) {
+        match (self, overlay) {
+            (Patch::Set(value), Patch::Set(overlay)) => value.merge(overlay),
+            (this, overlay) => merge_explicit_value(this, overlay),
+        }
+    }
+}
+
+impl<TW: WrapperSettings, T: ImpexDefaults<TW>> ImpexDefaults<TW> for Patch<T>
+where
+    T::Value: IntoImpex<TW, Impex = T>,
+{
+    fn value_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Patch::Set(a), Patch::Set(b)) => a.value_eq(b),
+            (Patch::Absent, Patch::Absent) | (Patch::Null(_), Patch::Null(_)) => true,
+            _ => false,
+        }
+    }
+
+    fn prune_defaults_with(&mut self, default: &Self) {
+        match (self, default) {
+            (Patch::Set(value), Patch::Set(default)) => value.prune_defaults_with(default),
+            (Patch::Null(is_explicit), Patch::Null(_)) => *is_explicit = false,
+            _ => {}
+        }
+    }
+
+    fn fmt_value(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Patch::Absent => f.write_str("Absent"),
+            Patch::Null(_) => f.write_str("Null"),
+            Patch::Set(value) => f
+                .debug_tuple("Set")
+                .field(&DebugValue::<TW, T>::new(value))
+                .finish(),
+        }
+    }
+
+    fn rebase_defaults_at(
+        &mut self,
+        new_default: Self,
+        path: &ImpexPath,
+        changes: &mut Vec<DefaultChange>,
+    ) {
+        match (self, new_default) {
+            (Patch::Set(value), Patch::Set(new_default)) => {
+                value.rebase_defaults_at(new_default, path, changes)
+            }
+            (this, new_default) => rebase_implicit_value(this, new_default, path, changes),
+        }
+    }
+}
+
+/// Paths pass through `Set` transparently
+impl<TW: WrapperSettings, T: VisitPath<TW>> VisitPath<TW> for Patch<T>
+where
+    T::Value: IntoImpex<TW, Impex = T>,
+{
+    fn visit_path<V: PathVisitor<TW>>(&mut self, path: &[&str], visitor: &mut V) -> bool {
+        match self {
+            _ if path.is_empty() => {
+                visitor.visit(self);
+                true
+            }
+            Patch::Set(value) => value.visit_path(path, visitor),
+            Patch::Absent | Patch::Null(_) => false,
+        }
+    }
+}
+
+impl<T: serde::Serialize> serde::Serialize for Patch<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Patch::Set(value) => value.serialize(serializer),
+            Patch::Absent | Patch::Null(_) => serializer.serialize_none(),
+        }
+    }
+}
+
+impl<'de, T: serde::de::Deserialize<'de>> serde::Deserialize<'de> for Patch<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        // Missing fields never reach this, they are `Absent` by `Default`
+        Ok(match Option::<T>::deserialize(deserializer)? {
+            Some(value) => Patch::Set(value),
+            None => Patch::explicit_null(),
+        })
+    }
+}
+
+/// `Absent` is never present in its parent, so only `Null` and `Set` are written
+impl<TW: WrapperSettings, T: ImpexCompact<TW>> ImpexCompact<TW> for Patch<T>
+where
+    T::Value: IntoImpex<TW, Impex = T>,
+{
+    fn serialize_compact<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Patch::Set(value) => serializer.serialize_some(&Compact::<_, TW>::new(value)),
+            Patch::Absent | Patch::Null(_) => serializer.serialize_none(),
+        }
+    }
+
+    fn deserialize_compact<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        use serde::de::DeserializeSeed;
+        Ok(
+            match CompactOptionSeed::<T, TW>::new().deserialize(deserializer)? {
+                Some(value) => Patch::Set(value),
+                None => Patch::explicit_null(),
+            },
+        )
+    }
+}
+
+/// `null` is written to remove the value
+#[cfg(feature = "schema")]
+impl<TW: WrapperSettings, T: crate::schema::ImpexSchema<TW>> crate::schema::ImpexSchema<TW>
+    for Patch<T>
+where
+    T::Value: IntoImpex<TW, Impex = T>,
+{
+    fn json_schema() -> crate::schema::Schema {
+        crate::schema::nullable(T::json_schema())
+    }
+}
+
+#[cfg(feature = "visitor")]
+impl<T, U> crate::Visitor<T> for Patch<U>
+where
+    U: crate::Visitor<T>,
+{
+    fn visit(&mut self, ctx: &mut T) {
+        if let Patch::Set(inner) = self {
+            inner.visit(ctx);
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use std::fmt::Display;
+
+use crate::{ImpexMerge, WrapperSettings};
+
+/// Location of a value inside an Impex type, displayed as `enum_config.Bar.1`.
+/// Segments are field names, enum variants or indices.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ImpexPath(Vec<String>);
+
+impl ImpexPath {
+    pub fn root() -> Self {
+        Self::default()
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Creates the path of a child value
+    pub fn join(&self, segment: impl Into<String>) -> Self {
+        let mut segments = self.0.clone();
+        segments.push(segment.into());
+        Self(segments)
+    }
+
+    pub fn segments(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(String::as_str)
+    }
+}
+
+impl Display for ImpexPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0.join("."))
+    }
+}
+
+impl From<&str> for ImpexPath {
+    fn from(value: &str) -> Self {
+        Self(
+            value
+                .split('.')
+                .filter(|x| !x.is_empty())
+                .map(String::from)
+                .collect(),
+        )
+    }
+}
+
+impl std::str::FromStr for ImpexPath {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(s.into())
+    }
+}
+
+/// Impex types which can look up their children by path.
+/// Implementations are generated by `#[derive(Impex)]`.
+/// Values found by path can be overridden with deserialized ones, e.g. from environment variables.
+pub trait VisitPath<TW: WrapperSettings>:
+    ImpexMerge<TW> + Clone + serde::de::DeserializeOwned
+{
+    /// Calls `visitor` with the value at `path`. Returns false if there is no such value.
+    fn visit_path<V: PathVisitor<TW>>(&mut self, path: &[&str], visitor: &mut V) -> bool;
+
+    /// Like [crate::Impex::materialize_defaults], but only for the value at `path`.
+    /// Lists are saved as a whole, so an element is materialized together with its list.
+    /// Elements of lists with `#[impex(list = "extend")]` have no path and return an error.
+    fn materialize_defaults_at(&mut self, path: &ImpexPath) -> Result<(), UnknownPathError> {
+        struct Materialize;
+        impl<TW: WrapperSettings> PathVisitor<TW> for Materialize {
+            fn visit<T: VisitPath<TW>>(&mut self, value: &mut T) {
+                value.materialize_defaults();
+            }
+        }
+
+        let segments: Vec<_> = path.segments().collect();
+        if self.visit_path(&segments, &mut Materialize) {
+            Ok(())
+        } else {
+            Err(UnknownPathError(path.clone()))
+        }
+    }
+}
+
+/// Operation on the value found by [VisitPath::visit_path]
+pub trait PathVisitor<TW: WrapperSettings> {
+    fn visit<T: VisitPath<TW>>(&mut self, value: &mut T);
+}
+
+/// Returned if a path doesn't point to a value, e.g. because of a typo or an inactive enum variant
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownPathError(pub ImpexPath);
+
+impl Display for UnknownPathError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "no value at path `{}`", self.0)
+    }
+}
+
+impl std::error::Error for UnknownPathError {}
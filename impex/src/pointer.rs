@@ -0,0 +1,220 @@
+use std::{rc::Rc, sync::Arc};
+
+use crate::{
+    DefaultChange, Impex, ImpexCompact, ImpexDefaults, ImpexMerge, ImpexPath, IntoImpex,
+    PathVisitor, VisitPath, WrapperSettings,
+};
+
+/// Implements the Impex traits for a shared pointer, which is transparent.
+/// Modifications clone the value if it is shared, like `make_mut`.
+macro_rules! impl_shared_pointer {
+    ($ptr:ident) => {
+        impl<TW: WrapperSettings, T: IntoImpex<TW> + Clone> IntoImpex<TW> for $ptr<T>
+        where
+            T::Impex: Clone,
+        {
+            type Impex = $ptr<T::Impex>;
+
+            fn into_impex(self, is_explicit: bool) -> Self::Impex {
+                $ptr::new($ptr::unwrap_or_clone(self).into_impex(is_explicit))
+            }
+        }
+
+        /// Shared pointers are transparent, the pointee tracks its explicitness
+        impl<TW, T: Impex<TW> + Clone> Impex<TW> for $ptr<T>
+        where
+            T::Value: Clone,
+        {
+            type Value = $ptr<T::Value>;
+
+            fn is_explicit(&self) -> bool {
+                (**self).is_explicit()
+            }
+
+            fn into_value(self) -> Self::Value {
+                $ptr::new($ptr::unwrap_or_clone(self).into_value())
+            }
+
+            fn set_impex(&mut self, v: Self::Value, is_explicit: bool) {
+                $ptr::make_mut(self).set_impex($ptr::unwrap_or_clone(v), is_explicit);
+            }
+
+            fn fill_defaults(&mut self, default: Self) {
+                $ptr::make_mut(self).fill_defaults($ptr::unwrap_or_clone(default));
+            }
+        }
+
+        impl<TW: WrapperSettings, T: ImpexDefaults<TW> + Clone> ImpexDefaults<TW> for $ptr<T>
+        where
+            T::Value: Clone,
+        {
+            fn value_eq(&self, other: &Self) -> bool {
+                (**self).value_eq(other)
+            }
+
+            fn prune_defaults_with(&mut self, default: &Self) {
+                $ptr::make_mut(self).prune_defaults_with(default);
+            }
+
+            fn fmt_value(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                (**self).fmt_value(f)
+            }
+
+            fn rebase_defaults_at(
+                &mut self,
+                new_default: Self,
+                path: &ImpexPath,
+                changes: &mut Vec<DefaultChange>,
+            ) {
+                $ptr::make_mut(self).rebase_defaults_at(
+                    $ptr::unwrap_or_clone(new_default),
+                    path,
+                    changes,
+                );
+            }
+        }
+
+        impl<TW: WrapperSettings, T: ImpexMerge<TW> + Clone> ImpexMerge<TW> for $ptr<T>
+        where
+            T::Value: Clone,
+        {
+            fn merge(&mut self, overlay: Self) {
+                $ptr::make_mut(self).merge($ptr::unwrap_or_clone(overlay));
+            }
+        }
+
+        impl<TW: WrapperSettings, T: VisitPath<TW>> VisitPath<TW> for $ptr<T>
+        where
+            T::Value: Clone,
+        {
+            fn visit_path<V: PathVisitor<TW>>(&mut self, path: &[&str], visitor: &mut V) -> bool {
+                $ptr::make_mut(self).visit_path(path, visitor)
+            }
+        }
+
+        impl<TW, T: ImpexCompact<TW> + Clone> ImpexCompact<TW> for $ptr<T>
+        where
+            T::Value: Clone,
+        {
+            fn serialize_compact<S: serde::Serializer>(
+                &self,
+                serializer: S,
+            ) -> Result<S::Ok, S::Error> {
+                (**self).serialize_compact(serializer)
+            }
+
+            fn deserialize_compact<'de, D: serde::Deserializer<'de>>(
+                deserializer: D,
+            ) -> Result<Self, D::Error> {
+                T::deserialize_compact(deserializer).map($ptr::new)
+            }
+        }
+
+        #[cfg(feature = "schema")]
+        impl<TW: WrapperSettings, T: crate::schema::ImpexSchema<TW> + Clone>
+            crate::schema::ImpexSchema<TW> for $ptr<T>
+        where
+            T::Value: Clone,
+        {
+            fn json_schema() -> crate::schema::Schema {
+                T::json_schema()
+            }
+        }
+
+        #[cfg(feature = "visitor")]
+        impl<T, U: crate::Visitor<T> + Clone> crate::Visitor<T> for $ptr<U> {
+            fn visit(&mut self, ctx: &mut T) {
+                $ptr::make_mut(self).visit(ctx);
+            }
+        }
+    };
+}
+
+impl_shared_pointer!(Rc);
+impl_shared_pointer!(Arc);
+
+/// `Box` is transparent like the shared pointers, but `IntoImpex` can't be implemented generically,
+/// as downstream crates may implement [crate::ImpexPrimitive] for boxes of their types.
+/// `#[derive(Impex)]` implements it for boxes of the derived type, boxed primitives are primitives.
+impl<TW, T: Impex<TW>> Impex<TW> for Box<T> {
+    type Value = Box<T::Value>;
+
+    fn is_explicit(&self) -> bool {
+        (**self).is_explicit()
+    }
+
+    fn into_value(self) -> Self::Value {
+        Box::new((*self).into_value())
+    }
+
+    fn set_impex(&mut self, v: Self::Value, is_explicit: bool) {
+        (**self).set_impex(*v, is_explicit);
+    }
+
+    fn fill_defaults(&mut self, default: Self) {
+        (**self).fill_defaults(*default);
+    }
+}
+
+impl<TW: WrapperSettings, T: ImpexDefaults<TW>> ImpexDefaults<TW> for Box<T> {
+    fn value_eq(&self, other: &Self) -> bool {
+        (**self).value_eq(other)
+    }
+
+    fn prune_defaults_with(&mut self, default: &Self) {
+        (**self).prune_defaults_with(default);
+    }
+
+    fn fmt_value(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        (**self).fmt_value(f)
+    }
+
+    fn rebase_defaults_at(
+        &mut self,
+        new_default: Self,
+        path: &ImpexPath,
+        changes: &mut Vec<DefaultChange>,
+    ) {
+        (**self).rebase_defaults_at(*new_default, path, changes);
+    }
+}
+
+impl<TW: WrapperSettings, T: ImpexMerge<TW>> ImpexMerge<TW> for Box<T> {
+    fn merge(&mut self, overlay: Self) {
+        (**self).merge(*overlay);
+    }
+}
+
+impl<TW: WrapperSettings, T: VisitPath<TW>> VisitPath<TW> for Box<T> {
+    fn visit_path<V: PathVisitor<TW>>(&mut self, path: &[&str], visitor: &mut V) -> bool {
+        (**self).visit_path(path, visitor)
+    }
+}
+
+impl<TW, T: ImpexCompact<TW>> ImpexCompact<TW> for Box<T> {
+    fn serialize_compact<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (**self).serialize_compact(serializer)
+    }
+
+    fn deserialize_compact<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        T::deserialize_compact(deserializer).map(Box::new)
+    }
+}
+
+#[cfg(feature = "schema")]
+impl<TW: WrapperSettings, T: crate::schema::ImpexSchema<TW>> crate::schema::ImpexSchema<TW>
+    for Box<T>
+{
+    fn json_schema() -> crate::schema::Schema {
+        T::json_schema()
+    }
+}
+
+#[cfg(feature = "visitor")]
+impl<T, U: crate::Visitor<T>> crate::Visitor<T> for Box<U> {
+    fn visit(&mut self, ctx: &mut T) {
+        (**self).visit(ctx);
+    }
+}
@@ -1,6 +1,9 @@
 use std::fmt::Debug;
 
-use crate::Impex;
+use crate::{
+    DefaultChange, DefaultWrapperSettings, Impex, ImpexCompact, ImpexDefaults, ImpexEdit,
+    ImpexMerge, ImpexPath, PathVisitor, VisitPath, WrapperSettings,
+};
 
 #[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
 pub struct ImpexPrimitiveValue<T> {
@@ -31,11 +34,24 @@ impl<T> ImpexPrimitiveValue<T> {
         self.value = value;
     }
 
+    pub fn set_implicit(&mut self, value: T) {
+        self.is_explicit = false;
+        self.value = value;
+    }
+
     pub fn into_value(self) -> T {
         self.value
     }
 }
 
+/// A single value behaves the same with any WrapperSettings, so the guard uses the default ones
+impl<T: ImpexPrimitive + PartialEq> ImpexPrimitiveValue<T> {
+    /// See [Impex::edit]
+    pub fn edit(&mut self) -> ImpexEdit<'_, DefaultWrapperSettings, Self> {
+        Impex::<DefaultWrapperSettings>::edit(self)
+    }
+}
+
 impl<T> std::ops::Deref for ImpexPrimitiveValue<T> {
     type Target = T;
 
@@ -83,6 +99,72 @@ impl<T: ImpexPrimitive, TW> Impex<TW> for ImpexPrimitiveValue<T> {
         self.is_explicit = is_explicit;
         self.value = v;
     }
+
+    fn fill_defaults(&mut self, default: Self) {
+        if !self.is_explicit {
+            *self = default;
+        }
+    }
+}
+
+impl<T: ImpexPrimitive + PartialEq, TW: WrapperSettings> ImpexDefaults<TW>
+    for ImpexPrimitiveValue<T>
+{
+    fn value_eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+
+    fn prune_defaults_with(&mut self, default: &Self) {
+        if self.value == default.value {
+            self.is_explicit = false;
+        }
+    }
+
+    fn fmt_value(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.value.fmt(f)
+    }
+
+    fn rebase_defaults_at(
+        &mut self,
+        new_default: Self,
+        path: &ImpexPath,
+        changes: &mut Vec<DefaultChange>,
+    ) {
+        if !self.is_explicit && self.value != new_default.value {
+            changes.push(DefaultChange::new::<TW, _>(path, self, &new_default));
+            self.value = new_default.value;
+        }
+    }
+}
+
+impl<T: ImpexPrimitive, TW: WrapperSettings> VisitPath<TW> for ImpexPrimitiveValue<T> {
+    fn visit_path<V: PathVisitor<TW>>(&mut self, path: &[&str], visitor: &mut V) -> bool {
+        if path.is_empty() {
+            visitor.visit(self);
+        }
+        path.is_empty()
+    }
+}
+
+impl<T: ImpexPrimitive, TW: WrapperSettings> ImpexMerge<TW> for ImpexPrimitiveValue<T> {
+    fn merge(&mut self, overlay: Self) {
+        if overlay.is_explicit {
+            *self = overlay;
+        }
+    }
+}
+
+/// Only the value is written, the parent records whether it is present
+impl<T: ImpexPrimitive, TW: WrapperSettings> ImpexCompact<TW> for ImpexPrimitiveValue<T> {
+    fn serialize_compact<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value.serialize(serializer)
+    }
+
+    fn deserialize_compact<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(|value| Self::new(value, true))
+    }
 }
 
 pub trait ImpexPrimitive:
@@ -119,6 +201,14 @@ impl ImpexPrimitive for std::net::Ipv6Addr {}
 impl ImpexPrimitive for std::net::SocketAddr {}
 impl ImpexPrimitive for std::net::SocketAddrV4 {}
 impl ImpexPrimitive for std::net::SocketAddrV6 {}
+impl ImpexPrimitive for std::borrow::Cow<'static, str> {}
+impl ImpexPrimitive for Box<str> {}
+impl ImpexPrimitive for std::rc::Rc<str> {}
+impl ImpexPrimitive for std::sync::Arc<str> {}
+impl<T: ImpexPrimitive> ImpexPrimitive for Box<T> {}
+// Elements of sets can't be tracked individually, as they need to be hashed or ordered
+impl<T: ImpexPrimitive + Eq + std::hash::Hash> ImpexPrimitive for std::collections::HashSet<T> {}
+impl<T: ImpexPrimitive + Ord> ImpexPrimitive for std::collections::BTreeSet<T> {}
 ///
 /// Wraps a normal value so it can be turned into a impex, even if the type doesn't implement IntoImpex
 /// This should be a field attribute #[impex(primitive)] in the macro
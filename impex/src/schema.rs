@@ -0,0 +1,279 @@
+//! JSON Schema of the persisted form, enabled by the `schema` feature.
+//!
+//! `#[derive(Impex)]` implements [ImpexSchema] for the generated types, so editors and CI can
+//! validate documents before they are loaded. Every property of a struct is optional, as omitted
+//! values take their default, and carries the `default` taken from the `Default` of the original type.
+//! Enums are written like the generated serializer does: unit variants as strings,
+//! every other variant externally tagged as `{"Variant": ...}`.
+//! Doc comments of types, fields and variants become descriptions.
+//! ```
+//! # use impex::{Impex, DefaultWrapperSettings, schema};
+//! #[derive(Impex)]
+//! /// Settings of the server
+//! struct Config {
+//!     /// Number of worker threads
+//!     threads: u32,
+//!     mode: Mode,
+//! }
+//!
+//! #[derive(Impex)]
+//! enum Mode {
+//!     Fast,
+//!     Limited { rate: u16 },
+//! }
+//! # impl Default for Config {
+//! #     fn default() -> Self { Config { threads: 4, mode: Mode::Fast } }
+//! # }
+//! # impl Default for Mode {
+//! #     fn default() -> Self { Mode::Fast }
+//! # }
+//!
+//! let schema = schema::json_schema::<DefaultWrapperSettings, ConfigImpex>();
+//! assert_eq!("Settings of the server", schema["description"]);
+//! assert_eq!(4, schema["properties"]["threads"]["default"]);
+//! assert_eq!("Fast", schema["properties"]["mode"]["oneOf"][0]["const"]);
+//! ```
+//!
+//! Recursive types are not supported, their schema would be infinite.
+
+use serde_json::{Map, json};
+
+use crate::{Impex, ImpexPrimitiveValue, PrimitiveWrapper, WrapperSettings};
+
+/// A JSON Schema, as a JSON value
+pub type Schema = serde_json::Value;
+
+/// Impex types which can describe their persisted form as JSON Schema.
+/// Implementations are generated by `#[derive(Impex)]`.
+pub trait ImpexSchema<TW: WrapperSettings>: Impex<TW> {
+    fn json_schema() -> Schema;
+}
+
+/// Schema of a primitive value, used for [ImpexPrimitiveValue].
+/// Implement it for custom [crate::ImpexPrimitive] types to use them in a schema.
+pub trait PrimitiveSchema {
+    fn json_schema() -> Schema;
+}
+
+/// The schema of `T` as a document, declaring the JSON Schema draft it follows
+pub fn json_schema<TW: WrapperSettings, T: ImpexSchema<TW>>() -> Schema {
+    let mut schema = T::json_schema();
+    if let Some(object) = schema.as_object_mut() {
+        object.insert(
+            "$schema".into(),
+            "https://json-schema.org/draft/2020-12/schema".into(),
+        );
+    }
+    schema
+}
+
+/// The persisted form of `value` with every default materialized, used as `default` of properties
+pub fn default_value<TW, T: Impex<TW> + Clone + serde::Serialize>(mut value: T) -> Schema {
+    value.materialize_defaults();
+    serde_json::to_value(value).unwrap_or_default()
+}
+
+/// Adds a description, if there is one
+pub fn describe(mut schema: Schema, description: Option<&str>) -> Schema {
+    if let (Some(object), Some(description)) = (schema.as_object_mut(), description) {
+        object.insert("description".into(), description.into());
+    }
+    schema
+}
+
+/// Object of a struct with named fields. Every property is optional and takes its `default`
+/// from the entry of the same name in `default`.
+/// Unknown properties are only allowed if they are preserved.
+pub fn struct_schema(fields: Vec<(&str, Schema)>, default: &Schema, allow_unknown: bool) -> Schema {
+    let properties: Map<_, _> = fields
+        .into_iter()
+        .map(|(name, mut schema)| {
+            if let (Some(object), Some(default)) = (schema.as_object_mut(), default.get(name)) {
+                object.insert("default".into(), default.clone());
+            }
+            (name.to_string(), schema)
+        })
+        .collect();
+    json!({
+        "type": "object",
+        "properties": properties,
+        "additionalProperties": allow_unknown,
+    })
+}
+
+/// Array of a tuple struct, every element must be present.
+/// Implicit elements are written as `null`.
+pub fn tuple_schema(items: Vec<Schema>, default: &Schema) -> Schema {
+    let items: Vec<_> = items
+        .into_iter()
+        .enumerate()
+        .map(|(index, schema)| {
+            let mut schema = nullable_element(schema);
+            if let (Some(object), Some(default)) = (schema.as_object_mut(), default.get(index)) {
+                object.insert("default".into(), default.clone());
+            }
+            schema
+        })
+        .collect();
+    fixed_array(items)
+}
+
+/// Content of an enum variant
+pub enum VariantSchema {
+    Unit,
+    /// A tuple variant with a single field, which is written without the array
+    Newtype(Schema),
+    Tuple(Vec<Schema>),
+    Struct(Vec<(&'static str, Schema)>),
+}
+
+/// Alternatives of an enum: unit variants are strings, the others are objects
+/// with the name of the variant as their only key.
+/// Fields of variants must be present, as the variant replaces the default as a whole.
+/// Implicit fields are written as `null`.
+pub fn enum_schema(
+    variants: Vec<(&'static str, Option<&'static str>, VariantSchema)>,
+    allow_unknown: bool,
+) -> Schema {
+    let alternatives: Vec<_> = variants
+        .into_iter()
+        .map(|(name, description, variant)| {
+            let content = match variant {
+                VariantSchema::Unit => return describe(json!({ "const": name }), description),
+                VariantSchema::Newtype(schema) => nullable_element(schema),
+                VariantSchema::Tuple(items) => {
+                    fixed_array(items.into_iter().map(nullable_element).collect())
+                }
+                VariantSchema::Struct(fields) => {
+                    let required: Vec<_> = fields.iter().map(|(name, _)| *name).collect();
+                    let properties: Map<_, _> = fields
+                        .into_iter()
+                        .map(|(name, schema)| (name.to_string(), nullable_element(schema)))
+                        .collect();
+                    json!({
+                        "type": "object",
+                        "properties": properties,
+                        "required": required,
+                        "additionalProperties": allow_unknown,
+                    })
+                }
+            };
+            let schema = json!({
+                "type": "object",
+                "properties": { name: content },
+                "required": [name],
+                "additionalProperties": false,
+            });
+            describe(schema, description)
+        })
+        .collect();
+    json!({ "oneOf": alternatives })
+}
+
+/// Allows `null` in addition to the values of `schema`
+pub(crate) fn nullable(schema: Schema) -> Schema {
+    json!({ "anyOf": [schema, { "type": "null" }] })
+}
+
+/// Element of a tuple struct or field of a variant, which is `null` if it is implicit.
+/// The description stays on the outer schema.
+fn nullable_element(mut schema: Schema) -> Schema {
+    let description = schema
+        .as_object_mut()
+        .and_then(|object| object.remove("description"));
+    let mut schema = nullable(schema);
+    if let (Some(object), Some(description)) = (schema.as_object_mut(), description) {
+        object.insert("description".into(), description);
+    }
+    schema
+}
+
+/// Array with exactly one element per schema
+pub(crate) fn fixed_array(items: Vec<Schema>) -> Schema {
+    let len = items.len();
+    json!({
+        "type": "array",
+        "prefixItems": items,
+        "minItems": len,
+        "maxItems": len,
+    })
+}
+
+impl<T: crate::ImpexPrimitive + PrimitiveSchema, TW: WrapperSettings> ImpexSchema<TW>
+    for ImpexPrimitiveValue<T>
+{
+    fn json_schema() -> Schema {
+        T::json_schema()
+    }
+}
+
+macro_rules! impl_primitive_schema {
+    ($($ty:ty),+ => $schema:tt) => {
+        $(impl PrimitiveSchema for $ty {
+            fn json_schema() -> Schema {
+                json!($schema)
+            }
+        })+
+    };
+}
+
+impl_primitive_schema!(bool => { "type": "boolean" });
+impl_primitive_schema!(i8 => { "type": "integer", "minimum": i8::MIN, "maximum": i8::MAX });
+impl_primitive_schema!(i16 => { "type": "integer", "minimum": i16::MIN, "maximum": i16::MAX });
+impl_primitive_schema!(i32 => { "type": "integer", "minimum": i32::MIN, "maximum": i32::MAX });
+impl_primitive_schema!(u8 => { "type": "integer", "minimum": 0, "maximum": u8::MAX });
+impl_primitive_schema!(u16 => { "type": "integer", "minimum": 0, "maximum": u16::MAX });
+impl_primitive_schema!(u32 => { "type": "integer", "minimum": 0, "maximum": u32::MAX });
+impl_primitive_schema!(i64, i128 => { "type": "integer" });
+impl_primitive_schema!(u64, u128, usize => { "type": "integer", "minimum": 0 });
+impl_primitive_schema!(
+    std::num::NonZeroU8,
+    std::num::NonZeroU16,
+    std::num::NonZeroU32,
+    std::num::NonZeroU64,
+    std::num::NonZeroU128,
+    std::num::NonZeroUsize
+    => { "type": "integer", "minimum": 1 }
+);
+impl_primitive_schema!(f32, f64 => { "type": "number" });
+impl_primitive_schema!(char => { "type": "string", "minLength": 1, "maxLength": 1 });
+impl_primitive_schema!(
+    String,
+    std::borrow::Cow<'static, str>,
+    Box<str>,
+    std::rc::Rc<str>,
+    std::sync::Arc<str>,
+    std::path::PathBuf,
+    std::net::IpAddr,
+    std::net::SocketAddr,
+    std::net::SocketAddrV4,
+    std::net::SocketAddrV6
+    => { "type": "string" }
+);
+impl_primitive_schema!(std::net::Ipv4Addr => { "type": "string", "format": "ipv4" });
+impl_primitive_schema!(std::net::Ipv6Addr => { "type": "string", "format": "ipv6" });
+
+impl<T: PrimitiveSchema> PrimitiveSchema for Box<T> {
+    fn json_schema() -> Schema {
+        T::json_schema()
+    }
+}
+
+impl<T: PrimitiveSchema> PrimitiveSchema for std::collections::HashSet<T> {
+    fn json_schema() -> Schema {
+        json!({ "type": "array", "items": T::json_schema(), "uniqueItems": true })
+    }
+}
+
+impl<T: PrimitiveSchema> PrimitiveSchema for std::collections::BTreeSet<T> {
+    fn json_schema() -> Schema {
+        json!({ "type": "array", "items": T::json_schema(), "uniqueItems": true })
+    }
+}
+
+/// The wrapped type is not known to have a schema, so any value is allowed
+impl<T> PrimitiveSchema for PrimitiveWrapper<T> {
+    fn json_schema() -> Schema {
+        json!({})
+    }
+}
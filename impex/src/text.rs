@@ -0,0 +1,185 @@
+//! Values given as text, shared by the environment and command line sources
+
+use serde::{
+    Deserializer,
+    de::{IntoDeserializer, Unexpected, Visitor},
+};
+
+use crate::{PathVisitor, VisitPath, WrapperSettings};
+
+/// Merges the value parsed from `text`, which is explicit, into the value at `path`.
+/// Parts of the target which the text doesn't mention are kept, e.g. the other entries of a map.
+/// Returns `None` if there is no value at `path`.
+pub(crate) fn set_from_text<TW: WrapperSettings, T: VisitPath<TW>>(
+    target: &mut T,
+    path: &[&str],
+    text: &str,
+) -> Option<Result<(), serde_json::Error>> {
+    let mut set = SetFromText { text, result: None };
+    target.visit_path(path, &mut set);
+    set.result
+}
+
+/// Checks whether there is a value at `path`
+pub(crate) fn path_exists<TW: WrapperSettings, T: VisitPath<TW>>(
+    target: &mut T,
+    path: &[&str],
+) -> bool {
+    struct Exists;
+    impl<TW: WrapperSettings> PathVisitor<TW> for Exists {
+        fn visit<T: VisitPath<TW>>(&mut self, _value: &mut T) {}
+    }
+
+    target.visit_path(path, &mut Exists)
+}
+
+/// Merges the value parsed from the text into the visited value
+struct SetFromText<'a> {
+    text: &'a str,
+    result: Option<Result<(), serde_json::Error>>,
+}
+
+impl<TW: WrapperSettings> PathVisitor<TW> for SetFromText<'_> {
+    fn visit<T: VisitPath<TW>>(&mut self, value: &mut T) {
+        self.result =
+            Some(T::deserialize(TextDeserializer(self.text)).map(|parsed| value.merge(parsed)));
+    }
+}
+
+/// Deserializer of a value given as text, e.g. in an environment variable or a command line argument.
+/// Primitives are parsed from the text, composite values are decoded as JSON.
+struct TextDeserializer<'a>(&'a str);
+
+impl TextDeserializer<'_> {
+    fn json(&self) -> Result<serde_json::Value, serde_json::Error> {
+        serde_json::from_str(self.0)
+    }
+}
+
+macro_rules! parse_primitive {
+    ($($method:ident => $visit:ident),* $(,)?) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                let value = self.0.trim().parse().map_err(|_| {
+                    serde::de::Error::invalid_value(Unexpected::Str(self.0), &visitor)
+                })?;
+                visitor.$visit(value)
+            }
+        )*
+    };
+}
+
+macro_rules! decode_json {
+    ($($method:ident($($arg:ident: $ty:ty),*)),* $(,)?) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Self::Error> {
+                self.json()?.$method($($arg,)* visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for TextDeserializer<'_> {
+    type Error = serde_json::Error;
+
+    /// Text which is valid JSON is decoded, anything else is a string
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.json() {
+            Ok(value) => value.deserialize_any(visitor),
+            Err(_) => visitor.visit_str(self.0),
+        }
+    }
+
+    parse_primitive! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_str(self.0)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_str(self.0)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_str(self.0)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_str(self.0)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_bytes(self.0.as_bytes())
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_bytes(self.0.as_bytes())
+    }
+
+    /// `null` is an explicit `None`, anything else is parsed as the inner value
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0.trim() {
+            "null" => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// Unit variants can be given by name, other variants are decoded as JSON
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.json() {
+            Ok(value) => value.deserialize_enum(name, variants, visitor),
+            Err(_) => visitor.visit_enum(self.0.into_deserializer()),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    decode_json! {
+        deserialize_seq(),
+        deserialize_tuple(len: usize),
+        deserialize_tuple_struct(name: &'static str, len: usize),
+        deserialize_map(),
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]),
+    }
+}
@@ -0,0 +1,185 @@
+//! Comment- and layout-preserving round trips of TOML documents, enabled by the `toml` feature.
+//!
+//! [TomlDocument] keeps the parsed document next to the loaded value.
+//! Saving writes only the changes back into the document:
+//! - changed values are replaced in place, keeping the comments around them
+//! - keys whose values became implicit are removed
+//! - untouched keys keep their formatting, order and comments
+//!
+//! TOML has no `null`, so explicit `None` values and map tombstones can't be saved.
+//! ```
+//! # use impex::{Impex, toml::TomlDocument};
+//! #[derive(Default, Impex)]
+//! struct Config {
+//!     threads: u32,
+//!     name: String,
+//! }
+//!
+//! let text = "# Worker threads\nthreads = 4 # tuned\n";
+//! let mut document = TomlDocument::<ConfigImpex>::parse(text).unwrap();
+//! document.name.set_explicit("worker".into());
+//! assert_eq!(
+//!     "# Worker threads\nthreads = 4 # tuned\nname = \"worker\"\n",
+//!     document.save().unwrap()
+//! );
+//! ```
+
+use std::fmt::Display;
+
+use serde::{Serialize, de::DeserializeOwned};
+use toml_edit::{DocumentMut, Item, TableLike, Value};
+
+/// A TOML document together with the value loaded from it
+#[derive(Debug, Clone)]
+pub struct TomlDocument<T> {
+    document: DocumentMut,
+    value: T,
+}
+
+impl<T: Serialize + DeserializeOwned> TomlDocument<T> {
+    /// Parses the document and loads the value from it
+    pub fn parse(text: &str) -> Result<Self, TomlError> {
+        let document: DocumentMut = text.parse().map_err(TomlError::Parse)?;
+        let value = toml_edit::de::from_document(document.clone()).map_err(TomlError::Load)?;
+        Ok(Self { document, value })
+    }
+
+    /// Writes the explicit values into the document, keeping its comments and formatting
+    pub fn update(&mut self) -> Result<(), TomlError> {
+        let saved = toml_edit::ser::to_document(&self.value).map_err(TomlError::Save)?;
+        update_table(self.document.as_table_mut(), saved.as_table(), true);
+        Ok(())
+    }
+
+    /// Updates the document and returns its text
+    pub fn save(&mut self) -> Result<String, TomlError> {
+        self.update()?;
+        Ok(self.document.to_string())
+    }
+}
+
+impl<T> TomlDocument<T> {
+    /// The document as it was parsed or last updated
+    pub fn document(&self) -> &DocumentMut {
+        &self.document
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> std::ops::Deref for TomlDocument<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<T> std::ops::DerefMut for TomlDocument<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
+
+/// Returned if a TOML document can't be parsed, loaded or saved
+#[derive(Debug, Clone)]
+pub enum TomlError {
+    Parse(toml_edit::TomlError),
+    Load(toml_edit::de::Error),
+    Save(toml_edit::ser::Error),
+}
+
+impl Display for TomlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TomlError::Parse(error) => write!(f, "invalid TOML: {error}"),
+            TomlError::Load(error) => write!(f, "failed to load TOML: {error}"),
+            TomlError::Save(error) => write!(f, "failed to save TOML: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for TomlError {}
+
+/// Removes the keys missing in `saved` and updates the others.
+/// New tables become `[table]` sections if `table` is one itself.
+fn update_table(table: &mut dyn TableLike, saved: &dyn TableLike, is_section: bool) {
+    let removed: Vec<_> = table
+        .iter()
+        .map(|(key, _)| key.to_owned())
+        .filter(|key| !saved.contains_key(key))
+        .collect();
+    for key in removed {
+        table.remove(&key);
+    }
+
+    for (key, saved) in saved.iter() {
+        match table.get_mut(key) {
+            Some(item) => update_item(item, saved),
+            None => {
+                let item = match saved.clone() {
+                    Item::Value(Value::InlineTable(inline)) if is_section => {
+                        Item::Table(inline.into_table())
+                    }
+                    item => item,
+                };
+                table.insert(key, item);
+            }
+        }
+    }
+}
+
+/// Replaces the value of `item` if it changed, keeping its comments
+fn update_item(item: &mut Item, saved: &Item) {
+    let is_section = matches!(item, Item::Table(table) if !table.is_dotted());
+    if let (Some(table), Some(saved)) = (item.as_table_like_mut(), saved.as_table_like()) {
+        update_table(table, saved, is_section);
+        return;
+    }
+    let Ok(saved) = saved.clone().into_value() else {
+        return;
+    };
+    if item
+        .clone()
+        .into_value()
+        .is_ok_and(|x| value_eq(&x, &saved))
+    {
+        return;
+    }
+
+    match item {
+        Item::Value(value) => {
+            let decor = value.decor().clone();
+            *value = saved;
+            *value.decor_mut() = decor;
+        }
+        Item::ArrayOfTables(_) => {
+            *item = Item::Value(saved)
+                .into_array_of_tables()
+                .map_or_else(|item| item, Item::ArrayOfTables);
+        }
+        _ => *item = Item::Value(saved),
+    }
+}
+
+/// Compares values independent of their formatting
+fn value_eq(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::String(a), Value::String(b)) => a.value() == b.value(),
+        (Value::Integer(a), Value::Integer(b)) => a.value() == b.value(),
+        (Value::Float(a), Value::Float(b)) => a.value() == b.value(),
+        (Value::Boolean(a), Value::Boolean(b)) => a.value() == b.value(),
+        (Value::Datetime(a), Value::Datetime(b)) => a.value() == b.value(),
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| value_eq(a, b))
+        }
+        (Value::InlineTable(a), Value::InlineTable(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(key, a)| b.get(key).is_some_and(|b| value_eq(a, b)))
+        }
+        _ => false,
+    }
+}
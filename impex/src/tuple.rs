@@ -0,0 +1,346 @@
+use std::marker::PhantomData;
+
+use crate::{
+    Compact, DebugValue, DefaultChange, DefaultWrapperSettings, Impex, ImpexCompact, ImpexDefaults,
+    ImpexMerge, ImpexPath, IntoImpex, PathVisitor, VisitPath, WrapperSettings,
+    compact::{Presence, next_element},
+};
+
+/// Impex wrapper for tuples, e.g. `(u16, u16)`.
+/// Every element tracks its own explicitness, like the fields of a tuple struct.
+/// Implicit elements are saved as `null` and restored from the default when loading,
+/// so partially explicit tuples round-trip.
+/// Elements loaded as `null` are pending until [Impex::fill_defaults] supplies the default,
+/// so the element types don't need a default of their own.
+///
+/// Dereferences to the tuple of Impex elements, so `tuple.0` works as usual.
+/// Dereferencing panics while elements are pending, e.g. in a list element loaded with `null`.
+pub struct TupleImpex<T: TupleElements, TW = DefaultWrapperSettings> {
+    /// `Err` while elements loaded as `null` are pending
+    elements: Result<T, T::Pending>,
+    _phantom: PhantomData<TW>,
+}
+
+/// Tuples of Impex elements, implemented per tuple size
+pub trait TupleElements {
+    /// The elements which are loaded, the others are `None` until the default is filled in
+    type Pending;
+}
+
+impl<T: TupleElements, TW> TupleImpex<T, TW> {
+    fn new(elements: T) -> Self {
+        Self {
+            elements: Ok(elements),
+            _phantom: PhantomData,
+        }
+    }
+
+    fn pending(elements: T::Pending) -> Self {
+        Self {
+            elements: Err(elements),
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        match self.elements {
+            Ok(elements) => elements,
+            Err(_) => panic!("{PENDING_MESSAGE}"),
+        }
+    }
+}
+
+const PENDING_MESSAGE: &str =
+    "tuple elements loaded as `null` are pending until the default is filled in";
+
+impl<T: TupleElements + Default, TW> Default for TupleImpex<T, TW> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: TupleElements, TW> std::ops::Deref for TupleImpex<T, TW> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        match &self.elements {
+            Ok(elements) => elements,
+            Err(_) => panic!("{PENDING_MESSAGE}"),
+        }
+    }
+}
+
+impl<T: TupleElements, TW> std::ops::DerefMut for TupleImpex<T, TW> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match &mut self.elements {
+            Ok(elements) => elements,
+            Err(_) => panic!("{PENDING_MESSAGE}"),
+        }
+    }
+}
+
+/// Reads a tuple in the compact form, implemented per tuple size
+struct CompactTupleVisitor<T, TW>(PhantomData<(T, TW)>);
+
+/// Implements the Impex traits for a tuple with one type parameter and index per element
+macro_rules! impl_tuple {
+    ($($name:ident $index:tt),+) => {
+        impl<$($name),+> TupleElements for ($($name,)+) {
+            type Pending = ($(Option<$name>,)+);
+        }
+
+        impl<TW, $($name),+> TupleImpex<($($name,)+), TW> {
+            /// The loaded elements, which are all of them unless elements are pending
+            fn loaded(&self) -> ($(Option<&$name>,)+) {
+                match &self.elements {
+                    Ok(elements) => ($(Some(&elements.$index),)+),
+                    Err(pending) => ($(pending.$index.as_ref(),)+),
+                }
+            }
+
+            /// Stays pending if any element is missing
+            fn from_loaded(loaded: ($(Option<$name>,)+)) -> Self {
+                if true $(&& loaded.$index.is_some())+ {
+                    Self::new(($(loaded.$index.expect("the element is loaded"),)+))
+                } else {
+                    Self::pending(loaded)
+                }
+            }
+
+            fn into_loaded(self) -> ($(Option<$name>,)+) {
+                match self.elements {
+                    Ok(elements) => ($(Some(elements.$index),)+),
+                    Err(pending) => pending,
+                }
+            }
+        }
+
+        impl<TW, $($name: std::fmt::Debug),+> std::fmt::Debug for TupleImpex<($($name,)+), TW> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_struct("TupleImpex").field("elements", &self.elements).finish()
+            }
+        }
+
+        impl<TW, $($name: Clone),+> Clone for TupleImpex<($($name,)+), TW> {
+            fn clone(&self) -> Self {
+                Self {
+                    elements: self.elements.clone(),
+                    _phantom: PhantomData,
+                }
+            }
+        }
+
+        impl<TW, $($name: PartialEq),+> PartialEq for TupleImpex<($($name,)+), TW> {
+            fn eq(&self, other: &Self) -> bool {
+                self.elements == other.elements
+            }
+        }
+
+        impl<TW, $($name: Eq),+> Eq for TupleImpex<($($name,)+), TW> {}
+
+        impl<TW: WrapperSettings, $($name: IntoImpex<TW>),+> IntoImpex<TW> for ($($name,)+) {
+            type Impex = TupleImpex<($($name::Impex,)+), TW>;
+
+            fn into_impex(self, is_explicit: bool) -> Self::Impex {
+                TupleImpex::new(($(self.$index.into_impex(is_explicit),)+))
+            }
+        }
+
+        impl<TW, $($name: Impex<TW>),+> Impex<TW> for TupleImpex<($($name,)+), TW> {
+            type Value = ($($name::Value,)+);
+
+            fn is_explicit(&self) -> bool {
+                let loaded = self.loaded();
+                false $(|| loaded.$index.is_some_and(|element| element.is_explicit()))+
+            }
+
+            fn into_value(self) -> Self::Value {
+                let elements = self.into_inner();
+                ($(elements.$index.into_value(),)+)
+            }
+
+            fn set_impex(&mut self, v: Self::Value, is_explicit: bool) {
+                $(self.$index.set_impex(v.$index, is_explicit);)+
+            }
+
+            /// Pending elements are taken from the default
+            fn fill_defaults(&mut self, default: Self) {
+                let default = default.into_inner();
+                let loaded = std::mem::replace(self, Self::pending(($(None::<$name>,)+))).into_loaded();
+                *self = Self::new(($(
+                    match loaded.$index {
+                        Some(mut element) => {
+                            element.fill_defaults(default.$index);
+                            element
+                        }
+                        None => default.$index,
+                    },
+                )+));
+            }
+        }
+
+        /// Tuples are compared, pruned and rebased element by element
+        impl<TW: WrapperSettings, $($name: ImpexDefaults<TW>),+> ImpexDefaults<TW> for TupleImpex<($($name,)+), TW> {
+            fn value_eq(&self, other: &Self) -> bool {
+                true $(&& self.$index.value_eq(&other.$index))+
+            }
+
+            fn prune_defaults_with(&mut self, default: &Self) {
+                $(self.$index.prune_defaults_with(&default.$index);)+
+            }
+
+            fn fmt_value(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_tuple("")
+                    $(.field(&DebugValue::<TW, $name>::new(&self.$index)))+
+                    .finish()
+            }
+
+            fn rebase_defaults_at(
+                &mut self,
+                new_default: Self,
+                path: &ImpexPath,
+                changes: &mut Vec<DefaultChange>,
+            ) {
+                let new_default = new_default.into_inner();
+                $(self.$index.rebase_defaults_at(new_default.$index, &path.join(stringify!($index)), changes);)+
+            }
+        }
+
+        impl<TW: WrapperSettings, $($name: ImpexMerge<TW>),+> ImpexMerge<TW> for TupleImpex<($($name,)+), TW> {
+            /// Elements pending in the overlay are left alone, like implicit ones
+            fn merge(&mut self, overlay: Self) {
+                let overlay = overlay.into_loaded();
+                let mut loaded = std::mem::replace(self, Self::pending(($(None::<$name>,)+))).into_loaded();
+                $(match (&mut loaded.$index, overlay.$index) {
+                    (Some(element), Some(overlay)) => element.merge(overlay),
+                    (element @ None, overlay) => *element = overlay,
+                    (Some(_), None) => {}
+                })+
+                *self = Self::from_loaded(loaded);
+            }
+        }
+
+        impl<TW: WrapperSettings, $($name: VisitPath<TW>),+> VisitPath<TW> for TupleImpex<($($name,)+), TW>
+        where
+            Self: serde::de::DeserializeOwned,
+        {
+            fn visit_path<V: PathVisitor<TW>>(&mut self, path: &[&str], visitor: &mut V) -> bool {
+                match path.split_first() {
+                    None => {
+                        visitor.visit(self);
+                        true
+                    }
+                    $(Some((&stringify!($index), rest)) => match &mut self.elements {
+                        Ok(elements) => elements.$index.visit_path(rest, visitor),
+                        Err(pending) => pending.$index.as_mut().is_some_and(|element| element.visit_path(rest, visitor)),
+                    },)+
+                    Some(_) => false,
+                }
+            }
+        }
+
+        impl<TW, $($name: serde::Serialize),+> serde::Serialize for TupleImpex<($($name,)+), TW> {
+            fn serialize<Ser>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error>
+            where
+                Ser: serde::Serializer,
+            {
+                self.loaded().serialize(serializer)
+            }
+        }
+
+        /// `null` elements are pending until they get their value from the default
+        impl<'de, TW, $($name: serde::Deserialize<'de>),+> serde::Deserialize<'de>
+            for TupleImpex<($($name,)+), TW>
+        {
+            fn deserialize<De>(deserializer: De) -> Result<Self, De::Error>
+            where
+                De: serde::Deserializer<'de>,
+            {
+                let loaded = <($(Option<$name>,)+)>::deserialize(deserializer)?;
+                Ok(Self::from_loaded(loaded))
+            }
+        }
+
+        /// Like a tuple struct, only the explicit elements are written after a presence bitmap
+        impl<TW: WrapperSettings, $($name: ImpexCompact<TW>),+> ImpexCompact<TW> for TupleImpex<($($name,)+), TW> {
+            fn serialize_compact<Ser: serde::Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+                use serde::ser::SerializeTuple;
+                let loaded = self.loaded();
+                let presence = Presence::new([$(loaded.$index.is_some_and(|element| element.is_explicit())),+]);
+                let mut tuple = serializer.serialize_tuple(1 + presence.count())?;
+                tuple.serialize_element(&presence)?;
+                $(if let Some(element) = loaded.$index.filter(|_| presence.is_present($index)) {
+                    tuple.serialize_element(&Compact::<_, TW>::new(element))?;
+                })+
+                tuple.end()
+            }
+
+            fn deserialize_compact<'de, De: serde::Deserializer<'de>>(
+                deserializer: De,
+            ) -> Result<Self, De::Error> {
+                let length = [$($index),+].len();
+                deserializer.deserialize_tuple(1 + length, CompactTupleVisitor::<($($name,)+), TW>(PhantomData))
+            }
+        }
+
+        /// Elements missing in the bitmap are pending, like `null` elements
+        impl<'de, TW: WrapperSettings, $($name: ImpexCompact<TW>),+> serde::de::Visitor<'de>
+            for CompactTupleVisitor<($($name,)+), TW>
+        {
+            type Value = TupleImpex<($($name,)+), TW>;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a tuple in the compact form")
+            }
+
+            fn visit_seq<Seq: serde::de::SeqAccess<'de>>(self, mut seq: Seq) -> Result<Self::Value, Seq::Error> {
+                let presence: Presence<{ [$($index),+].len() }> = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let mut index = 1;
+                Ok(Self::Value::from_loaded(($(
+                    if presence.is_present($index) {
+                        Some(next_element::<_, $name, TW>(&mut seq, &mut index, &self)?)
+                    } else {
+                        None
+                    },
+                )+)))
+            }
+        }
+
+        /// `null` elements are implicit, like when loading
+        #[cfg(feature = "schema")]
+        impl<TW: WrapperSettings, $($name: crate::schema::ImpexSchema<TW>),+> crate::schema::ImpexSchema<TW>
+            for TupleImpex<($($name,)+), TW>
+        {
+            fn json_schema() -> crate::schema::Schema {
+                crate::schema::fixed_array(vec![$(crate::schema::nullable($name::json_schema())),+])
+            }
+        }
+
+        #[cfg(feature = "visitor")]
+        impl<Ctx, TW, $($name: crate::Visitor<Ctx>),+> crate::Visitor<Ctx> for TupleImpex<($($name,)+), TW> {
+            fn visit(&mut self, ctx: &mut Ctx) {
+                match &mut self.elements {
+                    Ok(elements) => {
+                        $(elements.$index.visit(ctx);)+
+                    }
+                    Err(pending) => {
+                        $(if let Some(element) = &mut pending.$index {
+                            element.visit(ctx);
+                        })+
+                    }
+                }
+            }
+        }
+    };
+}
+
+impl_tuple!(A 0);
+impl_tuple!(A 0, B 1);
+impl_tuple!(A 0, B 1, C 2);
+impl_tuple!(A 0, B 1, C 2, D 3);
+impl_tuple!(A 0, B 1, C 2, D 3, E 4);
+impl_tuple!(A 0, B 1, C 2, D 3, E 4, F 5);
+impl_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
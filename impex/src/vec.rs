@@ -1,41 +1,166 @@
-use crate::{Impex, IntoImpex, WrapperSettings};
+use std::collections::VecDeque;
 
-impl<TW: WrapperSettings, T: IntoImpex<TW>> IntoImpex<TW> for Vec<T> {
-    type Impex = Vec<T::Impex>;
+use crate::{
+    CollectionImpex, DebugValue, DefaultChange, Impex, ImpexCompact, ImpexDefaults, ImpexMerge,
+    ImpexPath, IntoImpex, PathVisitor, VisitPath, WrapperSettings,
+    compact::{CompactSeq, CompactVecSeed},
+    merge_explicit_value, rebase_implicit_value,
+};
 
-    fn into_impex(self, is_explicit: bool) -> Self::Impex {
-        self.into_iter()
-            .map(|x| x.into_impex(is_explicit))
-            .collect()
-    }
-}
+/// Implements the Impex traits for a list type, which all share the same semantics
+macro_rules! impl_list {
+    ($list:ident) => {
+        impl<TW: WrapperSettings, T: IntoImpex<TW>> IntoImpex<TW> for $list<T> {
+            type Impex = CollectionImpex<$list<T::Impex>>;
 
-impl<TW: WrapperSettings, T: Impex<TW>> Impex<TW> for Vec<T>
-where
-    T::Value: IntoImpex<TW, Impex = T>,
-{
-    type Value = Vec<T::Value>;
+            fn into_impex(self, is_explicit: bool) -> Self::Impex {
+                let items = self
+                    .into_iter()
+                    .map(|x| x.into_impex(is_explicit))
+                    .collect();
+                CollectionImpex::new(items, is_explicit)
+            }
+        }
 
-    fn is_explicit(&self) -> bool {
-        self.iter().any(|x| x.is_explicit())
-    }
+        impl<TW: WrapperSettings, T: Impex<TW>> Impex<TW> for CollectionImpex<$list<T>>
+        where
+            T::Value: IntoImpex<TW, Impex = T>,
+        {
+            type Value = $list<T::Value>;
 
-    fn into_value(self) -> Self::Value {
-        self.into_iter().map(|x| x.into_value()).collect()
-    }
+            fn is_explicit(&self) -> bool {
+                self.is_explicit_flag() || self.iter().any(|x| x.is_explicit())
+            }
 
-    fn set_impex(&mut self, v: Self::Value, is_explicit: bool) {
-        self.clear();
-        self.extend(v.into_iter().map(|x| x.into_impex(is_explicit)));
-    }
-}
+            fn into_value(self) -> Self::Value {
+                self.into_inner()
+                    .into_iter()
+                    .map(|x| x.into_value())
+                    .collect()
+            }
+
+            fn set_impex(&mut self, v: Self::Value, is_explicit: bool) {
+                *self = v.into_impex(is_explicit);
+            }
+
+            fn fill_defaults(&mut self, default: Self) {
+                if self.is_implicit() {
+                    *self = default;
+                }
+            }
+        }
+
+        /// Lists are compared as a whole, so they never end up partially explicit
+        impl<TW: WrapperSettings, T: ImpexDefaults<TW>> ImpexDefaults<TW>
+            for CollectionImpex<$list<T>>
+        where
+            T::Value: IntoImpex<TW, Impex = T>,
+        {
+            fn value_eq(&self, other: &Self) -> bool {
+                self.len() == other.len() && self.iter().zip(other).all(|(a, b)| a.value_eq(b))
+            }
+
+            fn prune_defaults_with(&mut self, default: &Self) {
+                if self.value_eq(default) {
+                    self.set_explicit_flag(false);
+                    self.items_mut().iter_mut()
+                        .zip(default)
+                        .for_each(|(value, default)| value.prune_defaults_with(default));
+                }
+            }
+
+            fn fmt_value(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_list()
+                    .entries(self.iter().map(DebugValue::<TW, T>::new))
+                    .finish()
+            }
 
-#[cfg(feature = "visitor")]
-impl<T, U> crate::Visitor<T> for Vec<U>
-where
-    U: crate::Visitor<T>,
-{
-    fn visit(&mut self, ctx: &mut T) {
-        self.iter_mut().for_each(|x| x.visit(ctx));
-    }
+            fn rebase_defaults_at(
+                &mut self,
+                new_default: Self,
+                path: &ImpexPath,
+                changes: &mut Vec<DefaultChange>,
+            ) {
+                rebase_implicit_value(self, new_default, path, changes);
+            }
+        }
+
+        /// Explicit lists replace the whole list
+        impl<TW: WrapperSettings, T: Impex<TW>> ImpexMerge<TW> for CollectionImpex<$list<T>>
+        where
+            T::Value: IntoImpex<TW, Impex = T>,
+        {
+            fn merge(&mut self, overlay: Self) {
+                merge_explicit_value(self, overlay);
+            }
+        }
+
+        impl<TW: WrapperSettings, T: VisitPath<TW>> VisitPath<TW> for CollectionImpex<$list<T>>
+        where
+            T::Value: IntoImpex<TW, Impex = T>,
+        {
+            fn visit_path<V: PathVisitor<TW>>(&mut self, path: &[&str], visitor: &mut V) -> bool {
+                let Some((index, rest)) = path.split_first() else {
+                    visitor.visit(self);
+                    return true;
+                };
+                let found = index
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| self.items_mut().get_mut(index))
+                    .is_some_and(|value| value.visit_path(rest, visitor));
+                // Lists are saved as a whole, so the other elements need to be written as well
+                if found {
+                    self.materialize_defaults();
+                }
+                found
+            }
+        }
+
+        /// Lists are written as a whole and loaded as explicit
+        impl<TW: WrapperSettings, T: ImpexCompact<TW>> ImpexCompact<TW>
+            for CollectionImpex<$list<T>>
+        where
+            T::Value: IntoImpex<TW, Impex = T>,
+        {
+            fn serialize_compact<S: serde::Serializer>(
+                &self,
+                serializer: S,
+            ) -> Result<S::Ok, S::Error> {
+                serde::Serialize::serialize(&CompactSeq::<_, TW>::new(self.iter()), serializer)
+            }
+
+            fn deserialize_compact<'de, D: serde::Deserializer<'de>>(
+                deserializer: D,
+            ) -> Result<Self, D::Error> {
+                use serde::de::DeserializeSeed;
+                let items = CompactVecSeed::<T, TW>::new().deserialize(deserializer)?;
+                Ok(CollectionImpex::new(items.into_iter().collect(), true))
+            }
+        }
+
+        #[cfg(feature = "schema")]
+        impl<TW: WrapperSettings, T: crate::schema::ImpexSchema<TW>> crate::schema::ImpexSchema<TW>
+            for CollectionImpex<$list<T>>
+        where
+            T::Value: IntoImpex<TW, Impex = T>,
+        {
+            fn json_schema() -> crate::schema::Schema {
+                serde_json::json!({ "type": "array", "items": T::json_schema() })
+            }
+        }
+
+        #[cfg(feature = "visitor")]
+        impl<T, U> crate::Visitor<T> for $list<U>
+        where
+            U: crate::Visitor<T>,
+        {
+            fn visit(&mut self, ctx: &mut T) {
+                self.iter_mut().for_each(|x| x.visit(ctx));
+            }
+        }
+    };
 }
+
+impl_list!(Vec);
+impl_list!(VecDeque);
@@ -0,0 +1,249 @@
+use std::collections::BTreeMap;
+
+use impex::{
+    Impex, ImpexPath,
+    catalog::{Setting, SettingsCatalog},
+    jsonc::JsoncDocument,
+    toml::TomlDocument,
+};
+
+use crate::generated_struct::KeyStructConfigImpex;
+
+#[allow(unused)]
+mod generated_struct;
+
+/// Settings of the proxy
+#[derive(impex::Impex)]
+pub struct Proxy {
+    /// Upstream servers by name,
+    /// e.g. `{"web": "10.0.0.1"}`
+    pub upstreams: BTreeMap<String, String>,
+    pub mode: Mode,
+    /// Overrides the documentation of `Mode`
+    pub fallback: Mode,
+    pub timeouts: Timeouts,
+    #[impex(list = "extend")]
+    pub hosts: Vec<String>,
+}
+
+impl Default for Proxy {
+    fn default() -> Self {
+        Self {
+            upstreams: Default::default(),
+            mode: Default::default(),
+            fallback: Default::default(),
+            timeouts: Default::default(),
+            hosts: vec!["localhost".into()],
+        }
+    }
+}
+
+/// How requests are forwarded
+#[derive(Default, impex::Impex)]
+pub enum Mode {
+    #[default]
+    Direct,
+    Balanced(u8),
+}
+
+/// Timeouts in milliseconds
+#[derive(impex::Impex)]
+#[impex(track_presence)]
+pub struct Timeouts {
+    /// Time | to connect
+    pub connect: u32,
+    pub window: (u16, u16),
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            connect: 500,
+            window: (1, 2),
+        }
+    }
+}
+
+fn setting(
+    path: &str,
+    type_name: &str,
+    default: Option<&str>,
+    description: Option<&str>,
+) -> Setting {
+    Setting {
+        path: ImpexPath::from(path),
+        type_name: type_name.into(),
+        default: default.map(Into::into),
+        description: description.map(Into::into),
+        meta: None,
+    }
+}
+
+#[test]
+fn settings_are_listed_in_declaration_order() {
+    let catalog = SettingsCatalog::new::<_, KeyStructConfigImpex>();
+    assert_eq!(None, catalog.description);
+    assert_eq!(
+        vec![
+            setting("num_cores", "u32", Some("0"), None),
+            setting("num_threads", "Vec<u32>", Some("[42]"), None),
+            setting(
+                "enum_config",
+                "EnumConfig",
+                Some(r#"{"Bar":["Bar",42,[42,43]]}"#),
+                None
+            ),
+            setting(
+                "tuple_struct_config",
+                "TupleStructConfig",
+                Some("[42,43]"),
+                None
+            ),
+        ],
+        catalog.settings
+    );
+}
+
+#[test]
+fn nested_structs_are_sections() {
+    let catalog = SettingsCatalog::new::<_, ProxyImpex>();
+    assert_eq!(
+        Some("Settings of the proxy"),
+        catalog.description.as_deref()
+    );
+    assert_eq!(
+        vec![
+            setting(
+                "upstreams",
+                "BTreeMap<String, String>",
+                Some("{}"),
+                Some("Upstream servers by name,\ne.g. `{\"web\": \"10.0.0.1\"}`")
+            ),
+            setting(
+                "mode",
+                "Mode",
+                Some(r#""Direct""#),
+                Some("How requests are forwarded")
+            ),
+            setting(
+                "fallback",
+                "Mode",
+                Some(r#""Direct""#),
+                Some("Overrides the documentation of `Mode`")
+            ),
+            setting(
+                "timeouts",
+                "Timeouts",
+                None,
+                Some("Timeouts in milliseconds")
+            ),
+            setting(
+                "timeouts.connect",
+                "u32",
+                Some("500"),
+                Some("Time | to connect")
+            ),
+            setting("timeouts.window", "(u16, u16)", Some("[1,2]"), None),
+            setting("hosts", "Vec<String>", Some(r#"["localhost"]"#), None),
+        ],
+        catalog.settings
+    );
+}
+
+#[test]
+fn catalog_is_rendered_as_markdown_table() {
+    let markdown = SettingsCatalog::new::<_, ProxyImpex>().to_markdown();
+    let expected = r#"Settings of the proxy
+
+| Setting | Type | Default | Description |
+| --- | --- | --- | --- |
+| `upstreams` | `BTreeMap<String, String>` | `{}` | Upstream servers by name, e.g. `{"web": "10.0.0.1"}` |
+| `mode` | `Mode` | `"Direct"` | How requests are forwarded |
+| `fallback` | `Mode` | `"Direct"` | Overrides the documentation of `Mode` |
+| `timeouts` | `Timeouts` |  | Timeouts in milliseconds |
+| `timeouts.connect` | `u32` | `500` | Time \| to connect |
+| `timeouts.window` | `(u16, u16)` | `[1,2]` |  |
+| `hosts` | `Vec<String>` | `["localhost"]` |  |
+"#;
+    assert_eq!(expected, markdown);
+}
+
+#[derive(impex::Impex)]
+pub struct Optional {
+    pub limit: Option<u32>,
+    pub values: Vec<Option<u32>>,
+}
+
+impl Default for Optional {
+    fn default() -> Self {
+        Self {
+            limit: None,
+            values: vec![Some(1), None],
+        }
+    }
+}
+
+#[test]
+fn jsonc_example_loads_as_implicit() {
+    let example = SettingsCatalog::new::<_, ProxyImpex>().to_jsonc_example();
+    let expected = r#"// Settings of the proxy
+{
+  // Upstream servers by name,
+  // e.g. `{"web": "10.0.0.1"}`
+  // "upstreams": {},
+  // How requests are forwarded
+  // "mode": "Direct",
+  // Overrides the documentation of `Mode`
+  // "fallback": "Direct",
+  // Timeouts in milliseconds
+  // "timeouts": {
+  //   // Time | to connect
+  //   "connect": 500,
+  //   "window": [1,2],
+  // },
+  // "hosts": ["localhost"],
+}
+"#;
+    assert_eq!(expected, example);
+
+    let document = JsoncDocument::<ProxyImpex>::parse(&example).unwrap();
+    assert!(document.is_implicit());
+    assert!(document.timeouts.is_implicit());
+}
+
+#[test]
+fn toml_example_loads_as_implicit() {
+    let example = SettingsCatalog::new::<_, ProxyImpex>().to_toml_example();
+    let expected = r#"# Settings of the proxy
+
+# Upstream servers by name,
+# e.g. `{"web": "10.0.0.1"}`
+# upstreams = {}
+# How requests are forwarded
+# mode = "Direct"
+# Overrides the documentation of `Mode`
+# fallback = "Direct"
+# hosts = ["localhost"]
+
+# Timeouts in milliseconds
+# [timeouts]
+# Time | to connect
+# connect = 500
+# window = [1, 2]
+"#;
+    assert_eq!(expected, example);
+
+    let document = TomlDocument::<ProxyImpex>::parse(&example).unwrap();
+    assert!(document.is_implicit());
+    assert!(document.timeouts.is_implicit());
+}
+
+#[test]
+fn defaults_without_toml_representation_are_named() {
+    let catalog = SettingsCatalog::new::<_, OptionalImpex>();
+    assert_eq!(
+        "# limit is not set by default\n\
+         # values: the default [1,null] can't be written in TOML\n",
+        catalog.to_toml_example()
+    );
+}
@@ -0,0 +1,184 @@
+use impex::{
+    ImpexMerge,
+    cli::{self, CliError},
+};
+
+use crate::generated_struct::{EnumConfigImpex, KeyStructConfigImpex};
+
+#[allow(unused)]
+mod generated_struct;
+
+#[derive(Default, impex::Impex)]
+pub struct Options {
+    pub verbose: bool,
+    pub level: u8,
+}
+
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+#[test]
+fn flags_are_generated_for_leaves() {
+    let flags = cli::flags::<_, KeyStructConfigImpex>();
+    let names: Vec<_> = flags
+        .iter()
+        .map(|flag| (flag.name.as_str(), flag.value_name))
+        .collect();
+    assert_eq!(
+        [
+            ("num-cores", "N"),
+            ("num-threads", "JSON"),
+            ("enum-config", "JSON"),
+            ("enum-config.bar.0", "TEXT"),
+            ("enum-config.bar.1", "N"),
+            ("enum-config.bar.2", "JSON"),
+            ("enum-config.bar.2.0", "N"),
+            ("enum-config.bar.2.1", "N"),
+            ("tuple-struct-config", "JSON"),
+            ("tuple-struct-config.0", "N"),
+            ("tuple-struct-config.1", "N"),
+        ],
+        names.as_slice()
+    );
+    assert_eq!("enum_config.Bar.1", flags[4].path.to_string());
+    assert_eq!(Some("42"), flags[4].default.as_deref());
+}
+
+#[test]
+fn flags_and_assignments_set_explicit_values() {
+    let overlay = cli::overlay::<_, KeyStructConfigImpex>(&args(&[
+        "run",
+        "--num-cores",
+        "4",
+        "--enum-config.bar.1=43",
+        "--set",
+        "tuple_struct_config.0=7",
+        "--tuple-struct-config.1",
+        "8",
+        "--unknown",
+        "--",
+        "--num-cores",
+        "5",
+    ]))
+    .unwrap();
+    assert_eq!(
+        ["run", "--unknown", "--", "--num-cores", "5"],
+        overlay.remaining.as_slice()
+    );
+
+    let mut config: KeyStructConfigImpex = Default::default();
+    config.merge(overlay.value);
+    assert_eq!(4, *config.num_cores);
+    assert_eq!(7, *config.tuple_struct_config.0);
+    assert_eq!(
+        r#"{"num_cores":4,"enum_config":{"Bar":[null,43,[null,null]]},"tuple_struct_config":[7,8]}"#,
+        serde_json::to_string(&config).unwrap()
+    );
+}
+
+#[test]
+fn composite_values_are_json() {
+    let mut config: KeyStructConfigImpex = Default::default();
+    cli::apply(
+        &mut config,
+        &args(&[
+            "--num-threads",
+            "[1, 2]",
+            "--enum-config",
+            r#"{"Foo":{"foo_value":"x","tuple_struct_config":[1,2]}}"#,
+        ]),
+    )
+    .unwrap();
+    assert_eq!(2, config.num_threads.len());
+    let EnumConfigImpex::Foo { foo_value, .. } = &config.enum_config else {
+        panic!("expected the variant from the argument");
+    };
+    assert_eq!("x", **foo_value);
+}
+
+#[derive(impex::Impex)]
+pub struct PathsConfig {
+    #[impex(list = "extend")]
+    pub paths: Vec<String>,
+}
+
+impl Default for PathsConfig {
+    fn default() -> Self {
+        Self {
+            paths: vec!["/usr/lib".into()],
+        }
+    }
+}
+
+#[test]
+fn extend_lists_are_set_as_a_whole() {
+    let flags = cli::flags::<_, PathsConfigImpex>();
+    let names: Vec<_> = flags
+        .iter()
+        .map(|flag| (flag.name.as_str(), flag.value_name))
+        .collect();
+    assert_eq!([("paths", "JSON")], names.as_slice());
+
+    let mut config: PathsConfigImpex = serde_json::from_str(r#"{"paths":{"+":["/x"]}}"#).unwrap();
+    let remaining = cli::apply(&mut config, &args(&["--paths", r#"["/q","/usr/lib"]"#])).unwrap();
+    assert!(remaining.is_empty());
+    let paths: Vec<_> = config.paths.iter().map(|x| x.as_str()).collect();
+    assert_eq!(vec!["/q", "/usr/lib"], paths);
+    assert_eq!(
+        r#"{"paths":{"^":["/q"]}}"#,
+        serde_json::to_string(&config).unwrap()
+    );
+}
+
+#[test]
+fn switches_take_an_optional_value() {
+    let overlay = cli::overlay::<_, OptionsImpex>(&args(&["--verbose", "--level", "2"])).unwrap();
+    assert!(*overlay.value.verbose);
+    assert_eq!(2, *overlay.value.level);
+
+    let overlay = cli::overlay::<_, OptionsImpex>(&args(&["--verbose", "false", "x"])).unwrap();
+    assert!(!*overlay.value.verbose);
+    assert!(overlay.value.verbose.is_explicit());
+    assert_eq!(["x"], overlay.remaining.as_slice());
+}
+
+#[test]
+fn invalid_arguments_are_errors() {
+    let result = cli::overlay::<_, OptionsImpex>(&args(&["--level"]));
+    assert!(matches!(result, Err(CliError::MissingValue(arg)) if arg == "--level"));
+
+    let result = cli::overlay::<_, OptionsImpex>(&args(&["--set", "levels=1"]));
+    assert!(matches!(result, Err(CliError::UnknownPath(path)) if path.to_string() == "levels"));
+
+    let result = cli::overlay::<_, OptionsImpex>(&args(&["--level=300"]));
+    let Err(CliError::InvalidValue { path, .. }) = result else {
+        panic!("expected an invalid value");
+    };
+    assert_eq!("level", path.to_string());
+}
+
+#[test]
+fn clap_arguments_are_applied_in_order() {
+    let command = clap::Command::new("app")
+        .arg(clap::Arg::new("input"))
+        .args(cli::clap_args::<_, OptionsImpex>());
+    let matches = command
+        .try_get_matches_from([
+            "app",
+            "--level",
+            "1",
+            "--verbose",
+            "--set",
+            "level=3",
+            "input.txt",
+        ])
+        .unwrap();
+    let options = cli::from_clap_matches::<_, OptionsImpex>(&matches).unwrap();
+    assert!(*options.verbose);
+    assert_eq!(3, *options.level);
+    assert_eq!(
+        Some("input.txt"),
+        matches.get_one::<String>("input").map(String::as_str)
+    );
+}
@@ -1,4 +1,4 @@
-use impex::{DefaultWrapperSettings, Impex};
+use impex::{DefaultWrapperSettings, Impex, ImpexMerge, VisitPath};
 use serde::{Deserialize, Serialize};
 
 #[test]
@@ -18,3 +18,175 @@ fn array_forward_children() {
     );
     assert_eq!(text, serde_json::to_string(&x).unwrap());
 }
+
+#[derive(Clone, impex::Impex)]
+struct ListConfig {
+    list: Vec<u32>,
+    array: [u32; 0],
+}
+
+impl Default for ListConfig {
+    fn default() -> Self {
+        Self {
+            list: vec![42],
+            array: [],
+        }
+    }
+}
+
+#[test]
+fn explicitly_empty_list_survives_round_trip() {
+    let text = r#"{"list":[],"array":[]}"#;
+    let x: ListConfigImpex = serde_json::from_str(text).unwrap();
+    assert!(x.list.is_empty());
+    assert!(x.list.is_explicit());
+    assert!(x.array.is_explicit());
+    assert_eq!(text, serde_json::to_string(&x).unwrap());
+
+    let x: ListConfigImpex = serde_json::from_str("{}").unwrap();
+    assert_eq!(vec![42], x.list.iter().map(|x| **x).collect::<Vec<_>>());
+    assert!(x.list.is_implicit());
+    assert_eq!("{}", serde_json::to_string(&x).unwrap());
+}
+
+#[test]
+fn cleared_list_stays_explicit() {
+    let mut x: ListConfigImpex = Default::default();
+    x.list.edit().clear();
+    assert_eq!(r#"{"list":[]}"#, serde_json::to_string(&x).unwrap());
+}
+
+#[test]
+fn edited_list_survives_loading() {
+    let mut x: ListConfigImpex = Default::default();
+    x.list.edit()[0] = 42;
+    assert!(x.list.is_implicit());
+
+    x.list.edit().push(7);
+    let text = serde_json::to_string(&x).unwrap();
+    assert_eq!(r#"{"list":[42,7]}"#, text);
+    let x: ListConfigImpex = serde_json::from_str(&text).unwrap();
+    assert_eq!(vec![42, 7], x.list.iter().map(|x| **x).collect::<Vec<_>>());
+}
+
+#[test]
+fn list_element_materialized_by_path_survives_loading() {
+    let mut x: ContainerConfigImpex = Default::default();
+    x.materialize_defaults_at(&"shared.list.0".into()).unwrap();
+    x.materialize_defaults_at(&"queue.0".into()).unwrap();
+    let text = serde_json::to_string(&x).unwrap();
+    assert_eq!(r#"{"queue":[3],"shared":{"list":[42]}}"#, text);
+
+    let mut x: ListConfigImpex = serde_json::from_str(r#"{"list":[1,2]}"#).unwrap();
+    x.list.set_implicit(vec![1, 2]);
+    x.materialize_defaults_at(&"list.1".into()).unwrap();
+    let text = serde_json::to_string(&x).unwrap();
+    assert_eq!(r#"{"list":[1,2]}"#, text);
+    let loaded: ListConfigImpex = serde_json::from_str(&text).unwrap();
+    assert_eq!(text, serde_json::to_string(&loaded).unwrap());
+}
+
+#[derive(impex::Impex)]
+struct ContainerConfig {
+    range: (u16, u16),
+    queue: std::collections::VecDeque<u32>,
+    tags: std::collections::BTreeSet<String>,
+    shared: std::sync::Arc<ListConfig>,
+    name: std::borrow::Cow<'static, str>,
+    boxed: Box<u32>,
+    boxed_struct: Box<ListConfig>,
+}
+
+impl Default for ContainerConfig {
+    fn default() -> Self {
+        Self {
+            range: (1, 2),
+            queue: [3].into(),
+            tags: ["a".to_string()].into(),
+            shared: Default::default(),
+            name: "default".into(),
+            boxed: Box::new(4),
+            boxed_struct: Default::default(),
+        }
+    }
+}
+
+#[test]
+fn tuple_elements_round_trip() {
+    let mut x: ContainerConfigImpex = Default::default();
+    x.range.1.set_explicit(5);
+    let text = r#"{"range":[null,5]}"#;
+    assert_eq!(text, serde_json::to_string(&x).unwrap());
+
+    let x: ContainerConfigImpex = serde_json::from_str(text).unwrap();
+    assert_eq!((1, 5), (*x.range.0, *x.range.1));
+    assert!(x.range.0.is_implicit());
+    assert_eq!(text, serde_json::to_string(&x).unwrap());
+}
+
+#[derive(impex::Impex)]
+struct ListenConfig {
+    listen: (std::net::Ipv4Addr, u16),
+}
+
+impl Default for ListenConfig {
+    fn default() -> Self {
+        Self {
+            listen: (std::net::Ipv4Addr::LOCALHOST, 80),
+        }
+    }
+}
+
+#[test]
+fn tuple_elements_without_default_are_loaded() {
+    let text = r#"{"listen":[null,8080]}"#;
+    let mut x: ListenConfigImpex = serde_json::from_str(text).unwrap();
+    assert_eq!(std::net::Ipv4Addr::LOCALHOST, *x.listen.0);
+    assert!(x.listen.0.is_implicit());
+    assert_eq!(8080, *x.listen.1);
+    assert_eq!(text, serde_json::to_string(&x).unwrap());
+
+    // Elements which are `null` in an overlay are left alone
+    x.listen
+        .merge(serde_json::from_str(r#"["0.0.0.0",null]"#).unwrap());
+    assert_eq!(std::net::Ipv4Addr::UNSPECIFIED, *x.listen.0);
+    assert_eq!(8080, *x.listen.1);
+}
+
+#[test]
+fn containers_and_pointers_are_tracked() {
+    let text = r#"{"queue":[],"tags":["b"],"shared":{"list":[7]},"name":"x","boxed":8}"#;
+    let x: ContainerConfigImpex = serde_json::from_str(text).unwrap();
+    assert!(x.queue.is_empty() && x.queue.is_explicit());
+    assert!(x.tags.is_explicit());
+    assert!(x.shared.list.is_explicit());
+    assert!(x.shared.array.is_implicit());
+    assert_eq!("x", &**x.name);
+    assert_eq!(8, **x.boxed);
+    assert_eq!(text, serde_json::to_string(&x).unwrap());
+
+    let x: ContainerConfigImpex = serde_json::from_str("{}").unwrap();
+    assert_eq!(
+        vec![42],
+        x.shared.list.iter().map(|x| **x).collect::<Vec<_>>()
+    );
+    assert_eq!("{}", serde_json::to_string(&x).unwrap());
+}
+
+#[test]
+fn boxed_structs_are_transparent() {
+    let text = r#"{"boxed_struct":{"array":[]}}"#;
+    let mut x: ContainerConfigImpex = serde_json::from_str(text).unwrap();
+    assert!(x.boxed_struct.array.is_explicit());
+    assert!(x.boxed_struct.list.is_implicit());
+    assert_eq!(42, *x.boxed_struct.list[0]);
+    assert_eq!(text, serde_json::to_string(&x).unwrap());
+
+    x.boxed_struct.list.edit().push(1);
+    assert_eq!(
+        r#"{"boxed_struct":{"list":[42,1],"array":[]}}"#,
+        serde_json::to_string(&x).unwrap()
+    );
+    let value = x.into_value();
+    assert_eq!(vec![42, 1], value.boxed_struct.list);
+}
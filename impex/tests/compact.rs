@@ -0,0 +1,162 @@
+use std::collections::BTreeMap;
+
+use impex::{Compact, DefaultWrapperSettings, Impex};
+
+#[derive(Clone, PartialEq, impex::Impex)]
+pub struct Limits {
+    pub connections: u32,
+    pub timeout: u64,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            connections: 100,
+            timeout: 30,
+        }
+    }
+}
+
+#[derive(Clone, impex::Impex)]
+pub enum Mode {
+    Off,
+    Fixed(u32),
+    Dynamic { min: u32, max: u32 },
+}
+
+impl Default for Mode {
+    fn default() -> Self {
+        Self::Dynamic { min: 1, max: 8 }
+    }
+}
+
+#[derive(impex::Impex)]
+pub struct ServerConfig {
+    pub name: String,
+    pub port: u16,
+    pub limits: Limits,
+    pub mode: Mode,
+    pub proxy: Option<String>,
+    pub tags: Vec<String>,
+    pub routes: BTreeMap<String, u16>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            name: "server".into(),
+            port: 8080,
+            limits: Limits::default(),
+            mode: Mode::default(),
+            proxy: Some("proxy".into()),
+            tags: vec!["a".into()],
+            routes: BTreeMap::from([("/".into(), 80)]),
+        }
+    }
+}
+
+fn round_trip(config: &ServerConfigImpex) -> ServerConfigImpex {
+    let bytes = bincode::serialize(&Compact::new(config)).unwrap();
+    bincode::deserialize::<Compact<ServerConfigImpex>>(&bytes)
+        .unwrap()
+        .into_inner()
+}
+
+#[test]
+fn implicit_values_are_left_out() {
+    let config: ServerConfigImpex = Default::default();
+    let bytes = bincode::serialize(&Compact::new(&config)).unwrap();
+    // Only the presence bitmap of the 7 fields
+    assert_eq!(vec![0], bytes);
+
+    let loaded = round_trip(&config);
+    assert!(loaded.is_implicit());
+    assert_eq!("server", *loaded.name);
+    assert_eq!(Some("proxy"), loaded.proxy.as_deref().map(|x| x.as_str()));
+    assert_eq!(80, *loaded.routes["/"]);
+}
+
+#[test]
+fn explicit_state_round_trips() {
+    let mut config: ServerConfigImpex = Default::default();
+    config.port.set_explicit(9000);
+    config.limits.timeout.set_explicit(60);
+    Impex::<DefaultWrapperSettings>::set_explicit(&mut config.proxy, None);
+    config.tags.set_explicit(Vec::new());
+    config.routes.remove_explicit("/".into());
+    config.routes.insert_explicit("/api".into(), 9001);
+
+    let loaded = round_trip(&config);
+    assert!(loaded.name.is_implicit());
+    assert_eq!(9000, *loaded.port);
+    assert!(loaded.port.is_explicit());
+    assert!(loaded.limits.connections.is_implicit());
+    assert_eq!(100, *loaded.limits.connections);
+    assert_eq!(60, *loaded.limits.timeout);
+    assert!(loaded.limits.timeout.is_explicit());
+    assert!(loaded.proxy.is_none() && loaded.proxy.is_explicit::<DefaultWrapperSettings>());
+    assert!(loaded.tags.is_empty() && loaded.tags.is_explicit());
+    assert_eq!(vec!["/api"], loaded.routes.keys().collect::<Vec<_>>());
+    assert_eq!(["/"], loaded.routes.removed_keys());
+    assert!(loaded.mode.is_implicit());
+
+    assert_eq!(
+        serde_json::to_string(&config).unwrap(),
+        serde_json::to_string(&loaded).unwrap()
+    );
+}
+
+#[test]
+fn enum_variants_keep_explicit_fields() {
+    let mut config: ServerConfigImpex = Default::default();
+    if let ModeImpex::Dynamic { max, .. } = &mut config.mode {
+        max.set_explicit(16);
+    }
+    let loaded = round_trip(&config);
+    let ModeImpex::Dynamic { min, max } = &loaded.mode else {
+        panic!("variant should be kept");
+    };
+    assert!(min.is_implicit());
+    assert_eq!(1, **min);
+    assert!(max.is_explicit());
+    assert_eq!(16, **max);
+
+    config.mode.set_explicit(Mode::Off);
+    let loaded = round_trip(&config);
+    assert!(matches!(loaded.mode, ModeImpex::Off(_)));
+    assert!(loaded.mode.is_explicit());
+
+    config.mode.set_explicit(Mode::Fixed(4));
+    let loaded = round_trip(&config);
+    assert!(matches!(&loaded.mode, ModeImpex::Fixed(x) if **x == 4 && x.is_explicit()));
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Message {
+    id: u32,
+    #[serde(with = "impex::compact")]
+    limits: LimitsImpex,
+}
+
+#[test]
+fn compact_fields_in_serde_types() {
+    let mut limits: LimitsImpex = Default::default();
+    limits.connections.set_explicit(5);
+    let bytes = bincode::serialize(&Message { id: 7, limits }).unwrap();
+    let message: Message = bincode::deserialize(&bytes).unwrap();
+
+    assert_eq!(7, message.id);
+    assert_eq!(5, *message.limits.connections);
+    assert!(message.limits.connections.is_explicit());
+    assert!(message.limits.timeout.is_implicit());
+    assert_eq!(30, *message.limits.timeout);
+}
+
+#[test]
+fn truncated_data_is_an_error() {
+    let mut limits: LimitsImpex = Default::default();
+    limits.timeout.set_explicit(1);
+    let mut bytes = bincode::serialize(&Compact::new(&limits)).unwrap();
+    bytes.pop();
+    assert!(bincode::deserialize::<Compact<LimitsImpex>>(&bytes).is_err());
+}
@@ -1,6 +1,9 @@
 use std::num::NonZeroU8;
 
-use impex::{Impex, ImpexPrimitive, WrapperSettings};
+use impex::{
+    DefaultWrapperSettings, Impex, ImpexDefaults, ImpexPrimitive, ImpexPrimitiveValue,
+    WrapperSettings,
+};
 
 use crate::generated_struct::{
     EnumConfig, EnumConfigImpex, KeyStructConfigImpex, TupleStructConfig,
@@ -165,3 +168,53 @@ fn test_visitor() {
     assert_eq!(foo_value.variable_name, Some(variable_name));
     assert_eq!(tuple_struct_config.0.variable_name, Some(variable_name));
 }
+
+/// Settings which reuse the primitive wrapper of the default settings
+#[derive(PartialEq, Eq, Debug, Default, Clone, Copy)]
+pub struct ReusedWrapperSettings;
+
+impl WrapperSettings for ReusedWrapperSettings {
+    type PrimitiveWrapper<T: ImpexPrimitive> = ImpexPrimitiveValue<T>;
+
+    fn create_primitive<T: ImpexPrimitive>(
+        value: T,
+        is_explicit: bool,
+    ) -> Self::PrimitiveWrapper<T> {
+        DefaultWrapperSettings::create_primitive(value, is_explicit)
+    }
+}
+
+#[test]
+fn reused_primitive_wrapper() {
+    let text = r#"{"num_cores":0,"tuple_struct_config":[1,2]}"#;
+    let mut config: KeyStructConfigImpex<ReusedWrapperSettings> =
+        serde_json::from_str(text).unwrap();
+    config.prune_defaults();
+    assert!(config.num_cores.is_implicit());
+    config.edit().num_cores = 3;
+    assert_eq!(
+        r#"{"num_cores":3,"tuple_struct_config":[1,2]}"#,
+        serde_json::to_string(&config).unwrap()
+    );
+}
+
+#[derive(Default, impex::Impex)]
+pub struct OptionConfig {
+    pub opt: Option<u32>,
+}
+
+#[test]
+fn option_methods_with_custom_settings() {
+    let mut config: OptionConfigImpex<MyWrapperSettings> = Default::default();
+    assert!(config.opt.is_implicit::<MyWrapperSettings>());
+    assert!(config.opt.replace::<MyWrapperSettings>(3).is_none());
+    assert!(config.opt.is_explicit::<MyWrapperSettings>());
+    assert_eq!(
+        3,
+        config
+            .opt
+            .get_or_insert_with::<MyWrapperSettings>(|| 4)
+            .value
+    );
+    assert_eq!(r#"{"opt":3}"#, serde_json::to_string(&config).unwrap());
+}
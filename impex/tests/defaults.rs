@@ -0,0 +1,236 @@
+use impex::{Impex, ImpexDefaults, IntoImpex, VisitPath};
+
+use crate::generated_struct::{
+    EnumConfig, EnumConfigImpex, KeyStructConfig, KeyStructConfigImpex, TupleStructConfig,
+};
+
+#[allow(unused)]
+mod generated_struct;
+
+#[test]
+fn full_document_keeps_only_differences_explicit() {
+    let text = r#"{
+        "num_cores":0,
+        "num_threads":[42],
+        "enum_config":{"Bar":["Bar",43,[42,43]]},
+        "tuple_struct_config":[42,44]
+    }"#;
+    let mut de = serde_json::Deserializer::from_str(text);
+    let obj: KeyStructConfigImpex = impex::deserialize_inferred(&mut de).unwrap();
+
+    assert!(obj.num_cores.is_implicit());
+    assert!(obj.num_threads[0].is_implicit());
+    assert!(obj.tuple_struct_config.0.is_implicit());
+    assert!(obj.tuple_struct_config.1.is_explicit());
+    let EnumConfigImpex::Bar(x1, x2, _) = &obj.enum_config else {
+        panic!("Expected EnumConfigImpex::Bar")
+    };
+    assert!(x1.is_implicit());
+    assert!(x2.is_explicit());
+    assert_eq!(
+        r#"{"enum_config":{"Bar":[null,43,[null,null]]},"tuple_struct_config":[null,44]}"#,
+        serde_json::to_string(&obj).unwrap()
+    );
+}
+
+#[test]
+fn from_value_inferred() {
+    let value = KeyStructConfig {
+        num_cores: 4,
+        ..Default::default()
+    };
+    let obj: KeyStructConfigImpex = ImpexDefaults::from_value_inferred(value);
+    assert!(obj.num_cores.is_explicit());
+    assert_eq!(r#"{"num_cores":4}"#, serde_json::to_string(&obj).unwrap());
+}
+
+#[test]
+fn different_variant_stays_explicit() {
+    let mut obj: EnumConfigImpex = EnumConfig::Foo {
+        foo_value: "Bar".into(),
+        tuple_struct_config: TupleStructConfig::default(),
+    }
+    .into_explicit();
+    obj.prune_defaults_with(&EnumConfigImpex::default());
+    assert!(Impex::<impex::DefaultWrapperSettings>::is_explicit(&obj));
+}
+
+#[test]
+fn lists_are_compared_as_a_whole() {
+    let mut changed: KeyStructConfigImpex =
+        serde_json::from_str(r#"{"num_threads":[42,43]}"#).unwrap();
+    changed.prune_defaults_with(&Default::default());
+    assert!(changed.num_threads.iter().all(|x| x.is_explicit()));
+
+    let mut unchanged: KeyStructConfigImpex =
+        serde_json::from_str(r#"{"num_threads":[42]}"#).unwrap();
+    unchanged.prune_defaults_with(&Default::default());
+    assert_eq!("{}", serde_json::to_string(&unchanged).unwrap());
+}
+
+#[test]
+fn prune_defaults_after_toggling_back() {
+    let mut obj: KeyStructConfigImpex = serde_json::from_str(r#"{"num_cores":3}"#).unwrap();
+    obj.num_cores.set_explicit(0);
+    assert_eq!(r#"{"num_cores":0}"#, serde_json::to_string(&obj).unwrap());
+
+    obj.prune_defaults();
+    assert!(obj.num_cores.is_implicit());
+    assert_eq!("{}", serde_json::to_string(&obj).unwrap());
+}
+
+#[derive(Default, impex::Impex)]
+#[impex(prune_defaults)]
+pub struct PrunedStruct {
+    pub name: String,
+    pub count: u32,
+}
+
+#[derive(Default, impex::Impex)]
+pub struct PrunedFieldStruct {
+    #[impex(prune_defaults)]
+    pub pruned: u32,
+    pub kept: u32,
+}
+
+#[derive(impex::Impex)]
+#[impex(prune_defaults)]
+pub enum PrunedEnum {
+    Empty,
+    Tuple(u32, u32),
+}
+
+impl Default for PrunedEnum {
+    fn default() -> Self {
+        PrunedEnum::Tuple(0, 0)
+    }
+}
+
+#[test]
+fn prune_defaults_policy_for_type() {
+    let obj: PrunedStructImpex = serde_json::from_str(r#"{"name":"","count":2}"#).unwrap();
+    assert!(obj.name.is_explicit());
+    assert_eq!(r#"{"count":2}"#, serde_json::to_string(&obj).unwrap());
+}
+
+#[test]
+fn prune_defaults_policy_for_field() {
+    let obj: PrunedFieldStructImpex = serde_json::from_str(r#"{"pruned":0,"kept":0}"#).unwrap();
+    assert_eq!(r#"{"kept":0}"#, serde_json::to_string(&obj).unwrap());
+}
+
+#[test]
+fn prune_defaults_policy_for_enum() {
+    let obj: PrunedEnumImpex = serde_json::from_str(r#"{"Tuple":[0,1]}"#).unwrap();
+    assert_eq!(
+        r#"{"Tuple":[null,1]}"#,
+        serde_json::to_string(&obj).unwrap()
+    );
+}
+
+#[test]
+fn rebase_defaults_keeps_explicit_values() {
+    let mut obj: KeyStructConfigImpex = serde_json::from_str(r#"{"num_cores":3}"#).unwrap();
+    let EnumConfigImpex::Bar(_, x2, _) = &mut obj.enum_config else {
+        panic!("Expected EnumConfigImpex::Bar")
+    };
+    x2.set_explicit(43);
+    let changes = obj.rebase_defaults(KeyStructConfig {
+        num_cores: 8,
+        num_threads: vec![64],
+        enum_config: EnumConfig::Bar("Baz".into(), 44, TupleStructConfig(1, 43)),
+        tuple_struct_config: TupleStructConfig::default(),
+    });
+
+    assert_eq!(3, *obj.num_cores);
+    assert_eq!(64, *obj.num_threads[0]);
+    let EnumConfigImpex::Bar(x1, x2, x3) = &obj.enum_config else {
+        panic!("Expected EnumConfigImpex::Bar")
+    };
+    assert_eq!("Baz", x1.as_str());
+    assert_eq!(43, **x2);
+    assert_eq!(1, *x3.0);
+    assert_eq!(
+        vec![
+            "default for num_threads changed from [42] to [64]",
+            "default for enum_config.Bar.0 changed from \"Bar\" to \"Baz\"",
+            "default for enum_config.Bar.2.0 changed from 42 to 1",
+        ],
+        changes.iter().map(|x| x.to_string()).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        r#"{"num_cores":3,"enum_config":{"Bar":[null,43,[null,null]]}}"#,
+        serde_json::to_string(&obj).unwrap()
+    );
+}
+
+#[test]
+fn rebase_defaults_replaces_implicit_variant() {
+    let mut obj: EnumConfigImpex = Default::default();
+    let changes = obj.rebase_defaults(EnumConfig::Foo {
+        foo_value: "Foo".into(),
+        tuple_struct_config: TupleStructConfig::default(),
+    });
+    assert!(matches!(obj, EnumConfigImpex::Foo { .. }));
+    assert_eq!(1, changes.len());
+    assert_eq!(
+        r#"Foo { foo_value: "Foo", tuple_struct_config: TupleStructConfig(42, 43) }"#,
+        changes[0].new
+    );
+}
+
+#[test]
+fn materialize_defaults() {
+    let mut obj: KeyStructConfigImpex = serde_json::from_str(r#"{"num_cores":3}"#).unwrap();
+    obj.materialize_defaults();
+    assert!(obj.num_threads[0].is_explicit());
+    assert_eq!(
+        r#"{"num_cores":3,"num_threads":[42],"enum_config":{"Bar":["Bar",42,[42,43]]},"tuple_struct_config":[42,43]}"#,
+        serde_json::to_string(&obj).unwrap()
+    );
+}
+
+#[test]
+fn materialize_defaults_at_path() {
+    let mut obj: KeyStructConfigImpex = Default::default();
+    obj.materialize_defaults_at(&"enum_config.Bar.1".into())
+        .unwrap();
+    obj.materialize_defaults_at(&"num_threads".into()).unwrap();
+    assert_eq!(
+        r#"{"num_threads":[42],"enum_config":{"Bar":[null,42,[null,null]]}}"#,
+        serde_json::to_string(&obj).unwrap()
+    );
+
+    let error = obj
+        .materialize_defaults_at(&"enum_config.Foo".into())
+        .unwrap_err();
+    assert_eq!("no value at path `enum_config.Foo`", error.to_string());
+    assert!(
+        obj.materialize_defaults_at(&"num_threads.1".into())
+            .is_err()
+    );
+}
+
+#[test]
+fn materialize_list_element_with_its_list() {
+    let mut obj: KeyStructConfigImpex = Default::default();
+    obj.materialize_defaults_at(&"num_threads.0".into())
+        .unwrap();
+    assert!(obj.num_threads.is_explicit());
+    let text = serde_json::to_string(&obj).unwrap();
+    assert_eq!(r#"{"num_threads":[42]}"#, text);
+    let loaded: KeyStructConfigImpex = serde_json::from_str(&text).unwrap();
+    assert_eq!(text, serde_json::to_string(&loaded).unwrap());
+}
+
+#[test]
+fn set_implicit_keeps_values_implicit() {
+    let mut obj: KeyStructConfigImpex = serde_json::from_str(r#"{"num_cores":3}"#).unwrap();
+    obj.set_implicit(KeyStructConfig {
+        num_cores: 5,
+        ..Default::default()
+    });
+    assert!(obj.is_implicit());
+    assert_eq!(5, *obj.num_cores);
+    assert_eq!("{}", serde_json::to_string(&obj).unwrap());
+}
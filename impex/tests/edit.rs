@@ -0,0 +1,68 @@
+use impex::Impex;
+
+use crate::generated_struct::{KeyStructConfigImpex, TupleStructConfig};
+
+#[allow(unused)]
+mod generated_struct;
+
+#[test]
+fn edit_primitive_marks_explicit() {
+    let mut config: KeyStructConfigImpex = Default::default();
+    *config.num_cores.edit() += 2;
+    assert!(config.num_cores.is_explicit());
+    assert_eq!(
+        r#"{"num_cores":2}"#,
+        serde_json::to_string(&config).unwrap()
+    );
+}
+
+#[test]
+fn edit_vec_marks_explicit() {
+    let mut config: KeyStructConfigImpex = Default::default();
+    config.num_threads.edit().push(4);
+    assert_eq!(
+        r#"{"num_threads":[42,4]}"#,
+        serde_json::to_string(&config).unwrap()
+    );
+}
+
+#[test]
+fn edit_without_write_stays_implicit() {
+    let mut config: KeyStructConfigImpex = Default::default();
+    assert_eq!(42, config.tuple_struct_config.edit().0);
+    assert!(config.tuple_struct_config.is_implicit());
+
+    config.tuple_struct_config.edit().1 = 44;
+    assert!(config.tuple_struct_config.0.is_implicit());
+    assert!(config.tuple_struct_config.1.is_explicit());
+    assert_eq!(
+        r#"{"tuple_struct_config":[null,44]}"#,
+        serde_json::to_string(&config).unwrap()
+    );
+}
+
+#[test]
+fn edit_marks_only_changed_leaves() {
+    let mut config: KeyStructConfigImpex = Default::default();
+    config.tuple_struct_config.0.set_explicit(1);
+    let mut edit = config.edit();
+    edit.num_cores = 2;
+    edit.tuple_struct_config.1 = 43;
+    drop(edit);
+
+    assert!(config.num_threads.is_implicit());
+    assert!(config.enum_config.is_implicit());
+    // Explicit leaves stay explicit, even if they were not changed
+    assert!(config.tuple_struct_config.0.is_explicit());
+    assert!(config.tuple_struct_config.1.is_implicit());
+    assert_eq!(
+        r#"{"num_cores":2,"tuple_struct_config":[1,null]}"#,
+        serde_json::to_string(&config).unwrap()
+    );
+
+    config.edit().tuple_struct_config = TupleStructConfig(1, 44);
+    assert_eq!(
+        r#"{"num_cores":2,"tuple_struct_config":[1,44]}"#,
+        serde_json::to_string(&config).unwrap()
+    );
+}
@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+
+use impex::{ImpexMerge, env::EnvSource};
+
+use crate::generated_struct::{EnumConfigImpex, KeyStructConfigImpex};
+
+#[allow(unused)]
+mod generated_struct;
+
+fn source(vars: &[(&str, &str)]) -> EnvSource {
+    let vars: HashMap<_, _> = vars.iter().copied().collect();
+    EnvSource::from_vars("APP", vars)
+}
+
+#[test]
+fn variables_set_explicit_values_in_overlay() {
+    let overlay = source(&[("APP__NUM_CORES", "4"), ("APP__ENUM_CONFIG__BAR__1", "43")])
+        .overlay::<_, KeyStructConfigImpex>()
+        .unwrap();
+    assert!(overlay.unmapped.is_empty());
+
+    let mut config: KeyStructConfigImpex = serde_json::from_str(r#"{"num_threads":[1]}"#).unwrap();
+    config.merge(overlay.value);
+    assert_eq!(4, *config.num_cores);
+    assert_eq!(1, config.num_threads.len());
+    let EnumConfigImpex::Bar(name, value, _) = &config.enum_config else {
+        panic!("expected the default variant");
+    };
+    assert!(name.is_implicit());
+    assert_eq!(43, **value);
+    assert_eq!(
+        r#"{"num_cores":4,"num_threads":[1],"enum_config":{"Bar":[null,43,[null,null]]}}"#,
+        serde_json::to_string(&config).unwrap()
+    );
+}
+
+#[test]
+fn values_are_parsed_into_the_type_at_the_path() {
+    let mut config: KeyStructConfigImpex = Default::default();
+    let unmapped = source(&[
+        ("APP__ENUM_CONFIG__BAR__0", "42"),
+        ("APP__NUM_THREADS", "[1, 2, 3]"),
+        ("APP__TUPLE_STRUCT_CONFIG", "[5, 6]"),
+    ])
+    .apply(&mut config)
+    .unwrap();
+    assert!(unmapped.is_empty());
+
+    let EnumConfigImpex::Bar(name, _, _) = &config.enum_config else {
+        panic!("expected the default variant");
+    };
+    assert_eq!("42", **name);
+    assert_eq!(3, config.num_threads.len());
+    assert_eq!(6, *config.tuple_struct_config.1);
+}
+
+#[test]
+fn unmapped_variables_are_reported() {
+    let overlay = source(&[
+        ("APP__NUM_CORES", "1"),
+        ("APP__NUM_COURSES", "1"),
+        ("APP__ENUM_CONFIG__FOO__FOO_VALUE", "x"),
+        ("APP__NUM_THREADS__7", "1"),
+        ("APP____NUM_CORES", "1"),
+        ("OTHER__NUM_CORES", "1"),
+    ])
+    .overlay::<_, KeyStructConfigImpex>()
+    .unwrap();
+    assert_eq!(
+        [
+            "APP__ENUM_CONFIG__FOO__FOO_VALUE",
+            "APP__NUM_COURSES",
+            "APP__NUM_THREADS__7",
+            "APP____NUM_CORES",
+        ],
+        overlay.unmapped.as_slice()
+    );
+    assert_eq!(1, *overlay.value.num_cores);
+}
+
+#[test]
+fn invalid_values_are_errors() {
+    let Err(error) = source(&[("APP__NUM_CORES", "many")]).overlay::<_, KeyStructConfigImpex>()
+    else {
+        panic!("expected an error");
+    };
+    assert_eq!("APP__NUM_CORES", error.variable);
+    assert_eq!("num_cores", error.path.to_string());
+}
+
+#[test]
+fn separator_can_be_changed() {
+    let overlay = EnvSource::from_vars("APP", [("APP_TUPLE_STRUCT_CONFIG_1", "7")])
+        .with_separator("_")
+        .overlay::<_, KeyStructConfigImpex>()
+        .unwrap();
+    assert_eq!(["APP_TUPLE_STRUCT_CONFIG_1"], overlay.unmapped.as_slice());
+
+    let overlay = EnvSource::from_vars("APP", [("APP.TUPLE_STRUCT_CONFIG.1", "7")])
+        .with_separator(".")
+        .overlay::<_, KeyStructConfigImpex>()
+        .unwrap();
+    assert_eq!(7, *overlay.value.tuple_struct_config.1);
+}
+
+#[derive(impex::Impex)]
+pub struct Lists {
+    pub list: Vec<u32>,
+    pub array: [u32; 2],
+}
+
+impl Default for Lists {
+    fn default() -> Self {
+        Self {
+            list: vec![1, 2],
+            array: [3, 4],
+        }
+    }
+}
+
+#[test]
+fn list_elements_are_saved_with_their_list() {
+    let mut config: ListsImpex = Default::default();
+    let unmapped = source(&[("APP__LIST__1", "9"), ("APP__ARRAY__0", "5")])
+        .apply(&mut config)
+        .unwrap();
+    assert!(unmapped.is_empty());
+
+    // The other elements are written as well, so the document can be loaded again
+    let text = serde_json::to_string(&config).unwrap();
+    assert_eq!(r#"{"list":[1,9],"array":[5,4]}"#, text);
+    let loaded: ListsImpex = serde_json::from_str(&text).unwrap();
+    assert_eq!(text, serde_json::to_string(&loaded).unwrap());
+}
+
+#[derive(impex::Impex)]
+pub struct PathsConfig {
+    #[impex(list = "extend")]
+    pub paths: Vec<String>,
+}
+
+impl Default for PathsConfig {
+    fn default() -> Self {
+        Self {
+            paths: vec!["/usr/lib".into(), "/lib".into()],
+        }
+    }
+}
+
+#[test]
+fn extend_lists_are_set_as_a_whole() {
+    let mut config: PathsConfigImpex = serde_json::from_str(r#"{"paths":{"+":["/x"]}}"#).unwrap();
+    let unmapped = source(&[("APP__PATHS__0", "/z")])
+        .apply(&mut config)
+        .unwrap();
+    assert_eq!(["APP__PATHS__0"], unmapped.as_slice());
+
+    let overlay = source(&[("APP__PATHS", r#"["/q","/lib"]"#)])
+        .overlay::<_, PathsConfigImpex>()
+        .unwrap();
+    config.merge(overlay.value);
+    let paths: Vec<&str> = config.paths.iter().map(|x| x.as_str()).collect();
+    assert_eq!(vec!["/q", "/lib"], paths);
+    assert_eq!(
+        r#"{"paths":{"^":["/q"],"-":["/usr/lib"]}}"#,
+        serde_json::to_string(&config).unwrap()
+    );
+}
+
+#[derive(Clone, PartialEq, Default, impex::Impex)]
+pub struct Server {
+    pub name: String,
+    pub port: u16,
+}
+
+#[derive(impex::Impex)]
+pub struct CollectionsConfig {
+    pub limits: std::collections::BTreeMap<String, u32>,
+    #[impex(merge_key = "name")]
+    pub servers: Vec<Server>,
+    #[impex(list = "extend")]
+    pub paths: Vec<String>,
+}
+
+impl Default for CollectionsConfig {
+    fn default() -> Self {
+        let server = |name: &str, port| Server {
+            name: name.into(),
+            port,
+        };
+        Self {
+            limits: [("a".into(), 1), ("b".into(), 2)].into(),
+            servers: vec![server("web", 80), server("api", 90)],
+            paths: vec!["/usr/lib".into()],
+        }
+    }
+}
+
+#[test]
+fn untouched_default_entries_are_kept() {
+    let mut config: CollectionsConfigImpex = serde_json::from_str("{}").unwrap();
+    let unmapped = source(&[
+        ("APP__LIMITS", r#"{"b":3}"#),
+        ("APP__SERVERS", r#"[{"name":"api","port":91}]"#),
+        ("APP__PATHS", r#"{"+":["/x"]}"#),
+    ])
+    .apply(&mut config)
+    .unwrap();
+    assert!(unmapped.is_empty());
+
+    let limits: Vec<_> = config
+        .limits
+        .iter()
+        .map(|(k, v)| (k.as_str(), **v))
+        .collect();
+    assert_eq!(vec![("a", 1), ("b", 3)], limits);
+    let servers: Vec<_> = config
+        .servers
+        .iter()
+        .map(|x| (x.name.as_str(), *x.port))
+        .collect();
+    assert_eq!(vec![("web", 80), ("api", 91)], servers);
+    let paths: Vec<_> = config.paths.iter().map(|x| x.as_str()).collect();
+    assert_eq!(vec!["/usr/lib", "/x"], paths);
+}
@@ -0,0 +1,239 @@
+use impex::{ImpexDefaults, ImpexMerge};
+
+#[derive(impex::Impex)]
+pub struct PathsConfig {
+    #[impex(list = "extend")]
+    pub search_paths: Vec<String>,
+}
+
+impl Default for PathsConfig {
+    fn default() -> Self {
+        Self {
+            search_paths: vec!["/usr/lib".into(), "/lib".into()],
+        }
+    }
+}
+
+fn paths(obj: &PathsConfigImpex) -> Vec<&str> {
+    obj.search_paths.iter().map(|x| x.as_str()).collect()
+}
+
+#[test]
+fn changes_are_saved_relative_to_the_default() {
+    let mut obj: PathsConfigImpex = Default::default();
+    assert_eq!("{}", serde_json::to_string(&obj).unwrap());
+
+    obj.search_paths.push_explicit("/opt/lib".into());
+    obj.search_paths.prepend_explicit("/home/lib".into());
+    obj.search_paths.remove_explicit("/lib".into());
+    assert_eq!(vec!["/home/lib", "/usr/lib", "/opt/lib"], paths(&obj));
+    let text = r#"{"search_paths":{"^":["/home/lib"],"+":["/opt/lib"],"-":["/lib"]}}"#;
+    assert_eq!(text, serde_json::to_string(&obj).unwrap());
+
+    let obj: PathsConfigImpex = serde_json::from_str(text).unwrap();
+    assert_eq!(vec!["/home/lib", "/usr/lib", "/opt/lib"], paths(&obj));
+    assert_eq!(text, serde_json::to_string(&obj).unwrap());
+}
+
+#[test]
+fn new_default_elements_show_up() {
+    let mut obj: PathsConfigImpex =
+        serde_json::from_str(r#"{"search_paths":{"+":["/opt/lib"]}}"#).unwrap();
+    let changes = obj.rebase_defaults(PathsConfig {
+        search_paths: vec!["/usr/lib".into(), "/usr/local/lib".into()],
+    });
+    assert_eq!(vec!["/usr/lib", "/usr/local/lib", "/opt/lib"], paths(&obj));
+    assert_eq!(
+        vec![
+            r#"default for search_paths changed from ["/usr/lib", "/lib", "/opt/lib"] to ["/usr/lib", "/usr/local/lib", "/opt/lib"]"#
+        ],
+        changes.iter().map(|x| x.to_string()).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn set_explicit_records_the_difference() {
+    use impex::Impex;
+
+    let mut obj: PathsConfigImpex = Default::default();
+    obj.search_paths
+        .set_explicit(vec!["/usr/lib".into(), "/srv/lib".into()]);
+    assert_eq!(
+        r#"{"search_paths":{"+":["/srv/lib"],"-":["/lib"]}}"#,
+        serde_json::to_string(&obj).unwrap()
+    );
+
+    obj.search_paths
+        .set_explicit(PathsConfig::default().search_paths);
+    assert_eq!(vec!["/usr/lib", "/lib"], paths(&obj));
+    assert!(obj.search_paths.is_implicit());
+}
+
+#[test]
+fn prune_drops_removals_of_unknown_elements() {
+    let mut obj: PathsConfigImpex =
+        serde_json::from_str(r#"{"search_paths":{"-":["/lib","/unknown"]}}"#).unwrap();
+    obj.prune_defaults();
+    assert_eq!(
+        r#"{"search_paths":{"-":["/lib"]}}"#,
+        serde_json::to_string(&obj).unwrap()
+    );
+}
+
+#[test]
+fn overlays_extend_the_list() {
+    let mut obj: PathsConfigImpex =
+        serde_json::from_str(r#"{"search_paths":{"+":["/a"]}}"#).unwrap();
+    obj.merge(serde_json::from_str(r#"{"search_paths":{"+":["/b"],"-":["/usr/lib"]}}"#).unwrap());
+    assert_eq!(vec!["/lib", "/a", "/b"], paths(&obj));
+
+    assert!(serde_json::from_str::<PathsConfigImpex>(r#"{"search_paths":{"*":[]}}"#).is_err());
+}
+
+#[test]
+fn set_explicit_keeps_order_and_duplicates() {
+    use impex::Impex;
+
+    for list in [
+        vec!["/x", "/x", "/lib", "/usr/lib"],
+        vec!["/lib", "/usr/lib"],
+        vec!["/usr/lib", "/usr/lib", "/lib", "/lib"],
+        vec!["/x", "/usr/lib", "/y", "/lib"],
+        vec![],
+    ] {
+        let value: Vec<String> = list.iter().map(|x| x.to_string()).collect();
+        let mut obj: PathsConfigImpex = Default::default();
+        obj.search_paths.set_explicit(value.clone());
+        assert_eq!(value, obj.search_paths.clone().into_value());
+
+        let text = serde_json::to_string(&obj).unwrap();
+        let loaded: PathsConfigImpex = serde_json::from_str(&text).unwrap();
+        assert_eq!(list, paths(&loaded), "{text}");
+    }
+
+    let mut obj: PathsConfigImpex = Default::default();
+    obj.search_paths.set_explicit(vec![
+        "/x".into(),
+        "/x".into(),
+        "/lib".into(),
+        "/usr/lib".into(),
+    ]);
+    assert_eq!(
+        r#"{"search_paths":{"^":["/x","/x"],"+":["/usr/lib"],"-":["/usr/lib"]}}"#,
+        serde_json::to_string(&obj).unwrap()
+    );
+}
+
+#[test]
+fn prepended_elements_stay_in_front() {
+    use impex::Impex;
+
+    let mut obj: PathsConfigImpex = Default::default();
+    obj.search_paths.prepend_explicit("/home/lib".into());
+    obj.search_paths.materialize_defaults();
+    assert_eq!(vec!["/home/lib", "/usr/lib", "/lib"], paths(&obj));
+    assert_eq!(
+        r#"{"search_paths":{"^":["/home/lib"]}}"#,
+        serde_json::to_string(&obj).unwrap()
+    );
+
+    let mut obj: PathsConfigImpex = Default::default();
+    obj.search_paths.edit().insert(0, "/home/lib".into());
+    assert_eq!(
+        r#"{"search_paths":{"^":["/home/lib"]}}"#,
+        serde_json::to_string(&obj).unwrap()
+    );
+}
+
+#[test]
+fn edit_keeps_the_recorded_changes_once() {
+    use impex::Impex;
+
+    let mut obj: PathsConfigImpex =
+        serde_json::from_str(r#"{"search_paths":{"+":["/x"]}}"#).unwrap();
+    obj.search_paths.edit().push("/y".into());
+    assert_eq!(vec!["/usr/lib", "/lib", "/x", "/y"], paths(&obj));
+    assert_eq!(
+        r#"{"search_paths":{"+":["/x","/y"]}}"#,
+        serde_json::to_string(&obj).unwrap()
+    );
+
+    obj.search_paths.edit().retain(|x| x != "/x");
+    assert_eq!(vec!["/usr/lib", "/lib", "/y"], paths(&obj));
+
+    // Editing the struct leaves the unchanged list as it is
+    obj.search_paths.prepend_explicit("/home/lib".into());
+    obj.edit().search_paths.push("/z".into());
+    assert_eq!(
+        r#"{"search_paths":{"^":["/home/lib"],"+":["/y","/z"]}}"#,
+        serde_json::to_string(&obj).unwrap()
+    );
+}
+
+#[derive(impex::Impex)]
+pub struct LargeConfig {
+    #[impex(list = "extend")]
+    pub entries: Vec<u32>,
+}
+
+impl Default for LargeConfig {
+    fn default() -> Self {
+        Self {
+            entries: (0..500).collect(),
+        }
+    }
+}
+
+#[test]
+fn large_lists_are_aligned() {
+    use impex::Impex;
+
+    let value: Vec<u32> = [1000, 1001]
+        .into_iter()
+        .chain((0..500).filter(|x| x % 7 != 0))
+        .chain([1002])
+        .collect();
+    let mut obj: LargeConfigImpex = Default::default();
+    obj.entries.set_explicit(value.clone());
+    let values =
+        |list: &[impex::ImpexPrimitiveValue<u32>]| list.iter().map(|x| **x).collect::<Vec<_>>();
+    assert_eq!(vec![1000, 1001], values(obj.entries.prepended()));
+    assert_eq!(vec![1002], values(obj.entries.appended()));
+    assert_eq!(72, obj.entries.removed().len());
+
+    let text = serde_json::to_string(&obj).unwrap();
+    let mut loaded: LargeConfigImpex = serde_json::from_str(&text).unwrap();
+    assert_eq!(value, loaded.entries.clone().into_value());
+
+    loaded.entries.edit().push(1003);
+    assert_eq!(Some(&1003), loaded.entries.appended().last().map(|x| &**x));
+}
+
+#[test]
+fn list_of_all_elements_is_loaded_as_set() {
+    let obj: PathsConfigImpex = serde_json::from_str(r#"{"search_paths":["/x","/lib"]}"#).unwrap();
+    assert_eq!(vec!["/x", "/lib"], paths(&obj));
+    assert_eq!(
+        r#"{"search_paths":{"^":["/x"],"-":["/usr/lib"]}}"#,
+        serde_json::to_string(&obj).unwrap()
+    );
+
+    // It replaces the changes of the lower layer
+    let mut base: PathsConfigImpex =
+        serde_json::from_str(r#"{"search_paths":{"+":["/a"]}}"#).unwrap();
+    base.merge(obj);
+    assert_eq!(vec!["/x", "/lib"], paths(&base));
+}
+
+#[test]
+fn elements_have_no_path() {
+    use impex::VisitPath;
+
+    let mut obj: PathsConfigImpex = Default::default();
+    let error = obj
+        .materialize_defaults_at(&"search_paths.0".into())
+        .unwrap_err();
+    assert_eq!("no value at path `search_paths.0`", error.to_string());
+    obj.materialize_defaults_at(&"search_paths".into()).unwrap();
+    assert_eq!("{}", serde_json::to_string(&obj).unwrap());
+}
@@ -1,3 +1,5 @@
+#![allow(clippy::derivable_impls)]
+
 // This module uses the #[derive(Impex)] macro to auto-generate
 // the same code that is manually written in manual_struct/mod.rs
 
@@ -0,0 +1,146 @@
+#![cfg(feature = "jsonc")]
+
+use std::collections::BTreeMap;
+
+use impex::{Impex, jsonc::JsoncDocument};
+
+use crate::server_config::ServerConfigImpex;
+
+mod server_config;
+
+const TEXT: &str = r#"{
+    // Server settings
+    "name": "main", // shown in the logs
+    "port": 9000,
+
+    /* Connection limits */
+    "limits": {
+        "timeout": 60 // seconds
+    },
+    "routes": { "/": 80 }
+}
+"#;
+
+fn parse(text: &str) -> JsoncDocument<ServerConfigImpex> {
+    JsoncDocument::parse(text).unwrap()
+}
+
+#[test]
+fn unchanged_document_is_kept() {
+    let mut document = parse(TEXT);
+    assert_eq!("main", *document.name);
+    assert!(document.limits.connections.is_implicit());
+    assert_eq!(80, *document.routes["/"]);
+    assert_eq!(TEXT, document.save().unwrap());
+}
+
+#[test]
+fn changed_values_keep_comments() {
+    let mut document = parse(TEXT);
+    document.name.set_explicit("backup".into());
+    document.limits.timeout.set_explicit(90);
+    document.routes.insert_explicit("/".into(), 81);
+    assert_eq!(
+        r#"{
+    // Server settings
+    "name": "backup", // shown in the logs
+    "port": 9000,
+
+    /* Connection limits */
+    "limits": {
+        "timeout": 90 // seconds
+    },
+    "routes": { "/": 81 }
+}
+"#,
+        document.save().unwrap()
+    );
+}
+
+#[test]
+fn implicit_values_are_removed() {
+    let mut document = parse(TEXT);
+    document.port.set_implicit(8080);
+    document.limits.timeout.set_implicit(30);
+    document.routes.set_implicit(BTreeMap::new());
+    assert_eq!(
+        r#"{
+    // Server settings
+    "name": "main" // shown in the logs
+}
+"#,
+        document.save().unwrap()
+    );
+}
+
+#[test]
+fn new_values_are_appended() {
+    let mut document = parse(TEXT);
+    document.limits.connections.set_explicit(10);
+    document.tags.set_explicit(vec!["a".into(), "b".into()]);
+    document.routes.insert_explicit("/api".into(), 81);
+    assert_eq!(
+        r#"{
+    // Server settings
+    "name": "main", // shown in the logs
+    "port": 9000,
+
+    /* Connection limits */
+    "limits": {
+        "timeout": 60, // seconds
+        "connections": 10
+    },
+    "routes": { "/": 80, "/api": 81 },
+    "tags": ["a", "b"]
+}
+"#,
+        document.save().unwrap()
+    );
+}
+
+#[test]
+fn json5_syntax_is_kept() {
+    let text = "// JSON5\n{\n  name: 'main',\n  port: 0x2328,\n  limits: { timeout: +50, },\n  tags: ['a',],\n}\n";
+    let mut document = parse(text);
+    assert_eq!("main", *document.name);
+    assert_eq!(9000, *document.port);
+    assert_eq!(50, *document.limits.timeout);
+    assert_eq!("a", *document.tags[0]);
+    assert_eq!(text, document.save().unwrap());
+
+    document.port.set_implicit(8080);
+    document.tags.set_explicit(vec!["a".into(), "b".into()]);
+    assert_eq!(
+        "// JSON5\n{\n  name: 'main',\n  limits: { timeout: +50, },\n  tags: [\"a\", \"b\"],\n}\n",
+        document.save().unwrap()
+    );
+}
+
+#[test]
+fn members_sharing_a_line_are_removed_alone() {
+    let mut document = parse("{\n  port: 9000, name: 'main',\n  tags: ['a']\n}\n");
+    document.name.set_implicit("server".into());
+    document.tags.set_explicit(vec!["b".into(), "c".into()]);
+    assert_eq!(
+        "{\n  port: 9000,\n  tags: [\"b\", \"c\"]\n}\n",
+        document.save().unwrap()
+    );
+
+    let mut document = parse("{\n  name: 'main', port: 9000,\n  tags: ['a']\n}\n");
+    document.name.set_implicit("server".into());
+    assert_eq!(
+        "{\n  port: 9000,\n  tags: ['a']\n}\n",
+        document.save().unwrap()
+    );
+}
+
+#[test]
+fn invalid_documents_are_errors() {
+    let Err(error) = JsoncDocument::<ServerConfigImpex>::parse("{\n  \"port\": ,\n}") else {
+        panic!("the document is invalid");
+    };
+    assert_eq!("invalid JSONC at 2:11: expected a value", error.to_string());
+    assert!(JsoncDocument::<ServerConfigImpex>::parse("{ \"port\": 1 /* }").is_err());
+    assert!(JsoncDocument::<ServerConfigImpex>::parse("{ \"port\": \"x\" }").is_err());
+    assert!(JsoncDocument::<ServerConfigImpex>::parse("{ \"port\": NaN }").is_err());
+}
@@ -0,0 +1,128 @@
+use impex::{Impex, ImpexDefaults, ImpexMerge};
+
+#[derive(Clone, PartialEq, impex::Impex)]
+pub struct Server {
+    pub name: String,
+    pub port: u16,
+    pub enabled: bool,
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            port: 80,
+            enabled: true,
+        }
+    }
+}
+
+#[derive(impex::Impex)]
+pub struct ServersConfig {
+    #[impex(merge_key = "name")]
+    pub servers: Vec<Server>,
+}
+
+fn server(name: &str, port: u16) -> Server {
+    Server {
+        name: name.into(),
+        port,
+        enabled: true,
+    }
+}
+
+impl Default for ServersConfig {
+    fn default() -> Self {
+        Self {
+            servers: vec![server("web", 8080), server("api", 9000)],
+        }
+    }
+}
+
+#[test]
+fn changed_elements_are_saved_with_their_key() {
+    let mut obj: ServersConfigImpex = Default::default();
+    assert_eq!("{}", serde_json::to_string(&obj).unwrap());
+
+    obj.servers.edit()[1].port = 9001;
+    let text = r#"{"servers":[{"name":"api","port":9001}]}"#;
+    assert_eq!(text, serde_json::to_string(&obj).unwrap());
+
+    let obj: ServersConfigImpex = serde_json::from_str(text).unwrap();
+    let ports: Vec<_> = obj
+        .servers
+        .iter()
+        .map(|x| (x.name.as_str(), *x.port))
+        .collect();
+    assert_eq!(vec![("web", 8080), ("api", 9001)], ports);
+    assert!(obj.servers[0].is_implicit());
+    assert!(obj.servers[1].enabled.is_implicit());
+    assert_eq!(text, serde_json::to_string(&obj).unwrap());
+}
+
+#[test]
+fn added_elements_are_appended() {
+    let text = r#"{"servers":[{"name":"db","port":5432},{"name":"web","enabled":false}]}"#;
+    let obj: ServersConfigImpex = serde_json::from_str(text).unwrap();
+    let names: Vec<_> = obj.servers.iter().map(|x| x.name.as_str()).collect();
+    assert_eq!(vec!["web", "api", "db"], names);
+    assert_eq!(8080, *obj.servers[0].port);
+    assert!(!*obj.servers[0].enabled);
+    assert_eq!(
+        r#"{"servers":[{"name":"web","enabled":false},{"name":"db","port":5432}]}"#,
+        serde_json::to_string(&obj).unwrap()
+    );
+}
+
+#[test]
+fn overlay_merges_elements_by_key() {
+    let mut obj: ServersConfigImpex =
+        serde_json::from_str(r#"{"servers":[{"name":"web","port":1}]}"#).unwrap();
+    let overlay: ServersConfigImpex =
+        serde_json::from_str(r#"{"servers":[{"name":"web","enabled":false},{"name":"db"}]}"#)
+            .unwrap();
+    obj.merge(overlay);
+
+    let servers: Vec<_> = obj
+        .servers
+        .iter()
+        .map(|x| (x.name.as_str(), *x.port, *x.enabled))
+        .collect();
+    assert_eq!(
+        vec![("web", 1, false), ("api", 9000, true), ("db", 80, true)],
+        servers
+    );
+}
+
+#[test]
+fn prune_and_rebase_by_key() {
+    let mut obj: ServersConfigImpex =
+        serde_json::from_str(r#"{"servers":[{"name":"api","port":9000}]}"#).unwrap();
+    obj.prune_defaults();
+    assert_eq!("{}", serde_json::to_string(&obj).unwrap());
+
+    // The removed element is reported at its index in the old default
+    let changes = obj.rebase_defaults(ServersConfig {
+        servers: vec![server("api", 9100)],
+    });
+    assert_eq!(
+        vec![
+            "default for servers.0.port changed from 9000 to 9100",
+            "default for servers.0 changed from Some(Server { name: \"web\", port: 8080, enabled: true }) to None",
+        ],
+        changes.iter().map(|x| x.to_string()).collect::<Vec<_>>()
+    );
+    assert_eq!(1, obj.servers.len());
+}
+
+#[test]
+fn edit_saves_added_elements_whole() {
+    let mut obj: ServersConfigImpex = Default::default();
+    obj.servers.edit().push(server("db", 5432));
+    let text = r#"{"servers":[{"name":"db","port":5432,"enabled":true}]}"#;
+    assert_eq!(text, serde_json::to_string(&obj).unwrap());
+
+    let obj: ServersConfigImpex = serde_json::from_str(text).unwrap();
+    let names: Vec<_> = obj.servers.iter().map(|x| x.name.as_str()).collect();
+    assert_eq!(vec!["web", "api", "db"], names);
+}
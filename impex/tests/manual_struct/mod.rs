@@ -1,3 +1,5 @@
+#![allow(clippy::derivable_impls)]
+
 // #[derive(Impex)]
 pub struct KeyStructConfig {
     pub num_cores: u32,
@@ -0,0 +1,182 @@
+use std::collections::{BTreeMap, HashMap};
+
+use impex::{Impex, ImpexDefaults, ImpexMerge};
+
+#[derive(Clone, PartialEq, impex::Impex)]
+pub struct ServiceConfig {
+    pub port: u16,
+    pub enabled: bool,
+}
+
+impl Default for ServiceConfig {
+    fn default() -> Self {
+        Self {
+            port: 80,
+            enabled: true,
+        }
+    }
+}
+
+#[derive(impex::Impex)]
+pub struct MapConfig {
+    pub services: BTreeMap<String, ServiceConfig>,
+    pub limits: HashMap<u32, u32>,
+}
+
+impl Default for MapConfig {
+    fn default() -> Self {
+        Self {
+            services: BTreeMap::from([(
+                "web".into(),
+                ServiceConfig {
+                    port: 8080,
+                    enabled: true,
+                },
+            )]),
+            limits: HashMap::from([(1, 10)]),
+        }
+    }
+}
+
+#[test]
+fn untouched_default_entries_are_not_saved() {
+    let obj: MapConfigImpex = Default::default();
+    assert_eq!(1, obj.services.len());
+    assert_eq!("{}", serde_json::to_string(&obj).unwrap());
+}
+
+#[test]
+fn added_and_partially_overridden_entries_round_trip() {
+    let text = r#"{"services":{"db":{"port":5432},"web":{"enabled":false}}}"#;
+    let obj: MapConfigImpex = serde_json::from_str(text).unwrap();
+
+    let web = &obj.services["web"];
+    assert_eq!(8080, *web.port);
+    assert!(web.port.is_implicit());
+    assert!(!*web.enabled);
+    assert!(obj.services["db"].is_explicit());
+    assert!(obj.limits.is_implicit());
+    assert_eq!(Some(&10), obj.limits.get(&1).map(|x| &**x));
+    assert_eq!(text, serde_json::to_string(&obj).unwrap());
+}
+
+#[test]
+fn insert_entry_is_explicit() {
+    let mut obj: MapConfigImpex = Default::default();
+    obj.limits.insert_explicit(2, 20);
+    assert_eq!(
+        r#"{"limits":{"2":20}}"#,
+        serde_json::to_string(&obj).unwrap()
+    );
+}
+
+#[test]
+fn edit_leaves_tombstones_for_removed_entries() {
+    let mut obj: MapConfigImpex = Default::default();
+    let mut services = obj.services.edit();
+    services.remove("web");
+    services.insert("db".into(), ServiceConfig::default());
+    drop(services);
+
+    let text = serde_json::to_string(&obj).unwrap();
+    assert_eq!(
+        r#"{"services":{"db":{"port":80,"enabled":true},"web":null}}"#,
+        text
+    );
+    let obj: MapConfigImpex = serde_json::from_str(&text).unwrap();
+    assert_eq!(vec!["db"], obj.services.keys().collect::<Vec<_>>());
+}
+
+#[test]
+fn merge_and_rebase_by_key() {
+    let mut obj: MapConfigImpex = Default::default();
+    obj.merge(serde_json::from_str(r#"{"services":{"db":{"port":5432}}}"#).unwrap());
+    assert_eq!(2, obj.services.len());
+
+    let mut new_default = MapConfig::default();
+    new_default
+        .services
+        .insert("cache".into(), ServiceConfig::default());
+    new_default.services.remove("web");
+    let changes = obj.rebase_defaults(new_default);
+    assert_eq!(
+        vec![
+            "default for services.web changed from Some(ServiceConfig { port: 8080, enabled: true }) to None",
+            "default for services.cache changed from None to Some(ServiceConfig { port: 80, enabled: true })",
+        ],
+        changes.iter().map(|x| x.to_string()).collect::<Vec<_>>()
+    );
+    assert_eq!(vec!["cache", "db"], obj.services.keys().collect::<Vec<_>>());
+}
+
+#[test]
+fn visit_path_by_key() {
+    use impex::VisitPath;
+
+    let mut obj: MapConfigImpex = Default::default();
+    obj.materialize_defaults_at(&"services.web.port".into())
+        .unwrap();
+    assert_eq!(
+        r#"{"services":{"web":{"port":8080}}}"#,
+        serde_json::to_string(&obj).unwrap()
+    );
+    assert!(obj.materialize_defaults_at(&"limits.2".into()).is_err());
+}
+
+#[test]
+fn tombstone_keeps_default_entry_removed() {
+    let mut obj: MapConfigImpex = Default::default();
+    assert!(obj.services.remove_explicit("web".into()).is_some());
+    let text = serde_json::to_string(&obj).unwrap();
+    assert_eq!(r#"{"services":{"web":null}}"#, text);
+
+    let obj: MapConfigImpex = serde_json::from_str(&text).unwrap();
+    assert!(obj.services.is_empty());
+    assert_eq!(["web".to_string()], obj.services.removed_keys());
+    assert_eq!(text, serde_json::to_string(&obj).unwrap());
+}
+
+#[test]
+fn tombstones_are_compared_as_set() {
+    let mut a: MapConfigImpex = Default::default();
+    a.limits.insert_explicit(2, 20);
+    let mut b = a.clone();
+    a.limits.remove_explicit(1);
+    a.limits.remove_explicit(2);
+    b.limits.remove_explicit(2);
+    b.limits.remove_explicit(1);
+    assert_eq!(a.limits, b.limits);
+
+    b.limits.insert_explicit(2, 20);
+    assert_ne!(a.limits, b.limits);
+}
+
+#[test]
+fn tombstone_in_overlay_removes_entry() {
+    let mut obj: MapConfigImpex =
+        serde_json::from_str(r#"{"services":{"db":{"port":5432}}}"#).unwrap();
+    obj.merge(serde_json::from_str(r#"{"services":{"db":null,"web":null}}"#).unwrap());
+    assert!(obj.services.is_empty());
+
+    obj.merge(serde_json::from_str(r#"{"services":{"web":{"port":81}}}"#).unwrap());
+    assert_eq!(81, *obj.services["web"].port);
+    assert_eq!(
+        r#"{"services":{"db":null,"web":{"port":81}}}"#,
+        serde_json::to_string(&obj).unwrap()
+    );
+
+    // `db` is not in the default, so its tombstone is not needed
+    obj.prune_defaults();
+    assert_eq!(
+        r#"{"services":{"web":{"port":81}}}"#,
+        serde_json::to_string(&obj).unwrap()
+    );
+}
+
+#[test]
+fn rebase_skips_removed_entries() {
+    let mut obj: MapConfigImpex = serde_json::from_str(r#"{"services":{"web":null}}"#).unwrap();
+    let changes = obj.rebase_defaults(MapConfig::default());
+    assert!(changes.is_empty());
+    assert!(obj.services.is_empty());
+}
@@ -0,0 +1,107 @@
+use std::collections::BTreeMap;
+
+use impex::{FieldMeta, ImpexMeta, MetaValue, catalog::SettingsCatalog};
+
+#[derive(Default, impex::Impex)]
+pub struct Settings {
+    /// Number of worker threads
+    #[impex(meta(
+        label = "Threads",
+        min = 1,
+        max = 64,
+        unit = "cores",
+        category = "Performance"
+    ))]
+    pub threads: u32,
+    #[impex(meta(min = -1.5, step = 0.5, description = "Offset of the clock", advanced))]
+    pub offset: f64,
+    #[impex(meta(allowed = ["fast", 2, 2.5, true], advanced = false))]
+    pub mode: String,
+    pub cache: Option<Cache>,
+    pub caches: Vec<Cache>,
+    pub named: BTreeMap<String, Cache>,
+    pub untagged: u8,
+}
+
+#[derive(Default, Clone, impex::Impex)]
+pub struct Cache {
+    #[impex(meta(label = "Size", unit = "MiB"))]
+    pub size: u32,
+}
+
+#[test]
+fn meta_is_read_from_attributes() {
+    assert_eq!(
+        Some(&FieldMeta {
+            label: Some("Threads"),
+            description: Some("Number of worker threads"),
+            unit: Some("cores"),
+            min: Some(1.0),
+            max: Some(64.0),
+            category: Some("Performance"),
+            ..FieldMeta::EMPTY
+        }),
+        <SettingsImpex>::field_meta(&["threads"])
+    );
+    assert_eq!(
+        Some(&FieldMeta {
+            description: Some("Offset of the clock"),
+            min: Some(-1.5),
+            step: Some(0.5),
+            advanced: true,
+            ..FieldMeta::EMPTY
+        }),
+        <SettingsImpex>::field_meta(&["offset"])
+    );
+    assert_eq!(
+        [
+            MetaValue::Str("fast"),
+            MetaValue::Int(2),
+            MetaValue::Float(2.5),
+            MetaValue::Bool(true)
+        ],
+        <SettingsImpex>::field_meta(&["mode"]).unwrap().allowed
+    );
+    assert_eq!(None, <SettingsImpex>::field_meta(&["untagged"]));
+    assert_eq!(None, <SettingsImpex>::field_meta(&["threads", "0"]));
+    assert_eq!(None, <SettingsImpex>::field_meta(&["unknown"]));
+}
+
+#[test]
+fn meta_is_keyed_by_path() {
+    let size = Some(&FieldMeta {
+        label: Some("Size"),
+        unit: Some("MiB"),
+        ..FieldMeta::EMPTY
+    });
+    assert_eq!(size, <SettingsImpex>::field_meta(&["cache", "size"]));
+    assert_eq!(size, <SettingsImpex>::field_meta(&["caches", "7", "size"]));
+    assert_eq!(size, <SettingsImpex>::field_meta(&["named", "any", "size"]));
+    assert_eq!(
+        None,
+        <SettingsImpex>::field_meta(&["caches", "first", "size"])
+    );
+    assert_eq!(None, <SettingsImpex>::field_meta(&["cache"]));
+}
+
+#[test]
+fn fields_meta_lists_static_paths() {
+    let paths: Vec<_> = <SettingsImpex>::fields_meta()
+        .into_iter()
+        .map(|(path, _)| path.to_string())
+        .collect();
+    assert_eq!(
+        ["threads", "offset", "mode", "cache.size"],
+        paths.as_slice()
+    );
+}
+
+#[test]
+fn catalog_shares_the_meta() {
+    let catalog = SettingsCatalog::new::<_, SettingsImpex>();
+    let offset = &catalog.settings[1];
+    assert_eq!("offset", offset.path.to_string());
+    assert_eq!(<SettingsImpex>::field_meta(&["offset"]), offset.meta);
+    assert_eq!(Some("Offset of the clock"), offset.description.as_deref());
+    assert!(catalog.settings[3].meta.is_none());
+}
@@ -0,0 +1,55 @@
+use impex::{DefaultWrapperSettings, OptionImpex};
+
+#[derive(Default, impex::Impex)]
+pub struct OptionStruct {
+    pub opt: Option<i32>,
+    pub name: Option<String>,
+}
+
+#[test]
+fn take_leaves_explicit_none() {
+    let mut obj: OptionStructImpex = serde_json::from_str(r#"{"opt":42}"#).unwrap();
+    assert_eq!(Some(42), obj.opt.take().map(|x| x.into_value()));
+    assert!(obj.opt.is_none());
+    // Primitive wrappers work with any settings, so they are named
+    assert!(obj.opt.is_explicit::<DefaultWrapperSettings>());
+    assert_eq!(r#"{"opt":null}"#, serde_json::to_string(&obj).unwrap());
+}
+
+#[test]
+fn replace_and_get_or_insert_with_mark_explicit() {
+    let mut obj: OptionStructImpex = Default::default();
+    assert!(obj.opt.replace::<DefaultWrapperSettings>(1).is_none());
+    assert_eq!(
+        Some(1),
+        obj.opt
+            .replace::<DefaultWrapperSettings>(2)
+            .map(|x| x.into_value())
+    );
+
+    let name = obj
+        .name
+        .get_or_insert_with::<DefaultWrapperSettings>(|| "foo".into());
+    assert_eq!("foo", name.as_str());
+    let name = obj
+        .name
+        .get_or_insert_with::<DefaultWrapperSettings>(|| "bar".into());
+    assert_eq!("foo", name.as_str());
+    assert_eq!(
+        r#"{"opt":2,"name":"foo"}"#,
+        serde_json::to_string(&obj).unwrap()
+    );
+}
+
+#[test]
+fn option_like_accessors() {
+    let obj: OptionStructImpex = serde_json::from_str(r#"{"name":"foo"}"#).unwrap();
+    assert_eq!(Some("foo"), obj.name.as_deref().map(String::as_str));
+    assert_eq!(1, obj.name.iter().count());
+    assert_eq!(0, obj.opt.iter().count());
+    assert_eq!(3, obj.name.map(|x| x.len()).unwrap_or(0));
+
+    let none = OptionImpex::<i32>::explicit_none().map(|x| x + 1);
+    assert_eq!(OptionImpex::None(true), none);
+    assert_eq!(5, none.unwrap_or(5));
+}
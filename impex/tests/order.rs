@@ -0,0 +1,99 @@
+use std::collections::BTreeMap;
+
+use impex::ImpexMerge;
+
+#[derive(Clone, Default, impex::Impex)]
+#[impex(preserve_order)]
+#[impex(derive(Debug, PartialEq))]
+pub struct Server {
+    pub host: String,
+    pub port: u16,
+    pub tls: bool,
+}
+
+#[derive(Clone, Default, impex::Impex)]
+#[impex(preserve_order)]
+pub struct Config {
+    pub name: String,
+    pub server: Server,
+    pub tags: BTreeMap<String, u32>,
+}
+
+#[test]
+fn struct_keys_keep_document_order() {
+    let text = r#"{"server":{"tls":true,"host":"a"},"name":"x"}"#;
+    let config: ConfigImpex = serde_json::from_str(text).unwrap();
+    assert_eq!(text, serde_json::to_string(&config).unwrap());
+}
+
+#[test]
+fn new_keys_are_appended() {
+    let mut server: ServerImpex = serde_json::from_str(r#"{"tls":true}"#).unwrap();
+    server.port.set_explicit(8080);
+    server.host.set_explicit("a".into());
+    assert_eq!(
+        r#"{"tls":true,"host":"a","port":8080}"#,
+        serde_json::to_string(&server).unwrap()
+    );
+}
+
+#[test]
+fn map_keys_keep_document_order() {
+    let text = r#"{"tags":{"zeta":1,"alpha":2,"mid":null}}"#;
+    let mut config: ConfigImpex = serde_json::from_str(text).unwrap();
+    assert_eq!(text, serde_json::to_string(&config).unwrap());
+
+    config.tags.insert_explicit("beta".into(), 3);
+    config.tags.remove_explicit("zeta".into());
+    assert_eq!(
+        r#"{"tags":{"zeta":null,"alpha":2,"mid":null,"beta":3}}"#,
+        serde_json::to_string(&config).unwrap()
+    );
+}
+
+#[test]
+fn order_is_not_part_of_the_value() {
+    let a: ServerImpex = serde_json::from_str(r#"{"host":"a","port":1}"#).unwrap();
+    let b: ServerImpex = serde_json::from_str(r#"{"port":1,"host":"a"}"#).unwrap();
+    assert_eq!(a, b);
+}
+
+#[test]
+fn merge_appends_order_of_overlay() {
+    let mut base: ServerImpex = serde_json::from_str(r#"{"port":1}"#).unwrap();
+    let overlay: ServerImpex = serde_json::from_str(r#"{"tls":true,"host":"b"}"#).unwrap();
+    base.merge(overlay);
+    assert_eq!(
+        r#"{"port":1,"tls":true,"host":"b"}"#,
+        serde_json::to_string(&base).unwrap()
+    );
+}
+
+#[derive(Clone, Default, impex::Impex)]
+pub struct Declared {
+    pub host: String,
+    pub port: u16,
+}
+
+#[test]
+fn declaration_order_without_preserve_order() {
+    let config: DeclaredImpex = serde_json::from_str(r#"{"port":1,"host":"a"}"#).unwrap();
+    assert_eq!(
+        r#"{"host":"a","port":1}"#,
+        serde_json::to_string(&config).unwrap()
+    );
+}
+
+#[derive(Clone, Default, impex::Impex)]
+#[impex(preserve_order, preserve_unknown)]
+pub struct Loose {
+    pub host: String,
+    pub port: u16,
+}
+
+#[test]
+fn unknown_keys_keep_their_position() {
+    let text = r#"{"port":1,"extra":true,"host":"a"}"#;
+    let config: LooseImpex = serde_json::from_str(text).unwrap();
+    assert_eq!(text, serde_json::to_string(&config).unwrap());
+}
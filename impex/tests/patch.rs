@@ -0,0 +1,143 @@
+use impex::{DefaultWrapperSettings, ImpexDefaults, ImpexMerge, IntoImpex, OptionImpex, Patch};
+
+use crate::generated_struct::{
+    EnumConfig, EnumConfigImpex, KeyStructConfigImpex, TupleStructConfig,
+};
+
+#[allow(unused)]
+mod generated_struct;
+
+#[derive(Default, impex::Impex)]
+pub struct PatchStruct {
+    pub name: Patch<String>,
+    pub limit: Patch<u32>,
+    pub count: u32,
+}
+
+#[derive(Default, impex::Impex)]
+pub struct OptionStruct {
+    pub opt: Option<i32>,
+}
+
+#[test]
+fn patch_distinguishes_absent_null_and_set() {
+    let obj: PatchStructImpex = serde_json::from_str(r#"{"name":null,"limit":3}"#).unwrap();
+    assert!(obj.name.is_null());
+    assert!(obj.name.is_explicit());
+    assert_eq!(Some(3), obj.limit.as_ref().into_option().map(|x| **x));
+
+    let obj: PatchStructImpex = serde_json::from_str("{}").unwrap();
+    assert!(obj.name.is_absent());
+    assert!(obj.name.is_implicit());
+    assert_eq!("{}", serde_json::to_string(&obj).unwrap());
+}
+
+#[test]
+fn patch_serializes_null_but_skips_absent() {
+    let obj: PatchStructImpex = serde_json::from_str(r#"{"name":null,"count":1}"#).unwrap();
+    assert_eq!(
+        r#"{"name":null,"count":1}"#,
+        serde_json::to_string(&obj).unwrap()
+    );
+}
+
+#[test]
+fn merge_patch_only_touches_explicit_values() {
+    let mut obj: PatchStructImpex =
+        serde_json::from_str(r#"{"name":"foo","limit":3,"count":1}"#).unwrap();
+    let overlay: PatchStructImpex = serde_json::from_str(r#"{"name":null,"count":2}"#).unwrap();
+    obj.merge(overlay);
+
+    assert!(obj.name.is_null());
+    assert_eq!(
+        r#"{"name":null,"limit":3,"count":2}"#,
+        serde_json::to_string(&obj).unwrap()
+    );
+}
+
+#[test]
+fn merge_nested_overlay() {
+    let mut obj: KeyStructConfigImpex =
+        serde_json::from_str(r#"{"num_cores":3,"tuple_struct_config":[1,2]}"#).unwrap();
+    let mut overlay: KeyStructConfigImpex =
+        serde_json::from_str(r#"{"num_threads":[1,2]}"#).unwrap();
+    overlay.tuple_struct_config.1.set_explicit(5);
+    obj.merge(overlay);
+
+    assert_eq!(3, *obj.num_cores);
+    assert_eq!(2, obj.num_threads.len());
+    assert_eq!(1, *obj.tuple_struct_config.0);
+    assert_eq!(5, *obj.tuple_struct_config.1);
+}
+
+#[test]
+fn merge_enum_replaces_different_variant() {
+    let mut obj: EnumConfigImpex = Default::default();
+    let overlay: EnumConfigImpex = EnumConfig::Foo {
+        foo_value: "foo".into(),
+        tuple_struct_config: TupleStructConfig::default(),
+    }
+    .into_explicit();
+    obj.merge(overlay);
+    let EnumConfigImpex::Foo { foo_value, .. } = &obj else {
+        panic!("Expected EnumConfigImpex::Foo")
+    };
+    assert_eq!("foo", foo_value.as_str());
+
+    // An implicit overlay leaves the value untouched
+    obj.merge(EnumConfigImpex::default());
+    assert!(matches!(obj, EnumConfigImpex::Foo { .. }));
+}
+
+#[test]
+fn apply_patch_to_option() {
+    let mut obj: OptionStructImpex = serde_json::from_str(r#"{"opt":1}"#).unwrap();
+    obj.opt.apply_patch::<DefaultWrapperSettings>(Patch::Absent);
+    assert_eq!(Some(1), obj.opt.as_deref().copied());
+
+    obj.opt.apply_patch::<DefaultWrapperSettings>(Patch::Set(
+        IntoImpex::<DefaultWrapperSettings>::into_explicit(2),
+    ));
+    assert_eq!(Some(2), obj.opt.as_deref().copied());
+
+    obj.opt
+        .apply_patch::<DefaultWrapperSettings>(Patch::implicit_null());
+    assert_eq!(Some(2), obj.opt.as_deref().copied());
+
+    obj.opt
+        .apply_patch::<DefaultWrapperSettings>(Patch::explicit_null());
+    assert!(obj.opt.is_none() && obj.opt.is_explicit::<DefaultWrapperSettings>());
+    assert_eq!(
+        Patch::<i32>::explicit_null(),
+        Patch::from(OptionImpex::None(true))
+    );
+}
+
+#[derive(impex::Impex)]
+pub struct NullDefault {
+    pub cleared: Patch<u32>,
+}
+
+impl Default for NullDefault {
+    fn default() -> Self {
+        Self {
+            cleared: Patch::explicit_null(),
+        }
+    }
+}
+
+#[test]
+fn null_default_stays_implicit() {
+    let mut obj: NullDefaultImpex = Default::default();
+    assert!(obj.cleared.is_null() && obj.cleared.is_implicit());
+    assert_eq!("{}", serde_json::to_string(&obj).unwrap());
+
+    let loaded: NullDefaultImpex = serde_json::from_str("{}").unwrap();
+    assert!(loaded.cleared.is_null() && loaded.cleared.is_implicit());
+
+    obj = serde_json::from_str(r#"{"cleared":null}"#).unwrap();
+    assert!(obj.cleared.is_explicit());
+    obj.prune_defaults();
+    assert!(obj.cleared.is_null() && obj.cleared.is_implicit());
+    assert_eq!("{}", serde_json::to_string(&obj).unwrap());
+}
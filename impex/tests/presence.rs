@@ -0,0 +1,118 @@
+use impex::{Impex, ImpexDefaults, ImpexMerge};
+
+#[derive(Default, impex::Impex)]
+#[impex(track_presence)]
+#[impex(derive(Debug, PartialEq))]
+pub struct Limits {
+    pub min: u32,
+    pub max: u32,
+}
+
+#[derive(Default, impex::Impex)]
+pub struct PresenceConfig {
+    pub limits: Limits,
+    pub opt: Option<Limits>,
+}
+
+#[test]
+fn empty_object_stays_explicit() {
+    let text = r#"{"limits":{},"opt":{}}"#;
+    let obj: PresenceConfigImpex = serde_json::from_str(text).unwrap();
+    assert!(obj.limits.is_explicit());
+    assert!(obj.limits.min.is_implicit());
+    assert!(obj.opt.is_some());
+    assert_eq!(text, serde_json::to_string(&obj).unwrap());
+
+    let obj: PresenceConfigImpex = serde_json::from_str("{}").unwrap();
+    assert!(obj.limits.is_implicit());
+    assert!(obj.opt.is_none());
+    assert_eq!("{}", serde_json::to_string(&obj).unwrap());
+}
+
+#[test]
+fn presence_is_set_explicit_and_merged() {
+    let mut obj: PresenceConfigImpex = Default::default();
+    obj.limits.set_explicit(Limits::default());
+    assert!(obj.limits.is_explicit());
+    assert_eq!(
+        r#"{"limits":{"min":0,"max":0}}"#,
+        serde_json::to_string(&obj).unwrap()
+    );
+
+    let mut merged: PresenceConfigImpex = Default::default();
+    merged.merge(serde_json::from_str(r#"{"limits":{}}"#).unwrap());
+    assert!(merged.limits.is_explicit());
+    assert_ne!(
+        LimitsImpex::<impex::DefaultWrapperSettings>::default(),
+        merged.limits
+    );
+}
+
+#[test]
+fn prune_defaults_clears_presence() {
+    let mut obj: PresenceConfigImpex =
+        serde_json::from_str(r#"{"limits":{"min":0},"opt":{}}"#).unwrap();
+    obj.prune_defaults();
+    assert!(obj.limits.is_implicit());
+    // `Some` differs from the default `None`, so it is kept
+    assert_eq!(r#"{"opt":{}}"#, serde_json::to_string(&obj).unwrap());
+}
+
+#[derive(Default, impex::Impex)]
+#[impex(track_presence)]
+#[impex(derive(Debug, PartialEq))]
+pub struct Range(pub u32, pub u32);
+
+#[derive(Default, impex::Impex)]
+pub struct RangeConfig {
+    pub range: Range,
+    pub opt: Option<Range>,
+}
+
+#[test]
+fn tuple_struct_presence() {
+    let mut obj: RangeConfigImpex = serde_json::from_str(r#"{"range":[0,0]}"#).unwrap();
+    obj.range.0.set_implicit(0);
+    obj.range.1.set_implicit(0);
+    assert!(obj.range.is_explicit());
+    assert_eq!(
+        r#"{"range":[null,null]}"#,
+        serde_json::to_string(&obj).unwrap()
+    );
+
+    let mut merged: RangeConfigImpex = Default::default();
+    merged.merge(obj);
+    assert!(merged.range.is_explicit());
+
+    merged.prune_defaults();
+    assert!(merged.range.is_implicit());
+    assert_eq!("{}", serde_json::to_string(&merged).unwrap());
+}
+
+mod nested {
+    #[derive(Default, impex::Impex)]
+    #[impex(track_presence)]
+    pub struct Window {
+        pub width: u32,
+    }
+
+    #[derive(Default, impex::Impex)]
+    #[impex(track_presence)]
+    pub struct Span(pub u32, pub u32);
+}
+
+#[test]
+fn presence_allows_struct_literals() {
+    let window = nested::WindowImpex::<impex::DefaultWrapperSettings> {
+        _is_present: true,
+        ..Default::default()
+    };
+    assert!(window.is_explicit() && window.width.is_implicit());
+
+    let span = nested::SpanImpex::<impex::DefaultWrapperSettings>(
+        Default::default(),
+        Default::default(),
+        true,
+    );
+    assert!(span.is_explicit() && span.0.is_implicit());
+}
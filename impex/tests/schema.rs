@@ -0,0 +1,281 @@
+use std::collections::BTreeMap;
+
+use impex::{DefaultWrapperSettings, Impex, schema::ImpexSchema};
+use serde_json::{Value, json};
+
+use crate::generated_struct::{
+    EnumConfig, EnumConfigImpex, KeyStructConfigImpex, MixedEnumConfig, MixedEnumConfigImpex,
+    StructWithUnitEnumImpex, TupleStructConfig,
+};
+
+#[allow(unused)]
+mod generated_struct;
+
+/// Settings of a service
+#[derive(Default, impex::Impex)]
+#[impex(preserve_unknown)]
+pub struct Service {
+    /// Port to listen on
+    /// after startup
+    pub port: u16,
+    pub name: Option<String>,
+    pub labels: BTreeMap<String, u8>,
+    #[impex(list = "extend")]
+    pub plugins: Vec<String>,
+    #[impex(merge_key = "name")]
+    pub routes: Vec<Route>,
+}
+
+#[derive(Default, Clone, PartialEq, impex::Impex)]
+pub struct Route {
+    pub name: String,
+    pub weight: (u8, u8),
+}
+
+fn schema<T: ImpexSchema<DefaultWrapperSettings>>() -> serde_json::Value {
+    T::json_schema()
+}
+
+#[test]
+fn struct_properties_are_optional_with_defaults() {
+    let schema = impex::schema::json_schema::<_, KeyStructConfigImpex>();
+    assert_eq!(
+        "https://json-schema.org/draft/2020-12/schema",
+        schema["$schema"]
+    );
+    assert_eq!(json!(false), schema["additionalProperties"]);
+    assert!(schema.get("required").is_none());
+    let properties = &schema["properties"];
+    assert_eq!(
+        json!({ "type": "integer", "minimum": 0, "maximum": u32::MAX, "default": 0 }),
+        properties["num_cores"]
+    );
+    assert_eq!(json!([42]), properties["num_threads"]["default"]);
+    assert_eq!(
+        json!({"Bar": ["Bar", 42, [42, 43]]}),
+        properties["enum_config"]["default"]
+    );
+    assert_eq!(
+        json!({
+            "type": "array",
+            "prefixItems": [
+                {
+                    "anyOf": [
+                        { "type": "integer", "minimum": i32::MIN, "maximum": i32::MAX },
+                        { "type": "null" },
+                    ],
+                    "default": 42,
+                },
+                { "anyOf": [{ "type": "integer" }, { "type": "null" }], "default": 43 },
+            ],
+            "minItems": 2,
+            "maxItems": 2,
+            "default": [42, 43],
+        }),
+        properties["tuple_struct_config"]
+    );
+}
+
+#[test]
+fn enums_follow_the_serialized_representation() {
+    assert_eq!(
+        json!({
+            "description": "A mixed enum with both unit and non-unit variants",
+            "oneOf": [
+                { "const": "Empty" },
+                {
+                    "type": "object",
+                    "properties": {
+                        "Named": {
+                            "type": "object",
+                            "properties": {
+                                "value": { "anyOf": [{ "type": "string" }, { "type": "null" }] },
+                            },
+                            "required": ["value"],
+                            "additionalProperties": false,
+                        },
+                    },
+                    "required": ["Named"],
+                    "additionalProperties": false,
+                },
+                {
+                    "type": "object",
+                    "properties": {
+                        "Tuple": {
+                            "anyOf": [
+                                { "type": "integer", "minimum": i32::MIN, "maximum": i32::MAX },
+                                { "type": "null" },
+                            ],
+                        },
+                    },
+                    "required": ["Tuple"],
+                    "additionalProperties": false,
+                },
+            ],
+        }),
+        schema::<MixedEnumConfigImpex>()
+    );
+}
+
+#[test]
+fn doc_comments_become_descriptions() {
+    let schema = schema::<ServiceImpex>();
+    assert_eq!("Settings of a service", schema["description"]);
+    assert_eq!(
+        "Port to listen on\nafter startup",
+        schema["properties"]["port"]["description"]
+    );
+    assert!(schema["properties"]["name"].get("description").is_none());
+}
+
+#[test]
+fn wrappers_describe_their_persisted_form() {
+    let schema = schema::<ServiceImpex>();
+    assert_eq!(json!(true), schema["additionalProperties"]);
+    let properties = &schema["properties"];
+    assert_eq!(
+        json!({ "anyOf": [{ "type": "string" }, { "type": "null" }], "default": null }),
+        properties["name"]
+    );
+    assert_eq!(
+        json!({ "type": "null" }),
+        properties["labels"]["additionalProperties"]["anyOf"][1]
+    );
+    let list = json!({ "type": "array", "items": { "type": "string" } });
+    assert_eq!(
+        json!({
+            "type": "object",
+            "properties": { "^": list, "+": list, "-": list },
+            "additionalProperties": false,
+        }),
+        properties["plugins"]
+    );
+
+    let route = &properties["routes"]["items"];
+    assert_eq!(json!("array"), properties["routes"]["type"]);
+    assert_eq!(json!(""), route["properties"]["name"]["default"]);
+    // Implicit tuple elements are written as null
+    assert_eq!(
+        json!({ "type": "null" }),
+        route["properties"]["weight"]["prefixItems"][0]["anyOf"][1]
+    );
+}
+
+/// Checks `value` against the keywords used by the generated schemas
+fn is_valid(schema: &Value, value: &Value) -> bool {
+    let number = |key: &str| schema.get(key).and_then(Value::as_f64);
+    let len = |key: &str| schema.get(key).and_then(Value::as_u64);
+    let type_matches = match schema.get("type").and_then(Value::as_str) {
+        None => true,
+        Some("null") => value.is_null(),
+        Some("boolean") => value.is_boolean(),
+        Some("integer") => value.is_i64() || value.is_u64(),
+        Some("number") => value.is_number(),
+        Some("string") => value.is_string(),
+        Some("array") => value.is_array(),
+        Some("object") => value.is_object(),
+        Some(other) => panic!("unexpected type {other}"),
+    };
+    let in_range = value.as_f64().is_none_or(|x| {
+        number("minimum").is_none_or(|min| x >= min) && number("maximum").is_none_or(|max| x <= max)
+    });
+    let const_matches = schema.get("const").is_none_or(|x| x == value);
+    let any_of = schema
+        .get("anyOf")
+        .and_then(Value::as_array)
+        .is_none_or(|alternatives| alternatives.iter().any(|schema| is_valid(schema, value)));
+    let one_of = schema
+        .get("oneOf")
+        .and_then(Value::as_array)
+        .is_none_or(|alternatives| {
+            alternatives
+                .iter()
+                .filter(|schema| is_valid(schema, value))
+                .count()
+                == 1
+        });
+    let array_matches = value.as_array().is_none_or(|items| {
+        let prefix = schema.get("prefixItems").and_then(Value::as_array);
+        len("minItems").is_none_or(|min| items.len() as u64 >= min)
+            && len("maxItems").is_none_or(|max| items.len() as u64 <= max)
+            && items.iter().enumerate().all(|(index, item)| {
+                match prefix
+                    .and_then(|prefix| prefix.get(index))
+                    .or(schema.get("items"))
+                {
+                    Some(schema) => is_valid(schema, item),
+                    None => true,
+                }
+            })
+    });
+    let object_matches = value.as_object().is_none_or(|object| {
+        let required = schema.get("required").and_then(Value::as_array);
+        required.is_none_or(|required| {
+            required
+                .iter()
+                .all(|key| object.contains_key(key.as_str().unwrap()))
+        }) && object.iter().all(|(key, value)| {
+            match schema.get("properties").and_then(|x| x.get(key)) {
+                Some(schema) => is_valid(schema, value),
+                None => match schema.get("additionalProperties") {
+                    Some(Value::Bool(allowed)) => *allowed,
+                    Some(schema) => is_valid(schema, value),
+                    None => true,
+                },
+            }
+        })
+    });
+    type_matches && in_range && const_matches && any_of && one_of && array_matches && object_matches
+}
+
+fn assert_valid<T: ImpexSchema<DefaultWrapperSettings> + serde::Serialize>(value: &T) -> String {
+    let text = serde_json::to_string(value).unwrap();
+    let document: Value = serde_json::from_str(&text).unwrap();
+    assert!(is_valid(&T::json_schema(), &document), "{text}");
+    text
+}
+
+#[test]
+fn serialized_documents_match_the_schema() {
+    let mut config: KeyStructConfigImpex = Default::default();
+    config.tuple_struct_config.1.set_explicit(44);
+    let EnumConfigImpex::Bar(_, value, _) = &mut config.enum_config else {
+        panic!("expected the default variant");
+    };
+    value.set_explicit(43);
+    let text = assert_valid(&config);
+    assert_eq!(
+        r#"{"enum_config":{"Bar":[null,43,[null,null]]},"tuple_struct_config":[null,44]}"#,
+        text
+    );
+    // Implicit elements written as null are loaded from the default again
+    let loaded: KeyStructConfigImpex = serde_json::from_str(&text).unwrap();
+    assert_eq!(42, *loaded.tuple_struct_config.0);
+    assert!(loaded.tuple_struct_config.0.is_implicit());
+    assert_eq!(text, serde_json::to_string(&loaded).unwrap());
+
+    config.enum_config.set_explicit(EnumConfig::Foo {
+        foo_value: "foo".into(),
+        tuple_struct_config: TupleStructConfig(1, 2),
+    });
+    let EnumConfigImpex::Foo { foo_value, .. } = &mut config.enum_config else {
+        panic!("expected the variant which was set");
+    };
+    foo_value.set_implicit("foo".into());
+    let text = assert_valid(&config);
+    let loaded: KeyStructConfigImpex = serde_json::from_str(&text).unwrap();
+    assert_eq!(text, serde_json::to_string(&loaded).unwrap());
+
+    let mut mixed: StructWithUnitEnumImpex = Default::default();
+    mixed.mixed_enum.set_explicit(MixedEnumConfig::Tuple(3));
+    assert_eq!(r#"{"mixed_enum":{"Tuple":3}}"#, assert_valid(&mixed));
+    mixed
+        .mixed_enum
+        .set_explicit(MixedEnumConfig::Named { value: "x".into() });
+    assert_valid(&mixed);
+
+    let mut service: ServiceImpex =
+        serde_json::from_str(r#"{"routes":[{"name":"a","weight":[1,2]}],"extra":1}"#).unwrap();
+    service.port.set_explicit(8080);
+    assert_valid(&service);
+}
@@ -346,7 +346,10 @@ fn option_impex_explicit_null_reserializes_as_null() {
         serde_json::from_str(json).unwrap();
 
     // The field should be explicit (null was present in JSON)
-    assert!(obj.opt.is_explicit(), "null in JSON should be explicit");
+    assert!(
+        obj.opt.is_explicit::<impex::DefaultWrapperSettings>(),
+        "null in JSON should be explicit"
+    );
     assert!(obj.opt.is_none(), "Value should be None");
 
     // When serializing back, explicit null should appear
@@ -362,7 +365,10 @@ fn option_impex_missing_field_is_implicit_and_not_serialized() {
         serde_json::from_str(json).unwrap();
 
     // The field should be implicit (not present in JSON)
-    assert!(obj.opt.is_implicit(), "Missing field should be implicit");
+    assert!(
+        obj.opt.is_implicit::<impex::DefaultWrapperSettings>(),
+        "Missing field should be implicit"
+    );
     assert!(obj.opt.is_none(), "Value should be None");
 
     // When serializing back, implicit field should NOT appear
@@ -378,7 +384,10 @@ fn option_impex_explicit_value_reserializes() {
         serde_json::from_str(json).unwrap();
 
     // The field should be explicit
-    assert!(obj.opt.is_explicit(), "Value in JSON should be explicit");
+    assert!(
+        obj.opt.is_explicit::<impex::DefaultWrapperSettings>(),
+        "Value in JSON should be explicit"
+    );
     assert_eq!(**obj.opt.as_ref().unwrap(), 42);
 
     // When serializing back, explicit value should appear
@@ -394,7 +403,10 @@ fn some_option_impex_explicit_null_reserializes_as_null() {
         serde_json::from_str(json).unwrap();
 
     // The field should be explicit (null was present in JSON)
-    assert!(obj.opt.is_explicit(), "null in JSON should be explicit");
+    assert!(
+        obj.opt.is_explicit::<impex::DefaultWrapperSettings>(),
+        "null in JSON should be explicit"
+    );
     assert!(obj.opt.is_none(), "Value should be None");
 
     // When serializing back, explicit null should appear
@@ -410,7 +422,10 @@ fn some_option_impex_missing_field_is_implicit_and_not_serialized() {
         serde_json::from_str(json).unwrap();
 
     // The field should be implicit (not present in JSON)
-    assert!(obj.opt.is_implicit(), "Missing field should be implicit");
+    assert!(
+        obj.opt.is_implicit::<impex::DefaultWrapperSettings>(),
+        "Missing field should be implicit"
+    );
     assert_eq!(
         obj.opt.as_ref().map(|x| **x),
         Some(42),
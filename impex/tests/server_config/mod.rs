@@ -0,0 +1,39 @@
+// Configuration shared by the tests of the document formats
+
+use std::collections::BTreeMap;
+
+#[derive(Clone, impex::Impex)]
+pub struct Limits {
+    pub connections: u32,
+    pub timeout: u64,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            connections: 100,
+            timeout: 30,
+        }
+    }
+}
+
+#[derive(impex::Impex)]
+pub struct ServerConfig {
+    pub name: String,
+    pub port: u16,
+    pub limits: Limits,
+    pub tags: Vec<String>,
+    pub routes: BTreeMap<String, u16>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            name: "server".into(),
+            port: 8080,
+            limits: Limits::default(),
+            tags: Vec::new(),
+            routes: BTreeMap::new(),
+        }
+    }
+}
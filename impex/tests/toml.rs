@@ -0,0 +1,95 @@
+#![cfg(feature = "toml")]
+
+use impex::toml::TomlDocument;
+
+use crate::server_config::ServerConfigImpex;
+
+mod server_config;
+
+const TEXT: &str = r#"# Server settings
+name = 'main'   # shown in the logs
+port = 9000
+
+# Connection limits
+[limits]
+timeout = 60 # seconds
+
+[routes]
+"/" = 80
+"#;
+
+#[test]
+fn unchanged_document_is_kept() {
+    let mut document = TomlDocument::<ServerConfigImpex>::parse(TEXT).unwrap();
+    assert_eq!("main", *document.name);
+    assert!(document.limits.connections.is_implicit());
+    assert_eq!(TEXT, document.save().unwrap());
+}
+
+#[test]
+fn changed_values_keep_comments() {
+    let mut document = TomlDocument::<ServerConfigImpex>::parse(TEXT).unwrap();
+    document.name.set_explicit("backup".into());
+    document.limits.timeout.set_explicit(90);
+    assert_eq!(
+        r#"# Server settings
+name = "backup"   # shown in the logs
+port = 9000
+
+# Connection limits
+[limits]
+timeout = 90 # seconds
+
+[routes]
+"/" = 80
+"#,
+        document.save().unwrap()
+    );
+}
+
+#[test]
+fn implicit_values_are_removed() {
+    let mut document = TomlDocument::<ServerConfigImpex>::parse(TEXT).unwrap();
+    document.port.set_implicit(8080);
+    document.limits.timeout.set_implicit(30);
+    assert_eq!(
+        r#"# Server settings
+name = 'main'   # shown in the logs
+
+[routes]
+"/" = 80
+"#,
+        document.save().unwrap()
+    );
+}
+
+#[test]
+fn new_values_are_appended() {
+    let mut document = TomlDocument::<ServerConfigImpex>::parse(TEXT).unwrap();
+    document.limits.connections.set_explicit(10);
+    document.tags.set_explicit(vec!["a".into()]);
+    document.routes.insert_explicit("/api".into(), 81);
+    assert_eq!(
+        r#"# Server settings
+name = 'main'   # shown in the logs
+port = 9000
+tags = ["a"]
+
+# Connection limits
+[limits]
+timeout = 60 # seconds
+connections = 10
+
+[routes]
+"/" = 80
+"/api" = 81
+"#,
+        document.save().unwrap()
+    );
+}
+
+#[test]
+fn invalid_documents_are_errors() {
+    assert!(TomlDocument::<ServerConfigImpex>::parse("port = ").is_err());
+    assert!(TomlDocument::<ServerConfigImpex>::parse("port = 'x'").is_err());
+}
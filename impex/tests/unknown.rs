@@ -0,0 +1,102 @@
+use impex::{Impex, ImpexMerge, RawValue};
+
+#[derive(Clone, Default, impex::Impex)]
+#[impex(preserve_unknown)]
+#[impex(derive(Debug, PartialEq))]
+pub struct Limits {
+    pub min: u32,
+    pub max: u32,
+}
+
+#[derive(Clone, impex::Impex)]
+#[impex(preserve_unknown)]
+pub enum Mode {
+    Off,
+    Fixed(u32),
+    Dynamic { min: u32, max: u32 },
+}
+
+impl Default for Mode {
+    fn default() -> Self {
+        Self::Dynamic { min: 1, max: 8 }
+    }
+}
+
+#[derive(Default, impex::Impex)]
+#[impex(preserve_unknown)]
+pub struct Config {
+    pub name: String,
+    pub limits: Limits,
+    pub mode: Mode,
+}
+
+#[derive(Default, impex::Impex)]
+pub struct Strict {
+    pub name: String,
+}
+
+#[test]
+fn unknown_keys_round_trip() {
+    let text = r#"{"name":"a","added":[1,{"x":null}],"limits":{"max":3,"ratio":0.5},"mode":{"Dynamic":{"min":1,"max":8,"step":-2}}}"#;
+    let config: ConfigImpex = serde_json::from_str(text).unwrap();
+    assert_eq!("a", *config.name);
+    assert_eq!(3, *config.limits.max);
+    assert_eq!(
+        Some(&RawValue::F64(0.5)),
+        config.limits.unknown_fields().get("ratio")
+    );
+    let mode = config.mode.unknown_fields().unwrap();
+    assert_eq!(Some(&RawValue::I64(-2)), mode.get("step"));
+    assert!(config.mode.is_explicit());
+
+    // Known fields come first when saving
+    assert_eq!(
+        r#"{"name":"a","limits":{"max":3,"ratio":0.5},"mode":{"Dynamic":{"min":1,"max":8,"step":-2}},"added":[1,{"x":null}]}"#,
+        serde_json::to_string(&config).unwrap()
+    );
+}
+
+#[test]
+fn only_unknown_keys_are_saved() {
+    let config: ConfigImpex = serde_json::from_str(r#"{"limits":{"new":true}}"#).unwrap();
+    assert!(config.limits.is_explicit());
+    assert!(config.limits.min.is_implicit() && config.limits.max.is_implicit());
+    assert_eq!(
+        r#"{"limits":{"new":true}}"#,
+        serde_json::to_string(&config).unwrap()
+    );
+}
+
+#[test]
+fn setting_values_keeps_unknown_keys() {
+    let mut config: ConfigImpex = serde_json::from_str(r#"{"limits":{"new":1}}"#).unwrap();
+    config.limits.set_explicit(Limits { min: 2, max: 4 });
+    assert_eq!(
+        r#"{"limits":{"min":2,"max":4,"new":1}}"#,
+        serde_json::to_string(&config).unwrap()
+    );
+
+    config.mode.set_explicit(Mode::Off);
+    assert!(config.mode.unknown_fields().is_none());
+}
+
+#[test]
+fn merge_overlays_unknown_keys() {
+    let mut base: LimitsImpex = serde_json::from_str(r#"{"a":1,"b":2}"#).unwrap();
+    let overlay: LimitsImpex = serde_json::from_str(r#"{"b":3,"c":4}"#).unwrap();
+    base.merge(overlay);
+    assert_eq!(
+        r#"{"a":1,"b":3,"c":4}"#,
+        serde_json::to_string(&base).unwrap()
+    );
+    assert_ne!(
+        base,
+        LimitsImpex::<impex::DefaultWrapperSettings>::default()
+    );
+}
+
+#[test]
+fn unknown_keys_are_dropped_without_attribute() {
+    let strict: StrictImpex = serde_json::from_str(r#"{"name":"a","added":1}"#).unwrap();
+    assert_eq!(r#"{"name":"a"}"#, serde_json::to_string(&strict).unwrap());
+}
@@ -11,6 +11,11 @@ struct GenerateContext<'a> {
     derives: proc_macro2::TokenStream,
     has_partial_eq: bool,
     has_eq: bool,
+    prune_defaults: bool,
+    track_presence: bool,
+    preserve_unknown: bool,
+    preserve_order: bool,
+    description: proc_macro2::TokenStream,
 }
 
 #[proc_macro_derive(Impex, attributes(impex))]
@@ -18,15 +23,83 @@ pub fn derive_impex(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
     let name = &input.ident;
+    let impex_name = &Ident::new(&format!("{}Impex", name), name.span());
     let (derives, has_partial_eq, has_eq) = parse_impex_attributes(&input.attrs);
     let ctx = GenerateContext {
-        impex_name: &Ident::new(&format!("{}Impex", name), name.span()),
+        impex_name,
         original_name: name,
         vis: &input.vis,
         derives,
         has_partial_eq,
         has_eq,
+        prune_defaults: has_impex_flag(&input.attrs, "prune_defaults"),
+        track_presence: has_impex_flag(&input.attrs, "track_presence"),
+        preserve_unknown: has_impex_flag(&input.attrs, "preserve_unknown"),
+        preserve_order: has_impex_flag(&input.attrs, "preserve_order"),
+        description: doc_description(&input.attrs),
+    };
+
+    if ctx.track_presence && !matches!(&input.data, Data::Struct(_)) {
+        panic!("#[impex(track_presence)] is only supported for structs");
+    }
+    if ctx.preserve_unknown
+        && matches!(&input.data, Data::Struct(s) if !matches!(s.fields, Fields::Named(_)))
+    {
+        panic!(
+            "#[impex(preserve_unknown)] is only supported for structs with named fields and enums"
+        );
+    }
+    // Struct variants are written in declaration order, as the order would need to be kept per variant
+    if ctx.preserve_order
+        && !matches!(&input.data, Data::Struct(s) if matches!(s.fields, Fields::Named(_)))
+    {
+        panic!("#[impex(preserve_order)] is only supported for structs with named fields");
+    }
+    let is_list_field = |f: &syn::Field| merge_key(&f.attrs).is_some() || is_extend_list(&f.attrs);
+    let has_list_attribute = match &input.data {
+        Data::Struct(data_struct) if matches!(data_struct.fields, Fields::Named(_)) => false,
+        Data::Struct(data_struct) => data_struct.fields.iter().any(is_list_field),
+        Data::Enum(data_enum) => data_enum
+            .variants
+            .iter()
+            .flat_map(|variant| &variant.fields)
+            .any(is_list_field),
+        Data::Union(_) => false,
+    };
+    if has_list_attribute {
+        panic!(
+            "#[impex(merge_key)] and #[impex(list)] are only supported in structs with named fields"
+        );
+    }
+    let has_meta_attribute = match &input.data {
+        Data::Struct(data_struct) if matches!(data_struct.fields, Fields::Named(_)) => false,
+        Data::Struct(data_struct) => data_struct
+            .fields
+            .iter()
+            .any(|f| has_impex_flag(&f.attrs, "meta")),
+        Data::Enum(data_enum) => data_enum
+            .variants
+            .iter()
+            .flat_map(|variant| &variant.fields)
+            .any(|f| has_impex_flag(&f.attrs, "meta")),
+        Data::Union(_) => false,
+    };
+    if has_meta_attribute {
+        panic!("#[impex(meta)] is only supported in structs with named fields");
+    }
+    let has_variant_pruning = match &input.data {
+        Data::Enum(data_enum) => data_enum
+            .variants
+            .iter()
+            .flat_map(|variant| &variant.fields)
+            .any(|f| has_impex_flag(&f.attrs, "prune_defaults")),
+        _ => false,
     };
+    if has_variant_pruning {
+        panic!(
+            "#[impex(prune_defaults)] on fields is only supported in structs, put it on the enum instead"
+        );
+    }
 
     let expanded = match &input.data {
         Data::Struct(data_struct) => match &data_struct.fields {
@@ -37,8 +110,26 @@ pub fn derive_impex(input: TokenStream) -> TokenStream {
         Data::Enum(data_enum) => generate_enum(ctx, data_enum),
         Data::Union(_) => panic!("Unions are not supported"),
     };
+    let box_impl = generate_box_into_impex(impex_name, name);
+
+    TokenStream::from(quote! {
+        #expanded
+        #box_impl
+    })
+}
 
-    TokenStream::from(expanded)
+/// Generates IntoImpex for boxes of the type, which impex can't implement generically,
+/// as it would overlap with boxed primitives
+fn generate_box_into_impex(impex_name: &Ident, original_name: &Ident) -> proc_macro2::TokenStream {
+    quote! {
+        impl<TW: ::impex::WrapperSettings> ::impex::IntoImpex<TW> for ::std::boxed::Box<#original_name> {
+            type Impex = ::std::boxed::Box<#impex_name<TW>>;
+
+            fn into_impex(self, is_explicit: bool) -> Self::Impex {
+                ::std::boxed::Box::new(::impex::IntoImpex::<TW>::into_impex(*self, is_explicit))
+            }
+        }
+    }
 }
 
 fn parse_impex_attributes(attrs: &[syn::Attribute]) -> (proc_macro2::TokenStream, bool, bool) {
@@ -87,6 +178,438 @@ fn parse_impex_attributes(attrs: &[syn::Attribute]) -> (proc_macro2::TokenStream
     (derives, *has_partial_eq, *has_eq)
 }
 
+/// Name of a field as it appears in paths, without the `r#` prefix of raw identifiers
+fn field_name_str(name: &Ident) -> String {
+    let name = name.to_string();
+    name.strip_prefix("r#").unwrap_or(&name).to_string()
+}
+
+/// Doc comment as `Option<&str>`, used as description in the JSON Schema, the settings catalog
+/// and field metadata
+fn doc_description(attrs: &[syn::Attribute]) -> proc_macro2::TokenStream {
+    let lines: Vec<_> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                value:
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(line),
+                        ..
+                    }),
+                ..
+            }) => Some(line.value()),
+            _ => None,
+        })
+        .collect();
+    let description = lines
+        .iter()
+        .map(|line| line.strip_prefix(' ').unwrap_or(line))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let description = description.trim();
+    if description.is_empty() {
+        quote! { None }
+    } else {
+        quote! { Some(#description) }
+    }
+}
+
+/// Parses `#[impex(meta(label = "...", min = 1, allowed = [...], advanced, ...))]` on a field
+/// into a `FieldMeta` expression. The description defaults to the doc comment.
+fn field_meta(field: &syn::Field) -> Option<proc_macro2::TokenStream> {
+    let entries = field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("impex"))
+        .filter_map(|attr| {
+            attr.parse_args_with(Punctuated::<syn::Meta, Token![,]>::parse_terminated)
+                .ok()
+        })
+        .flatten()
+        .find_map(|meta| match meta {
+            syn::Meta::List(list) if list.path.is_ident("meta") => Some(
+                list.parse_args_with(Punctuated::<syn::Meta, Token![,]>::parse_terminated)
+                    .expect("#[impex(meta(...))] expects a list like `label = \"...\", min = 1`"),
+            ),
+            _ => None,
+        })?;
+
+    let mut description = doc_description(&field.attrs);
+    let mut values = Vec::new();
+    for entry in entries {
+        let name = entry
+            .path()
+            .get_ident()
+            .map(ToString::to_string)
+            .unwrap_or_default();
+        let value = match &entry {
+            syn::Meta::Path(_) if name == "advanced" => {
+                values.push(quote! { advanced: true });
+                continue;
+            }
+            syn::Meta::NameValue(entry) => &entry.value,
+            _ => panic!("Unknown meta entry `{name}`"),
+        };
+        let value = match name.as_str() {
+            "label" | "unit" | "category" => {
+                let text = meta_str(value, &name);
+                quote! { Some(#text) }
+            }
+            "description" => {
+                let text = meta_str(value, &name);
+                description = quote! { Some(#text) };
+                continue;
+            }
+            "min" | "max" | "step" => {
+                meta_value(value, &name);
+                quote! { Some(#value as f64) }
+            }
+            "allowed" => {
+                let syn::Expr::Array(array) = value else {
+                    panic!("meta entry `allowed` expects a list like `[\"a\", \"b\"]`");
+                };
+                let allowed = array.elems.iter().map(|value| meta_value(value, &name));
+                quote! { &[#(#allowed),*] }
+            }
+            "advanced" => {
+                let syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Bool(flag),
+                    ..
+                }) = value
+                else {
+                    panic!("meta entry `advanced` expects a bool");
+                };
+                quote! { #flag }
+            }
+            _ => panic!("Unknown meta entry `{name}`"),
+        };
+        let name = Ident::new(&name, proc_macro2::Span::call_site());
+        values.push(quote! { #name: #value });
+    }
+
+    Some(quote! {
+        ::impex::FieldMeta {
+            description: #description,
+            #(#values,)*
+            ..::impex::FieldMeta::EMPTY
+        }
+    })
+}
+
+fn meta_str<'a>(value: &'a syn::Expr, name: &str) -> &'a syn::LitStr {
+    match value {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Str(text),
+            ..
+        }) => text,
+        _ => panic!("meta entry `{name}` expects a string"),
+    }
+}
+
+/// Converts a literal, which may be negative, into a `MetaValue`.
+/// Numbers of `min`, `max` and `step` are only checked.
+fn meta_value(value: &syn::Expr, name: &str) -> proc_macro2::TokenStream {
+    let (negative, literal) = match value {
+        syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Neg(_),
+            expr,
+            ..
+        }) => (true, expr.as_ref()),
+        _ => (false, value),
+    };
+    let syn::Expr::Lit(syn::ExprLit { lit, .. }) = literal else {
+        panic!("meta entry `{name}` expects literals");
+    };
+    match lit {
+        syn::Lit::Int(_) => quote! { ::impex::MetaValue::Int(#value) },
+        syn::Lit::Float(_) => quote! { ::impex::MetaValue::Float(#value) },
+        syn::Lit::Str(text) if !negative => quote! { ::impex::MetaValue::Str(#text) },
+        syn::Lit::Bool(flag) if !negative => quote! { ::impex::MetaValue::Bool(#flag) },
+        _ => panic!("meta entry `{name}` expects numbers, strings or bools"),
+    }
+}
+
+/// Checks for a flag like `#[impex(prune_defaults)]` on a type or field
+fn has_impex_flag(attrs: &[syn::Attribute], flag: &str) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("impex"))
+        .any(|attr| {
+            attr.parse_args_with(Punctuated::<syn::Meta, Token![,]>::parse_terminated)
+                .is_ok_and(|metas| metas.iter().any(|meta| meta.path().is_ident(flag)))
+        })
+}
+
+/// Generates the pruning of default values before serializing, which is requested by
+/// `#[impex(prune_defaults)]` on the type or on single fields.
+/// Returns the where clauses needed for it and the statements pruning the cloned `value`.
+fn generate_serialize_pruning<'a>(
+    prune_defaults: bool,
+    fields: impl Iterator<
+        Item = (
+            proc_macro2::TokenStream,
+            &'a syn::Field,
+            proc_macro2::TokenStream,
+        ),
+    >,
+) -> (Vec<proc_macro2::TokenStream>, proc_macro2::TokenStream) {
+    if prune_defaults {
+        let where_clause = quote! { Self: ::impex::ImpexDefaults<TW> };
+        let statement = quote! {
+            let mut value = value;
+            ::impex::ImpexDefaults::<TW>::prune_defaults(&mut value);
+        };
+        return (vec![where_clause], statement);
+    }
+
+    let (accessors, types): (Vec<_>, Vec<_>) = fields
+        .filter(|(_, field, _)| has_impex_flag(&field.attrs, "prune_defaults"))
+        .map(|(accessor, _, ty)| (accessor, ty))
+        .unzip();
+    if accessors.is_empty() {
+        return (Vec::new(), quote! {});
+    }
+    let where_clauses = types
+        .iter()
+        .map(|ty| quote! { #ty: ::impex::ImpexDefaults<TW> })
+        .collect();
+    let statement = quote! {
+        let mut value = value;
+        let default = Self::default();
+        #(::impex::ImpexDefaults::<TW>::prune_defaults_with(&mut value.#accessors, &default.#accessors);)*
+    };
+    (where_clauses, statement)
+}
+
+/// Generates the ImpexCompact implementation of a struct, which writes a presence bitmap
+/// followed by the explicit fields. Missing fields are taken from the default when reading.
+fn generate_compact_struct(
+    impex_name: &Ident,
+    accessors: &[proc_macro2::TokenStream],
+    field_types: &[proc_macro2::TokenStream],
+    (prune_where_clauses, prune_statement): (
+        Vec<proc_macro2::TokenStream>,
+        proc_macro2::TokenStream,
+    ),
+    presence_deserialize: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let field_count = accessors.len();
+    let field_indices: Vec<_> = (0..field_count).map(Index::from).collect();
+    let where_clauses: Vec<_> = field_types
+        .iter()
+        .map(|ty| quote! { #ty: ::impex::ImpexCompact<TW> })
+        .collect();
+    let serialized_value = if prune_where_clauses.is_empty() {
+        quote! { let value = self; }
+    } else {
+        quote! {
+            let value = Clone::clone(self);
+            #prune_statement
+            let value = &value;
+        }
+    };
+
+    quote! {
+        impl<TW: ::impex::WrapperSettings> ::impex::ImpexCompact<TW> for #impex_name<TW>
+        where
+            #(#where_clauses,)*
+            #(#prune_where_clauses,)*
+            Self: Clone,
+        {
+            fn serialize_compact<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                use ::serde::ser::SerializeTuple;
+                #serialized_value
+                let presence = ::impex::compact::Presence::new([#(::impex::Impex::<TW>::is_explicit(&value.#accessors)),*]);
+                let mut tuple = serializer.serialize_tuple(1 + presence.count())?;
+                tuple.serialize_element(&presence)?;
+                #(if presence.is_present(#field_indices) {
+                    tuple.serialize_element(&::impex::Compact::<_, TW>::new(&value.#accessors))?;
+                })*
+                tuple.end()
+            }
+
+            fn deserialize_compact<'de, D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct __CompactVisitor<TW>(::std::marker::PhantomData<TW>);
+
+                impl<'de, TW: ::impex::WrapperSettings> ::serde::de::Visitor<'de> for __CompactVisitor<TW>
+                where
+                    #(#where_clauses),*
+                {
+                    type Value = #impex_name<TW>;
+
+                    fn expecting(&self, formatter: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                        formatter.write_str(concat!("a compact ", stringify!(#impex_name)))
+                    }
+
+                    fn visit_seq<A: ::serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                        let presence: ::impex::compact::Presence<#field_count> = seq
+                            .next_element()?
+                            .ok_or_else(|| ::serde::de::Error::invalid_length(0, &self))?;
+                        let mut index = 1;
+                        let mut value = #impex_name::<TW>::default();
+                        #(if presence.is_present(#field_indices) {
+                            value.#accessors = ::impex::compact::next_element::<_, _, TW>(&mut seq, &mut index, &self)?;
+                        })*
+                        ::impex::Impex::<TW>::fill_defaults(&mut value, #impex_name::<TW>::default());
+                        #presence_deserialize
+                        Ok(value)
+                    }
+                }
+
+                deserializer.deserialize_tuple(1 + #field_count, __CompactVisitor(::std::marker::PhantomData))
+            }
+        }
+    }
+}
+
+/// Parses a string attribute like `#[impex(merge_key = "name")]` on a type or field
+fn impex_attribute_value(attrs: &[syn::Attribute], name: &str) -> Option<syn::LitStr> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("impex"))
+        .filter_map(|attr| {
+            attr.parse_args_with(Punctuated::<syn::Meta, Token![,]>::parse_terminated)
+                .ok()
+        })
+        .flatten()
+        .find_map(|meta| match meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                path,
+                value:
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(value),
+                        ..
+                    }),
+                ..
+            }) if path.is_ident(name) => Some(value),
+            _ => None,
+        })
+}
+
+/// Parses the key field of `#[impex(merge_key = "name")]` on a field
+fn merge_key(attrs: &[syn::Attribute]) -> Option<Ident> {
+    impex_attribute_value(attrs, "merge_key")
+        .map(|key| key.parse().expect("merge_key must be a field name"))
+}
+
+/// Checks for `#[impex(list = "extend")]` on a field
+fn is_extend_list(attrs: &[syn::Attribute]) -> bool {
+    match impex_attribute_value(attrs, "list") {
+        Some(mode) if mode.value() == "extend" => true,
+        Some(mode) => panic!("Unknown list mode {:?}, expected \"extend\"", mode.value()),
+        None => false,
+    }
+}
+
+/// Element type of a `Vec` field with a list attribute like `#[impex(merge_key)]`
+fn vec_element_type<'a>(field: &'a syn::Field, attribute: &str) -> &'a syn::Type {
+    let element = match &field.ty {
+        syn::Type::Path(path) => {
+            path.path
+                .segments
+                .last()
+                .and_then(|segment| match &segment.arguments {
+                    syn::PathArguments::AngleBracketed(args) if segment.ident == "Vec" => {
+                        match args.args.first() {
+                            Some(syn::GenericArgument::Type(element)) => Some(element),
+                            _ => None,
+                        }
+                    }
+                    _ => None,
+                })
+        }
+        _ => None,
+    };
+    element.unwrap_or_else(|| panic!("#[impex({attribute})] is only supported for Vec fields"))
+}
+
+/// Name of the hidden type identifying the elements of a keyed list field, e.g. `ConfigImpexServersKey`
+fn merge_key_marker(impex_name: &Ident, field_str: &str) -> Ident {
+    let field: String = field_str
+        .split('_')
+        .flat_map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .into_iter()
+                .flat_map(char::to_uppercase)
+                .chain(chars)
+        })
+        .collect();
+    Ident::new(&format!("{impex_name}{field}Key"), impex_name.span())
+}
+
+/// Type of a field in the generated struct, which depends on list attributes:
+/// `KeyedListImpex` for `#[impex(merge_key)]` and `ExtendListImpex` for `#[impex(list = "extend")]`
+fn field_impex_type(
+    impex_name: &Ident,
+    field_str: &str,
+    field: &syn::Field,
+) -> proc_macro2::TokenStream {
+    let ty = &field.ty;
+    if merge_key(&field.attrs).is_some() {
+        let marker = merge_key_marker(impex_name, field_str);
+        quote! { ::impex::KeyedListImpex<#marker, TW> }
+    } else if is_extend_list(&field.attrs) {
+        let element = vec_element_type(field, "list");
+        quote! { ::impex::ExtendListImpex<<#element as ::impex::IntoImpex<TW>>::Impex, TW> }
+    } else {
+        quote! { <#ty as ::impex::IntoImpex<TW>>::Impex }
+    }
+}
+
+/// Converts the value of a field into the type returned by `field_impex_type`
+fn field_into_impex(
+    field: &syn::Field,
+    value: proc_macro2::TokenStream,
+    is_explicit: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    if merge_key(&field.attrs).is_some() {
+        quote! { ::impex::KeyedListImpex::new(#value, #is_explicit) }
+    } else if is_extend_list(&field.attrs) {
+        quote! { ::impex::ExtendListImpex::new(#value, #is_explicit) }
+    } else {
+        quote! { ::impex::IntoImpex::<TW>::into_impex(#value, #is_explicit) }
+    }
+}
+
+/// Generates the hidden type implementing `ListKey` for a field with `#[impex(merge_key = "name")]`.
+/// Keys are compared by their value, so the key field can be of any type implementing `PartialEq`.
+fn generate_merge_key_marker(
+    impex_name: &Ident,
+    vis: &syn::Visibility,
+    field_str: &str,
+    field: &syn::Field,
+) -> proc_macro2::TokenStream {
+    let Some(key) = merge_key(&field.attrs) else {
+        return quote! {};
+    };
+    if is_extend_list(&field.attrs) {
+        panic!("#[impex(merge_key)] can't be combined with #[impex(list = \"extend\")]");
+    }
+    let element = vec_element_type(field, "merge_key");
+    let marker = merge_key_marker(impex_name, field_str);
+
+    quote! {
+        #[doc(hidden)]
+        #vis struct #marker;
+
+        impl<TW: ::impex::WrapperSettings> ::impex::ListKey<TW> for #marker {
+            type Element = <#element as ::impex::IntoImpex<TW>>::Impex;
+
+            fn same_key(a: &Self::Element, b: &Self::Element) -> bool {
+                ::impex::Impex::<TW>::into_value(Clone::clone(&a.#key))
+                    == ::impex::Impex::<TW>::into_value(Clone::clone(&b.#key))
+            }
+
+            fn set_key_explicit(element: &mut Self::Element) {
+                ::impex::Impex::<TW>::materialize_defaults(&mut element.#key);
+            }
+        }
+    }
+}
+
 fn generate_named_struct(
     ctx: GenerateContext,
     fields: &syn::FieldsNamed,
@@ -98,26 +621,151 @@ fn generate_named_struct(
         derives,
         has_partial_eq,
         has_eq,
+        prune_defaults,
+        track_presence,
+        preserve_unknown,
+        preserve_order,
+        description,
     } = ctx;
 
     let field_names: Vec<_> = fields.named.iter().map(|f| &f.ident).collect();
-    let field_types: Vec<_> = fields.named.iter().map(|f| &f.ty).collect();
+    let field_strs: Vec<_> = field_names
+        .iter()
+        .map(|name| field_name_str(name.as_ref().unwrap()))
+        .collect();
+    let field_types: Vec<_> = fields
+        .named
+        .iter()
+        .zip(&field_strs)
+        .map(|(f, name)| field_impex_type(impex_name, name, f))
+        .collect();
+    let merge_key_markers = fields
+        .named
+        .iter()
+        .zip(&field_strs)
+        .map(|(f, name)| generate_merge_key_marker(impex_name, vis, name, f));
 
     // Generate the Impex struct definition (without serde attributes)
-    let impex_fields = fields.named.iter().map(|f| {
+    let impex_fields = fields.named.iter().zip(&field_types).map(|(f, ty)| {
         let name = &f.ident;
-        let ty = &f.ty;
         let field_vis = &f.vis;
         quote! {
-            #field_vis #name: <#ty as ::impex::IntoImpex<TW>>::Impex
+            #field_vis #name: #ty
         }
     });
 
-    // Generate IntoImpex implementation
-    let into_impex_fields = field_names.iter().map(|name| {
+    // With `#[impex(track_presence)]` the struct has its own explicit flag,
+    // so an explicitly present object stays explicit even if all of its fields are implicit.
+    // The flag is visible like the struct, so it can still be built with a struct literal
+    let presence_field = if track_presence {
+        quote! {
+            /// Whether the struct itself is explicit, even if none of its fields are
+            #vis _is_present: bool,
+        }
+    } else {
+        quote! {}
+    };
+    let presence_init = |value: proc_macro2::TokenStream| {
+        if track_presence {
+            quote! { _is_present: #value, }
+        } else {
+            quote! {}
+        }
+    };
+    let presence_into_impex = presence_init(quote! { is_explicit });
+    let presence_implicit = presence_init(quote! { false });
+    let presence_check = if track_presence {
+        quote! { self._is_present || }
+    } else {
+        quote! {}
+    };
+    let presence_set = if track_presence {
+        quote! { self._is_present = is_explicit; }
+    } else {
+        quote! {}
+    };
+
+    // With `#[impex(preserve_unknown)]` unknown keys are kept in a hidden field to be saved again.
+    // They count as explicit, so a struct with only unknown keys is still written.
+    let unknown_field = if preserve_unknown {
+        quote! {
+            #[doc(hidden)]
+            _unknown: ::impex::UnknownFields,
+        }
+    } else {
+        quote! {}
+    };
+    let unknown_init = |value: proc_macro2::TokenStream| {
+        if preserve_unknown {
+            quote! { _unknown: #value, }
+        } else {
+            quote! {}
+        }
+    };
+    let unknown_empty = unknown_init(quote! { ::std::default::Default::default() });
+    let unknown_from_value = unknown_init(quote! { value._unknown });
+    let unknown_check = if preserve_unknown {
+        quote! { !self._unknown.is_empty() || }
+    } else {
+        quote! {}
+    };
+    let unknown_serde_field = if preserve_unknown {
+        quote! {
+            #[serde(flatten)]
+            _unknown: ::impex::UnknownFields,
+        }
+    } else {
+        quote! {}
+    };
+    // With `#[impex(preserve_order)]` the order of the keys in the loaded document is kept
+    // in a hidden field, so fields are saved in the same order and new fields are appended.
+    // Otherwise fields are saved in declaration order
+    let order_field = if preserve_order {
+        quote! {
+            #[doc(hidden)]
+            _order: ::impex::KeyOrder,
+        }
+    } else {
+        quote! {}
+    };
+    let order_empty = if preserve_order {
+        quote! { _order: ::std::default::Default::default(), }
+    } else {
+        quote! {}
+    };
+    let order_value = if preserve_order {
+        quote! { value._order }
+    } else {
+        quote! { ::impex::KeyOrder::default() }
+    };
+    let order_merge = if preserve_order {
+        quote! { self._order.merge(overlay._order); }
+    } else {
+        quote! {}
+    };
+
+    let unknown_accessors = if preserve_unknown {
         quote! {
-            #name: ::impex::IntoImpex::<TW>::into_impex(self.#name, is_explicit)
+            impl<TW: ::impex::WrapperSettings> #impex_name<TW> {
+                /// Keys which are not fields of this struct, kept to be saved again
+                pub fn unknown_fields(&self) -> &::impex::UnknownFields {
+                    &self._unknown
+                }
+
+                pub fn unknown_fields_mut(&mut self) -> &mut ::impex::UnknownFields {
+                    &mut self._unknown
+                }
+            }
         }
+    } else {
+        quote! {}
+    };
+
+    // Generate IntoImpex implementation
+    let into_impex_fields = fields.named.iter().map(|f| {
+        let name = &f.ident;
+        let value = field_into_impex(f, quote! { self.#name }, quote! { is_explicit });
+        quote! { #name: #value }
     });
 
     // Generate is_explicit check (all fields OR'd together)
@@ -128,10 +776,10 @@ fn generate_named_struct(
             quote! { || ::impex::Impex::<TW>::is_explicit(&self.#name) }
         });
         quote! {
-            #first_check #(#rest_checks)*
+            #presence_check #unknown_check #first_check #(#rest_checks)*
         }
     } else {
-        quote! { false }
+        quote! { #presence_check #unknown_check false }
     };
 
     // Generate into_value implementation
@@ -149,17 +797,17 @@ fn generate_named_struct(
     });
 
     // Generate default implementation
-    let default_fields = field_names.iter().map(|name| {
-        quote! {
-            #name: ::impex::IntoImpex::<TW>::into_implicit(x.#name)
-        }
+    let default_fields = fields.named.iter().map(|f| {
+        let name = &f.ident;
+        let value = field_into_impex(f, quote! { x.#name }, quote! { false });
+        quote! { #name: #value }
     });
 
     // Generate Visitor implementation (only if visitor feature is enabled)
     let visitor_impl = if cfg!(feature = "visitor") {
         let visitor_where_clauses = field_types.iter().map(|ty| {
             quote! {
-                <#ty as ::impex::IntoImpex<TW>>::Impex: ::impex::Visitor<T>
+                #ty: ::impex::Visitor<T>
             }
         });
 
@@ -183,57 +831,297 @@ fn generate_named_struct(
         quote! {}
     };
 
-    // Generate PartialEq and Eq implementations with proper bounds
-    let mut eq_impl = quote! {};
-    let mut partial_eq_impl = quote! {};
-    if has_partial_eq || has_eq {
-        let partial_eq_where_clauses = field_types.iter().map(|ty| {
-            quote! {
-                <#ty as ::impex::IntoImpex<TW>>::Impex: PartialEq
+    // Generate ImpexDefaults implementation (only usable if all leaves implement PartialEq)
+    let defaults_where_clauses = field_types.iter().map(|ty| {
+        quote! {
+            #ty: ::impex::ImpexDefaults<TW>
+        }
+    });
+    let original_str = original_name.to_string();
+    let presence_prune = if track_presence {
+        quote! {
+            if ::impex::ImpexDefaults::<TW>::value_eq(self, default) {
+                self._is_present = false;
             }
-        });
-
-        let eq_where_clauses = field_types.iter().map(|ty| {
-            quote! {
-                <#ty as ::impex::IntoImpex<TW>>::Impex: Eq
+        }
+    } else {
+        quote! {}
+    };
+    let defaults_impl = quote! {
+        impl<TW: ::impex::WrapperSettings> ::impex::ImpexDefaults<TW> for #impex_name<TW>
+        where
+            #(#defaults_where_clauses),*
+        {
+            fn value_eq(&self, other: &Self) -> bool {
+                true #(&& ::impex::ImpexDefaults::<TW>::value_eq(&self.#field_names, &other.#field_names))*
             }
-        });
 
-        let field_comparisons = field_names.iter().map(|name| {
-            quote! { self.#name == other.#name }
-        });
+            fn prune_defaults_with(&mut self, default: &Self) {
+                #presence_prune
+                #(::impex::ImpexDefaults::<TW>::prune_defaults_with(&mut self.#field_names, &default.#field_names);)*
+            }
 
-        if has_partial_eq {
-            partial_eq_impl = quote! {
-                impl<TW: ::impex::WrapperSettings> PartialEq for #impex_name<TW>
-                where
-                    #(#partial_eq_where_clauses),*
-                {
-                    fn eq(&self, other: &Self) -> bool {
-                        #(#field_comparisons)&&*
-                    }
-                }
+            fn fmt_value(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.debug_struct(#original_str)
+                    #(.field(#field_strs, &::impex::DebugValue::<TW, _>::new(&self.#field_names)))*
+                    .finish()
             }
-        }
 
-        if has_eq {
-            eq_impl = quote! {
-                impl<TW: ::impex::WrapperSettings> Eq for #impex_name<TW>
-                where
-                    #(#eq_where_clauses),*
-                {}
+            fn rebase_defaults_at(
+                &mut self,
+                new_default: Self,
+                path: &::impex::ImpexPath,
+                changes: &mut Vec<::impex::DefaultChange>,
+            ) {
+                #(::impex::ImpexDefaults::<TW>::rebase_defaults_at(&mut self.#field_names, new_default.#field_names, &path.join(#field_strs), changes);)*
             }
         }
-    }
+    };
 
-    // Generate serialization struct with serde attributes
+    // Generate ImpexMerge implementation, merging field by field
+    let merge_where_clauses = field_types.iter().map(|ty| {
+        quote! {
+            #ty: ::impex::ImpexMerge<TW>
+        }
+    });
+    let presence_merge = if track_presence {
+        quote! { self._is_present |= overlay._is_present; }
+    } else {
+        quote! {}
+    };
+    let unknown_merge = if preserve_unknown {
+        quote! { self._unknown.merge(overlay._unknown); }
+    } else {
+        quote! {}
+    };
+    let merge_impl = quote! {
+        impl<TW: ::impex::WrapperSettings> ::impex::ImpexMerge<TW> for #impex_name<TW>
+        where
+            #(#merge_where_clauses),*
+        {
+            fn merge(&mut self, overlay: Self) {
+                #presence_merge
+                #unknown_merge
+                #order_merge
+                #(::impex::ImpexMerge::<TW>::merge(&mut self.#field_names, overlay.#field_names);)*
+            }
+        }
+    };
+
+    // Generate VisitPath implementation
+    let visit_path_where_clauses = field_types.iter().map(|ty| {
+        quote! {
+            #ty: ::impex::VisitPath<TW>
+        }
+    });
+    let visit_path_impl = quote! {
+        impl<TW: ::impex::WrapperSettings> ::impex::VisitPath<TW> for #impex_name<TW>
+        where
+            #(#visit_path_where_clauses),*
+        {
+            fn visit_path<V: ::impex::PathVisitor<TW>>(&mut self, path: &[&str], visitor: &mut V) -> bool {
+                match path.split_first() {
+                    None => {
+                        visitor.visit(self);
+                        true
+                    }
+                    #(Some((&#field_strs, rest)) => ::impex::VisitPath::<TW>::visit_path(&mut self.#field_names, rest, visitor),)*
+                    Some(_) => false,
+                }
+            }
+        }
+    };
+
+    // Generate ImpexSchema implementation (only if schema feature is enabled)
+    let schema_impl = if cfg!(feature = "schema") {
+        let schema_where_clauses = field_types.iter().map(|ty| {
+            quote! {
+                #ty: ::impex::schema::ImpexSchema<TW>
+            }
+        });
+        let field_descriptions = fields.named.iter().map(|f| doc_description(&f.attrs));
+
+        quote! {
+            impl<TW: ::impex::WrapperSettings> ::impex::schema::ImpexSchema<TW> for #impex_name<TW>
+            where
+                Self: ::serde::Serialize,
+                #(#schema_where_clauses),*
+            {
+                fn json_schema() -> ::impex::schema::Schema {
+                    let default = ::impex::schema::default_value::<TW, Self>(Default::default());
+                    let schema = ::impex::schema::struct_schema(
+                        vec![#((#field_strs, ::impex::schema::describe(
+                            <#field_types as ::impex::schema::ImpexSchema<TW>>::json_schema(),
+                            #field_descriptions,
+                        ))),*],
+                        &default,
+                        #preserve_unknown,
+                    );
+                    ::impex::schema::describe(schema, #description)
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // Generate ImpexCatalog implementation (only if catalog feature is enabled).
+    // The struct is a section, followed by the settings of its fields
+    let catalog_impl = if cfg!(feature = "catalog") {
+        let catalog_where_clauses = field_types.iter().map(|ty| {
+            quote! {
+                #ty: ::impex::catalog::ImpexCatalog<TW>
+            }
+        });
+        let field_descriptions = fields.named.iter().map(|f| doc_description(&f.attrs));
+
+        quote! {
+            impl<TW: ::impex::WrapperSettings> ::impex::catalog::ImpexCatalog<TW> for #impex_name<TW>
+            where
+                Self: ::serde::Serialize,
+                #(#catalog_where_clauses),*
+            {
+                fn add_settings(
+                    &self,
+                    path: &::impex::ImpexPath,
+                    description: Option<&str>,
+                    catalog: &mut ::impex::catalog::SettingsCatalog,
+                ) {
+                    catalog.add_section::<TW, Self>(path, description.or(#description));
+                    #(::impex::catalog::ImpexCatalog::<TW>::add_settings(
+                        &self.#field_names,
+                        &path.join(#field_strs),
+                        #field_descriptions,
+                        catalog,
+                    );)*
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // Generate ImpexMeta implementation, with the metadata of the fields in statics
+    let meta_where_clauses = field_types.iter().map(|ty| {
+        quote! {
+            #ty: ::impex::ImpexMeta<TW>
+        }
+    });
+    let (meta_lookup, meta_collect): (Vec<_>, Vec<_>) = fields
+        .named
+        .iter()
+        .zip(&field_strs)
+        .map(|(f, field_str)| {
+            let Some(meta) = field_meta(f) else {
+                return (quote! { None }, quote! {});
+            };
+            let meta_static = quote! {
+                static META: ::impex::FieldMeta = #meta;
+            };
+            (
+                quote! {{ #meta_static Some(&META) }},
+                quote! {{
+                    #meta_static
+                    fields.push((path.join(#field_str), &META));
+                }},
+            )
+        })
+        .unzip();
+    let meta_impl = quote! {
+        impl<TW: ::impex::WrapperSettings> ::impex::ImpexMeta<TW> for #impex_name<TW>
+        where
+            #(#meta_where_clauses),*
+        {
+            fn field_meta(path: &[&str]) -> Option<&'static ::impex::FieldMeta> {
+                match path.split_first() {
+                    #(Some((&#field_strs, [])) => #meta_lookup,)*
+                    #(Some((&#field_strs, rest)) => <#field_types as ::impex::ImpexMeta<TW>>::field_meta(rest),)*
+                    _ => None,
+                }
+            }
+
+            fn collect_field_meta(
+                path: &::impex::ImpexPath,
+                fields: &mut Vec<(::impex::ImpexPath, &'static ::impex::FieldMeta)>,
+            ) {
+                #(
+                    #meta_collect
+                    <#field_types as ::impex::ImpexMeta<TW>>::collect_field_meta(&path.join(#field_strs), fields);
+                )*
+            }
+        }
+    };
+
+    // Generate PartialEq and Eq implementations with proper bounds
+    let mut eq_impl = quote! {};
+    let mut partial_eq_impl = quote! {};
+    if has_partial_eq || has_eq {
+        let partial_eq_where_clauses = field_types.iter().map(|ty| {
+            quote! {
+                #ty: PartialEq
+            }
+        });
+
+        let eq_where_clauses = field_types.iter().map(|ty| {
+            quote! {
+                #ty: Eq
+            }
+        });
+
+        let mut field_comparisons: Vec<_> = field_names
+            .iter()
+            .map(|name| {
+                quote! { self.#name == other.#name }
+            })
+            .collect();
+        if track_presence {
+            field_comparisons.push(quote! { self._is_present == other._is_present });
+        }
+        if preserve_unknown {
+            field_comparisons.push(quote! { self._unknown == other._unknown });
+        }
+
+        if has_partial_eq {
+            partial_eq_impl = quote! {
+                impl<TW: ::impex::WrapperSettings> PartialEq for #impex_name<TW>
+                where
+                    #(#partial_eq_where_clauses),*
+                {
+                    fn eq(&self, other: &Self) -> bool {
+                        #(#field_comparisons)&&*
+                    }
+                }
+            }
+        }
+
+        if has_eq {
+            eq_impl = quote! {
+                impl<TW: ::impex::WrapperSettings> Eq for #impex_name<TW>
+                where
+                    #(#eq_where_clauses),*
+                {}
+            }
+        }
+    }
+
+    // Generate serialization struct with serde attributes
     let serde_struct_name = Ident::new(&format!("{}Serde", impex_name), impex_name.span());
-    let serde_fields = fields.named.iter().map(|f| {
+    let order_deserialize = if preserve_order {
+        quote! {
+            let (serde_struct, order) = ::impex::KeyOrder::deserialize::<#serde_struct_name<TW>, D>(deserializer)?;
+            let mut value: Self = serde_struct.into();
+            value._order = order;
+        }
+    } else {
+        quote! {
+            let serde_struct = #serde_struct_name::<TW>::deserialize(deserializer)?;
+            let mut value: Self = serde_struct.into();
+        }
+    };
+    let serde_fields = fields.named.iter().zip(&field_types).map(|(f, ty)| {
         let name = &f.ident;
-        let ty = &f.ty;
         quote! {
-            #[serde(skip_serializing_if = "::impex::Impex::<TW>::is_implicit")]
-            #name: <#ty as ::impex::IntoImpex<TW>>::Impex
+            #name: #ty
         }
     });
 
@@ -244,22 +1132,95 @@ fn generate_named_struct(
         })
         .collect();
 
-    let serde_where_clauses: Vec<_> = field_types.iter().map(|ty| {
+    let serde_where_clauses: Vec<_> = field_types
+        .iter()
+        .map(|ty| {
+            quote! {
+                #ty: ::serde::Serialize + ::serde::de::DeserializeOwned
+            }
+        })
+        .collect();
+
+    let (prune_where_clauses, prune_statement) = generate_serialize_pruning(
+        prune_defaults,
+        fields.named.iter().zip(&field_types).map(|(f, ty)| {
+            let name = &f.ident;
+            (quote! { #name }, f, ty.clone())
+        }),
+    );
+
+    let presence_deserialize = if track_presence {
+        quote! { value._is_present = true; }
+    } else {
+        quote! {}
+    };
+
+    // Explicit fields are written in the recorded key order, unknown keys go through a map
+    let field_count = field_names.len();
+    let field_indices: Vec<_> = (0..field_count)
+        .map(proc_macro2::Literal::usize_unsuffixed)
+        .collect();
+    let serialize_body = if preserve_unknown {
         quote! {
-            <#ty as ::impex::IntoImpex<TW>>::Impex: ::serde::Serialize + ::serde::de::DeserializeOwned
+            use ::serde::ser::SerializeMap as _;
+            let keys = #order_value.arrange(&[#(#field_strs),*], &value._unknown);
+            let len = explicit.iter().filter(|x| **x).count() + value._unknown.len();
+            let mut state = serializer.serialize_map(Some(len))?;
+            for key in keys {
+                match key {
+                    #(::impex::OrderedKey::Field(#field_indices) => if explicit[#field_indices] {
+                        state.serialize_entry(#field_strs, &value.#field_names)?;
+                    },)*
+                    ::impex::OrderedKey::Unknown(key, raw) => state.serialize_entry(key, raw)?,
+                    _ => {}
+                }
+            }
+            state.end()
         }
-    }).collect();
+    } else {
+        quote! {
+            use ::serde::ser::SerializeStruct as _;
+            let unknown = ::impex::UnknownFields::default();
+            let keys = #order_value.arrange(&[#(#field_strs),*], &unknown);
+            let len = explicit.iter().filter(|x| **x).count();
+            let mut state = serializer.serialize_struct(#original_str, len)?;
+            for key in keys {
+                match key {
+                    #(::impex::OrderedKey::Field(#field_indices) => if explicit[#field_indices] {
+                        state.serialize_field(#field_strs, &value.#field_names)?;
+                    } else {
+                        state.skip_field(#field_strs)?;
+                    },)*
+                    _ => {}
+                }
+            }
+            state.end()
+        }
+    };
+
+    let field_accessors: Vec<_> = field_names.iter().map(|name| quote! { #name }).collect();
+    let compact_impl = generate_compact_struct(
+        impex_name,
+        &field_accessors,
+        &field_types,
+        (prune_where_clauses.clone(), prune_statement.clone()),
+        presence_deserialize.clone(),
+    );
 
     quote! {
         #[derive(Clone, #derives)]
         #vis struct #impex_name<TW: ::impex::WrapperSettings = ::impex::DefaultWrapperSettings> {
-            #(#impex_fields),*
+            #(#impex_fields,)*
+            #presence_field
+            #unknown_field
+            #order_field
         }
 
-        #[derive(::serde::Serialize, ::serde::Deserialize)]
+        #[derive(::serde::Deserialize)]
         #[serde(default, bound = "")]
         struct #serde_struct_name<TW: ::impex::WrapperSettings> {
-            #(#serde_fields),*
+            #(#serde_fields,)*
+            #unknown_serde_field
         }
 
         impl<TW: ::impex::WrapperSettings> Default for #serde_struct_name<TW>
@@ -276,7 +1237,10 @@ fn generate_named_struct(
         impl<TW: ::impex::WrapperSettings> From<#serde_struct_name<TW>> for #impex_name<TW> {
             fn from(value: #serde_struct_name<TW>) -> Self {
                 Self {
-                    #(#serde_from_fields),*
+                    #(#serde_from_fields,)*
+                    #presence_implicit
+                    #unknown_from_value
+                    #order_empty
                 }
             }
         }
@@ -284,7 +1248,8 @@ fn generate_named_struct(
         impl<TW: ::impex::WrapperSettings> From<#impex_name<TW>> for #serde_struct_name<TW> {
             fn from(value: #impex_name<TW>) -> Self {
                 Self {
-                    #(#field_names: value.#field_names),*
+                    #(#field_names: value.#field_names,)*
+                    #unknown_from_value
                 }
             }
         }
@@ -292,14 +1257,17 @@ fn generate_named_struct(
         impl<TW: ::impex::WrapperSettings> ::serde::Serialize for #impex_name<TW>
         where
             #(#serde_where_clauses,)*
+            #(#prune_where_clauses,)*
             Self: Clone,
         {
             fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
             where
                 S: ::serde::Serializer,
             {
-                let serde_struct: #serde_struct_name<TW> = Clone::clone(self).into();
-                serde_struct.serialize(serializer)
+                let value = Clone::clone(self);
+                #prune_statement
+                let explicit: [bool; #field_count] = [#(::impex::Impex::<TW>::is_explicit(&value.#field_names)),*];
+                #serialize_body
             }
         }
 
@@ -311,8 +1279,10 @@ fn generate_named_struct(
             where
                 D: ::serde::Deserializer<'de>,
             {
-                let serde_struct = #serde_struct_name::<TW>::deserialize(deserializer)?;
-                Ok(serde_struct.into())
+                #order_deserialize
+                ::impex::Impex::<TW>::fill_defaults(&mut value, Self::default());
+                #presence_deserialize
+                Ok(value)
             }
         }
 
@@ -321,7 +1291,10 @@ fn generate_named_struct(
 
             fn into_impex(self, is_explicit: bool) -> Self::Impex {
                 #impex_name {
-                    #(#into_impex_fields),*
+                    #(#into_impex_fields,)*
+                    #presence_into_impex
+                    #unknown_empty
+                    #order_empty
                 }
             }
         }
@@ -340,20 +1313,37 @@ fn generate_named_struct(
             }
 
             fn set_impex(&mut self, v: Self::Value, is_explicit: bool) {
+                #presence_set
                 #(#set_impex_fields)*
             }
+
+            fn fill_defaults(&mut self, default: Self) {
+                #(::impex::Impex::<TW>::fill_defaults(&mut self.#field_names, default.#field_names);)*
+            }
         }
 
         impl<TW: ::impex::WrapperSettings> Default for #impex_name<TW> {
             fn default() -> Self {
                 let x = #original_name::default();
                 Self {
-                    #(#default_fields),*
+                    #(#default_fields,)*
+                    #presence_implicit
+                    #unknown_empty
+                    #order_empty
                 }
             }
         }
 
+        #unknown_accessors
+        #(#merge_key_markers)*
         #visitor_impl
+        #defaults_impl
+        #merge_impl
+        #visit_path_impl
+        #meta_impl
+        #schema_impl
+        #catalog_impl
+        #compact_impl
         #eq_impl
         #partial_eq_impl
     }
@@ -370,12 +1360,66 @@ fn generate_tuple_struct(
         derives,
         has_partial_eq,
         has_eq,
+        prune_defaults,
+        track_presence,
+        description,
+        ..
     } = ctx;
 
     let field_types: Vec<_> = fields.unnamed.iter().map(|f| &f.ty).collect();
     let field_vis: Vec<_> = fields.unnamed.iter().map(|f| &f.vis).collect();
     let field_indices: Vec<Index> = (0..fields.unnamed.len()).map(Index::from).collect();
 
+    // With `#[impex(track_presence)]` the explicit flag of the struct is an additional last element,
+    // which is not part of the document
+    let presence_index = Index::from(fields.unnamed.len());
+    let presence_field = if track_presence {
+        quote! {
+            /// Whether the struct itself is explicit, even if none of its elements are
+            #vis bool,
+        }
+    } else {
+        quote! {}
+    };
+    let presence_init = |value: proc_macro2::TokenStream| {
+        if track_presence {
+            quote! { #value, }
+        } else {
+            quote! {}
+        }
+    };
+    let presence_into_impex = presence_init(quote! { is_explicit });
+    let presence_implicit = presence_init(quote! { false });
+    let presence_check = if track_presence {
+        quote! { self.#presence_index || }
+    } else {
+        quote! {}
+    };
+    let presence_set = if track_presence {
+        quote! { self.#presence_index = is_explicit; }
+    } else {
+        quote! {}
+    };
+    let presence_prune = if track_presence {
+        quote! {
+            if ::impex::ImpexDefaults::<TW>::value_eq(self, default) {
+                self.#presence_index = false;
+            }
+        }
+    } else {
+        quote! {}
+    };
+    let presence_merge = if track_presence {
+        quote! { self.#presence_index |= overlay.#presence_index; }
+    } else {
+        quote! {}
+    };
+    let presence_deserialize = if track_presence {
+        quote! { value.#presence_index = true; }
+    } else {
+        quote! {}
+    };
+
     // Generate the Impex struct definition
     let impex_fields: Vec<_> = field_types
         .iter()
@@ -402,10 +1446,10 @@ fn generate_tuple_struct(
             quote! { || ::impex::Impex::<TW>::is_explicit(&self.#idx) }
         });
         quote! {
-            #first_check #(#rest_checks)*
+            #presence_check #first_check #(#rest_checks)*
         }
     } else {
-        quote! { false }
+        quote! { #presence_check false }
     };
 
     // Generate into_value implementation
@@ -457,6 +1501,90 @@ fn generate_tuple_struct(
         quote! {}
     };
 
+    // Generate ImpexDefaults implementation (only usable if all leaves implement PartialEq)
+    let defaults_where_clauses = field_types.iter().map(|ty| {
+        quote! {
+            <#ty as ::impex::IntoImpex<TW>>::Impex: ::impex::ImpexDefaults<TW>
+        }
+    });
+    let original_str = original_name.to_string();
+    let field_strs: Vec<_> = field_indices
+        .iter()
+        .map(|idx| idx.index.to_string())
+        .collect();
+    let defaults_impl = quote! {
+        impl<TW: ::impex::WrapperSettings> ::impex::ImpexDefaults<TW> for #impex_name<TW>
+        where
+            #(#defaults_where_clauses),*
+        {
+            fn value_eq(&self, other: &Self) -> bool {
+                true #(&& ::impex::ImpexDefaults::<TW>::value_eq(&self.#field_indices, &other.#field_indices))*
+            }
+
+            fn prune_defaults_with(&mut self, default: &Self) {
+                #presence_prune
+                #(::impex::ImpexDefaults::<TW>::prune_defaults_with(&mut self.#field_indices, &default.#field_indices);)*
+            }
+
+            fn fmt_value(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.debug_tuple(#original_str)
+                    #(.field(&::impex::DebugValue::<TW, _>::new(&self.#field_indices)))*
+                    .finish()
+            }
+
+            fn rebase_defaults_at(
+                &mut self,
+                new_default: Self,
+                path: &::impex::ImpexPath,
+                changes: &mut Vec<::impex::DefaultChange>,
+            ) {
+                #(::impex::ImpexDefaults::<TW>::rebase_defaults_at(&mut self.#field_indices, new_default.#field_indices, &path.join(#field_strs), changes);)*
+            }
+        }
+    };
+
+    // Generate ImpexMerge implementation, merging field by field
+    let merge_where_clauses = field_types.iter().map(|ty| {
+        quote! {
+            <#ty as ::impex::IntoImpex<TW>>::Impex: ::impex::ImpexMerge<TW>
+        }
+    });
+    let merge_impl = quote! {
+        impl<TW: ::impex::WrapperSettings> ::impex::ImpexMerge<TW> for #impex_name<TW>
+        where
+            #(#merge_where_clauses),*
+        {
+            fn merge(&mut self, overlay: Self) {
+                #presence_merge
+                #(::impex::ImpexMerge::<TW>::merge(&mut self.#field_indices, overlay.#field_indices);)*
+            }
+        }
+    };
+
+    // Generate VisitPath implementation
+    let visit_path_where_clauses = field_types.iter().map(|ty| {
+        quote! {
+            <#ty as ::impex::IntoImpex<TW>>::Impex: ::impex::VisitPath<TW>
+        }
+    });
+    let visit_path_impl = quote! {
+        impl<TW: ::impex::WrapperSettings> ::impex::VisitPath<TW> for #impex_name<TW>
+        where
+            #(#visit_path_where_clauses),*
+        {
+            fn visit_path<V: ::impex::PathVisitor<TW>>(&mut self, path: &[&str], visitor: &mut V) -> bool {
+                match path.split_first() {
+                    None => {
+                        visitor.visit(self);
+                        true
+                    }
+                    #(Some((&#field_strs, rest)) => ::impex::VisitPath::<TW>::visit_path(&mut self.#field_indices, rest, visitor),)*
+                    Some(_) => false,
+                }
+            }
+        }
+    };
+
     let mut eq_impl = quote! {};
     let mut partial_eq_impl = quote! {};
     if has_partial_eq || has_eq {
@@ -472,9 +1600,15 @@ fn generate_tuple_struct(
             }
         });
 
-        let field_comparisons = field_indices.iter().map(|idx| {
-            quote! { self.#idx == other.#idx }
-        });
+        let mut field_comparisons: Vec<_> = field_indices
+            .iter()
+            .map(|idx| {
+                quote! { self.#idx == other.#idx }
+            })
+            .collect();
+        if track_presence {
+            field_comparisons.push(quote! { self.#presence_index == other.#presence_index });
+        }
 
         if has_partial_eq {
             partial_eq_impl = quote! {
@@ -501,10 +1635,15 @@ fn generate_tuple_struct(
 
     // Generate serialization struct
     let serde_struct_name = Ident::new(&format!("{}Serde", impex_name), impex_name.span());
+    // Implicit elements are written as `null` and get their value from the default when loading
+    let serde_fields: Vec<_> = field_types
+        .iter()
+        .map(|ty| quote! { Option<<#ty as ::impex::IntoImpex<TW>>::Impex> })
+        .collect();
     let serde_from_fields: Vec<_> = field_indices
         .iter()
         .map(|idx| {
-            quote! { value.#idx }
+            quote! { value.#idx.unwrap_or(default.#idx) }
         })
         .collect();
     let serde_where_clauses: Vec<_> = field_types.iter().map(|ty| {
@@ -513,16 +1652,103 @@ fn generate_tuple_struct(
         }
     }).collect();
 
+    let (prune_where_clauses, prune_statement) = generate_serialize_pruning(
+        prune_defaults,
+        field_indices
+            .iter()
+            .zip(fields.unnamed.iter())
+            .map(|(idx, f)| {
+                let ty = &f.ty;
+                (
+                    quote! { #idx },
+                    f,
+                    quote! { <#ty as ::impex::IntoImpex<TW>>::Impex },
+                )
+            }),
+    );
+    // Generate ImpexSchema implementation (only if schema feature is enabled)
+    let schema_impl = if cfg!(feature = "schema") {
+        let schema_where_clauses = field_types.iter().map(|ty| {
+            quote! {
+                <#ty as ::impex::IntoImpex<TW>>::Impex: ::impex::schema::ImpexSchema<TW>
+            }
+        });
+        let field_descriptions = fields.unnamed.iter().map(|f| doc_description(&f.attrs));
+
+        quote! {
+            impl<TW: ::impex::WrapperSettings> ::impex::schema::ImpexSchema<TW> for #impex_name<TW>
+            where
+                Self: ::serde::Serialize,
+                #(#schema_where_clauses),*
+            {
+                fn json_schema() -> ::impex::schema::Schema {
+                    let default = ::impex::schema::default_value::<TW, Self>(Default::default());
+                    let schema = ::impex::schema::tuple_schema(
+                        vec![#(::impex::schema::describe(
+                            <<#field_types as ::impex::IntoImpex<TW>>::Impex as ::impex::schema::ImpexSchema<TW>>::json_schema(),
+                            #field_descriptions,
+                        )),*],
+                        &default,
+                    );
+                    ::impex::schema::describe(schema, #description)
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // Generate ImpexCatalog implementation (only if catalog feature is enabled).
+    // A tuple struct is a single setting, described by its own doc comment if the field has none
+    let catalog_impl = if cfg!(feature = "catalog") {
+        quote! {
+            impl<TW: ::impex::WrapperSettings> ::impex::catalog::ImpexCatalog<TW> for #impex_name<TW>
+            where
+                Self: ::serde::Serialize,
+            {
+                fn add_settings(
+                    &self,
+                    path: &::impex::ImpexPath,
+                    description: Option<&str>,
+                    catalog: &mut ::impex::catalog::SettingsCatalog,
+                ) {
+                    catalog.add_setting::<TW, Self>(path, description.or(#description), self);
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // Generate ImpexMeta implementation. A tuple struct has no metadata of its own
+    let meta_impl = quote! {
+        impl<TW: ::impex::WrapperSettings> ::impex::ImpexMeta<TW> for #impex_name<TW> {}
+    };
+
+    let field_accessors: Vec<_> = field_indices.iter().map(|idx| quote! { #idx }).collect();
+    let impex_field_types: Vec<_> = field_types
+        .iter()
+        .map(|ty| quote! { <#ty as ::impex::IntoImpex<TW>>::Impex })
+        .collect();
+    let compact_impl = generate_compact_struct(
+        impex_name,
+        &field_accessors,
+        &impex_field_types,
+        (prune_where_clauses.clone(), prune_statement.clone()),
+        presence_deserialize.clone(),
+    );
+
     quote! {
         #[derive(Clone, #derives)]
         #vis struct #impex_name<TW: ::impex::WrapperSettings = ::impex::DefaultWrapperSettings>(
-            #(#impex_fields),*
+            #(#impex_fields,)*
+            #presence_field
         );
 
         #[derive(::serde::Serialize, ::serde::Deserialize)]
         #[serde(bound = "")]
         struct #serde_struct_name<TW: ::impex::WrapperSettings>(
-            #(#impex_fields),*
+            #(#serde_fields),*
         );
 
         impl<TW: ::impex::WrapperSettings> Default for #serde_struct_name<TW>
@@ -532,32 +1758,36 @@ fn generate_tuple_struct(
             fn default() -> Self {
                 let default_value = #original_name::default();
                 let impex: #impex_name<TW> = ::impex::IntoImpex::into_impex(default_value, false);
-                Self(#(impex.#field_indices),*)
+                Self(#(Some(impex.#field_indices)),*)
             }
         }
 
         impl<TW: ::impex::WrapperSettings> From<#serde_struct_name<TW>> for #impex_name<TW> {
             fn from(value: #serde_struct_name<TW>) -> Self {
-                Self(#(#serde_from_fields),*)
+                let default = Self::default();
+                Self(#(#serde_from_fields,)* #presence_implicit)
             }
         }
 
         impl<TW: ::impex::WrapperSettings> From<#impex_name<TW>> for #serde_struct_name<TW> {
             fn from(value: #impex_name<TW>) -> Self {
-                Self(#(value.#field_indices),*)
+                Self(#(Some(value.#field_indices)),*)
             }
         }
 
         impl<TW: ::impex::WrapperSettings> ::serde::Serialize for #impex_name<TW>
         where
             #(#serde_where_clauses,)*
+            #(#prune_where_clauses,)*
             Self: Clone,
         {
             fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
             where
                 S: ::serde::Serializer,
             {
-                let serde_struct: #serde_struct_name<TW> = Clone::clone(self).into();
+                let value = Clone::clone(self);
+                #prune_statement
+                let serde_struct: #serde_struct_name<TW> = value.into();
                 serde_struct.serialize(serializer)
             }
         }
@@ -571,7 +1801,10 @@ fn generate_tuple_struct(
                 D: ::serde::Deserializer<'de>,
             {
                 let serde_struct = #serde_struct_name::<TW>::deserialize(deserializer)?;
-                Ok(serde_struct.into())
+                let mut value: Self = serde_struct.into();
+                ::impex::Impex::<TW>::fill_defaults(&mut value, Self::default());
+                #presence_deserialize
+                Ok(value)
             }
         }
 
@@ -580,7 +1813,8 @@ fn generate_tuple_struct(
 
             fn into_impex(self, is_explicit: bool) -> Self::Impex {
                 #impex_name(
-                    #(#into_impex_fields),*
+                    #(#into_impex_fields,)*
+                    #presence_into_impex
                 )
             }
         }
@@ -599,20 +1833,33 @@ fn generate_tuple_struct(
             }
 
             fn set_impex(&mut self, v: Self::Value, is_explicit: bool) {
+                #presence_set
                 #(#set_impex_fields)*
             }
+
+            fn fill_defaults(&mut self, default: Self) {
+                #(::impex::Impex::<TW>::fill_defaults(&mut self.#field_indices, default.#field_indices);)*
+            }
         }
 
         impl<TW: ::impex::WrapperSettings> Default for #impex_name<TW> {
             fn default() -> Self {
                 let x = #original_name::default();
                 Self(
-                    #(#default_fields),*
+                    #(#default_fields,)*
+                    #presence_implicit
                 )
             }
         }
 
         #visitor_impl
+        #defaults_impl
+        #merge_impl
+        #visit_path_impl
+        #meta_impl
+        #schema_impl
+        #catalog_impl
+        #compact_impl
         #eq_impl
         #partial_eq_impl
     }
@@ -669,7 +1916,72 @@ fn generate_visibility_struct(
     }
 }
 
-fn generate_enum(ctx: GenerateContext, data_enum: &syn::DataEnum) -> proc_macro2::TokenStream {
+/// Generates match arms for `(self, other)` which call `function` field by field,
+/// if both are the same variant. Unit variants have no fields and are skipped.
+/// Generates match arms applying `function` to the fields of `(self, other)` with the same variant.
+/// With `#[impex(preserve_unknown)]`, `unknown` is run with the unknown fields of struct variants
+/// bound to `__self_unknown` and `__other_unknown`.
+fn generate_same_variant_arms(
+    data_enum: &syn::DataEnum,
+    function: proc_macro2::TokenStream,
+    unknown: Option<proc_macro2::TokenStream>,
+) -> Vec<proc_macro2::TokenStream> {
+    data_enum
+        .variants
+        .iter()
+        .filter_map(|variant| {
+            let variant_name = &variant.ident;
+            match &variant.fields {
+                Fields::Named(fields) => {
+                    let field_names: Vec<_> = fields
+                        .named
+                        .iter()
+                        .map(|f| f.ident.as_ref().unwrap())
+                        .collect();
+                    let self_fields: Vec<_> = field_names
+                        .iter()
+                        .map(|name| Ident::new(&format!("self_{}", name), name.span()))
+                        .collect();
+                    let other_fields: Vec<_> = field_names
+                        .iter()
+                        .map(|name| Ident::new(&format!("other_{}", name), name.span()))
+                        .collect();
+                    let (self_unknown, other_unknown) = match &unknown {
+                        Some(_) => (
+                            quote! { _unknown: __self_unknown },
+                            quote! { _unknown: __other_unknown },
+                        ),
+                        None => (quote! {}, quote! {}),
+                    };
+                    Some(quote! {
+                        (Self::#variant_name { #(#field_names: #self_fields,)* #self_unknown },
+                         Self::#variant_name { #(#field_names: #other_fields,)* #other_unknown }) => {
+                            #unknown
+                            #(#function(#self_fields, #other_fields);)*
+                        }
+                    })
+                }
+                Fields::Unnamed(fields) => {
+                    let self_fields: Vec<Ident> = (0..fields.unnamed.len())
+                        .map(|i| Ident::new(&format!("self_{}", i + 1), variant_name.span()))
+                        .collect();
+                    let other_fields: Vec<Ident> = (0..fields.unnamed.len())
+                        .map(|i| Ident::new(&format!("other_{}", i + 1), variant_name.span()))
+                        .collect();
+                    Some(quote! {
+                        (Self::#variant_name(#(#self_fields),*),
+                         Self::#variant_name(#(#other_fields),*)) => {
+                            #(#function(#self_fields, #other_fields);)*
+                        }
+                    })
+                }
+                Fields::Unit => None,
+            }
+        })
+        .collect()
+}
+
+fn generate_enum(ctx: GenerateContext, data_enum: &syn::DataEnum) -> proc_macro2::TokenStream {
     let GenerateContext {
         impex_name,
         original_name,
@@ -677,8 +1989,32 @@ fn generate_enum(ctx: GenerateContext, data_enum: &syn::DataEnum) -> proc_macro2
         derives,
         has_partial_eq,
         has_eq,
+        prune_defaults,
+        preserve_unknown,
+        description,
+        ..
     } = ctx;
 
+    // With `#[impex(preserve_unknown)]` struct variants keep their unknown keys in a hidden field
+    let unknown_variant_field = if preserve_unknown {
+        quote! {
+            #[doc(hidden)]
+            _unknown: ::impex::UnknownFields,
+        }
+    } else {
+        quote! {}
+    };
+    let unknown_empty = if preserve_unknown {
+        quote! { _unknown: ::std::default::Default::default(), }
+    } else {
+        quote! {}
+    };
+    let unknown_rest = if preserve_unknown {
+        quote! { .. }
+    } else {
+        quote! {}
+    };
+
     // Collect unit variants to generate visibility structs for them
     let unit_variants: Vec<_> = data_enum
         .variants
@@ -714,7 +2050,8 @@ fn generate_enum(ctx: GenerateContext, data_enum: &syn::DataEnum) -> proc_macro2
                 });
                 quote! {
                     #variant_name {
-                        #(#fields),*
+                        #(#fields,)*
+                        #unknown_variant_field
                     }
                 }
             }
@@ -755,7 +2092,8 @@ fn generate_enum(ctx: GenerateContext, data_enum: &syn::DataEnum) -> proc_macro2
                 });
                 quote! {
                     #original_name::#variant_name { #(#field_names),* } => #impex_name::#variant_name {
-                        #(#field_conversions),*
+                        #(#field_conversions,)*
+                        #unknown_empty
                     }
                 }
             }
@@ -798,9 +2136,14 @@ fn generate_enum(ctx: GenerateContext, data_enum: &syn::DataEnum) -> proc_macro2
                         ::impex::Impex::<TW>::is_explicit(#name)
                     }
                 });
+                let (unknown_binding, unknown_check) = if preserve_unknown {
+                    (quote! { _unknown }, quote! { !_unknown.is_empty() || })
+                } else {
+                    (quote! {}, quote! {})
+                };
                 quote! {
-                    #impex_name::#variant_name { #(#field_names),* } => {
-                        #(#checks)||*
+                    #impex_name::#variant_name { #(#field_names,)* #unknown_binding } => {
+                        #unknown_check #(#checks)||*
                     }
                 }
             }
@@ -838,7 +2181,7 @@ fn generate_enum(ctx: GenerateContext, data_enum: &syn::DataEnum) -> proc_macro2
                     }
                 });
                 quote! {
-                    #impex_name::#variant_name { #(#field_names),* } => #original_name::#variant_name {
+                    #impex_name::#variant_name { #(#field_names,)* #unknown_rest } => #original_name::#variant_name {
                         #(#field_conversions),*
                     }
                 }
@@ -878,7 +2221,8 @@ fn generate_enum(ctx: GenerateContext, data_enum: &syn::DataEnum) -> proc_macro2
                 });
                 quote! {
                     Self::Value::#variant_name { #(#field_names),* } => #impex_name::#variant_name {
-                        #(#field_conversions),*
+                        #(#field_conversions,)*
+                        #unknown_empty
                     }
                 }
             }
@@ -909,124 +2253,486 @@ fn generate_enum(ctx: GenerateContext, data_enum: &syn::DataEnum) -> proc_macro2
             }
         }
     });
-
-    // Generate default match arms
-    let default_arms = data_enum.variants.iter().map(|variant| {
+
+    // Generate default match arms
+    let default_arms = data_enum.variants.iter().map(|variant| {
+        let variant_name = &variant.ident;
+        match &variant.fields {
+            Fields::Named(fields) => {
+                let field_names: Vec<_> = fields.named.iter().map(|f| &f.ident).collect();
+                let field_conversions = field_names.iter().map(|name| {
+                    quote! {
+                        #name: ::impex::IntoImpex::<TW>::into_implicit(#name)
+                    }
+                });
+                quote! {
+                    #original_name::#variant_name { #(#field_names),* } => Self::#variant_name {
+                        #(#field_conversions,)*
+                        #unknown_empty
+                    }
+                }
+            }
+            Fields::Unnamed(fields) => {
+                let field_names: Vec<Ident> = (0..fields.unnamed.len())
+                    .map(|i| Ident::new(&format!("x{}", i + 1), variant_name.span()))
+                    .collect();
+                let field_conversions = field_names.iter().map(|name| {
+                    quote! {
+                        ::impex::IntoImpex::<TW>::into_implicit(#name)
+                    }
+                });
+                quote! {
+                    #original_name::#variant_name(#(#field_names),*) => Self::#variant_name(
+                        #(#field_conversions),*
+                    )
+                }
+            }
+            // Unit variants use default visibility (is_explicit: false)
+            Fields::Unit => quote! {
+                #original_name::#variant_name => Self::#variant_name(Default::default())
+            },
+        }
+    });
+
+    let visitor_impl = if cfg!(feature = "visitor") {
+        let mut visitor_field_types = Vec::new();
+        for variant in &data_enum.variants {
+            match &variant.fields {
+                Fields::Named(fields) => {
+                    visitor_field_types.extend(fields.named.iter().map(|f| &f.ty));
+                }
+                Fields::Unnamed(fields) => {
+                    visitor_field_types.extend(fields.unnamed.iter().map(|f| &f.ty));
+                }
+                Fields::Unit => {}
+            }
+        }
+
+        let visitor_where_clauses = visitor_field_types.iter().map(|ty| {
+            quote! {
+                <#ty as ::impex::IntoImpex<TW>>::Impex: ::impex::Visitor<T>
+            }
+        });
+
+        // Generate Visitor match arms
+        let visitor_match_arms = data_enum.variants.iter().map(|variant| {
+            let variant_name = &variant.ident;
+            match &variant.fields {
+                Fields::Named(fields) => {
+                    let field_names: Vec<_> = fields.named.iter().map(|f| &f.ident).collect();
+                    let visit_calls = field_names.iter().map(|name| {
+                        quote! {
+                            ::impex::Visitor::<T>::visit(#name, ctx);
+                        }
+                    });
+                    quote! {
+                        Self::#variant_name { #(#field_names,)* #unknown_rest } => {
+                            #(#visit_calls)*
+                        }
+                    }
+                }
+                Fields::Unnamed(fields) => {
+                    let field_names: Vec<Ident> = (0..fields.unnamed.len())
+                        .map(|i| Ident::new(&format!("x{}", i + 1), variant_name.span()))
+                        .collect();
+                    let visit_calls = field_names.iter().map(|name| {
+                        quote! {
+                            ::impex::Visitor::<T>::visit(#name, ctx);
+                        }
+                    });
+                    quote! {
+                        Self::#variant_name(#(#field_names),*) => {
+                            #(#visit_calls)*
+                        }
+                    }
+                }
+                // Unit variants (now tuple variants with visibility) have no fields to visit
+                Fields::Unit => quote! {
+                    Self::#variant_name(_) => {}
+                },
+            }
+        });
+
+        let visitor_where_clauses: Vec<_> = visitor_where_clauses.collect();
+        let where_clause = if visitor_where_clauses.is_empty() {
+            quote! {}
+        } else {
+            quote! { where #(#visitor_where_clauses),* }
+        };
+        quote! {
+            impl<T, TW: ::impex::WrapperSettings> ::impex::Visitor<T> for #impex_name<TW>
+            #where_clause
+            {
+                fn visit(&mut self, ctx: &mut T) {
+                    match self {
+                        #(#visitor_match_arms),*
+                    }
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // Generate ImpexDefaults implementation (only usable if all leaves implement PartialEq)
+    let mut defaults_field_types = Vec::new();
+    for variant in &data_enum.variants {
+        match &variant.fields {
+            Fields::Named(fields) => {
+                defaults_field_types.extend(fields.named.iter().map(|f| &f.ty));
+            }
+            Fields::Unnamed(fields) => {
+                defaults_field_types.extend(fields.unnamed.iter().map(|f| &f.ty));
+            }
+            Fields::Unit => {}
+        }
+    }
+    let defaults_where_clauses: Vec<_> = defaults_field_types
+        .iter()
+        .map(|ty| {
+            quote! {
+                <#ty as ::impex::IntoImpex<TW>>::Impex: ::impex::ImpexDefaults<TW>
+            }
+        })
+        .collect();
+    let defaults_where_clause = if defaults_where_clauses.is_empty() {
+        quote! {}
+    } else {
+        quote! { where #(#defaults_where_clauses),* }
+    };
+
+    // Same variants are compared field by field, different variants are never equal
+    let value_eq_arms = data_enum.variants.iter().map(|variant| {
+        let variant_name = &variant.ident;
+        match &variant.fields {
+            Fields::Named(fields) => {
+                let field_names: Vec<_> = fields
+                    .named
+                    .iter()
+                    .map(|f| f.ident.as_ref().unwrap())
+                    .collect();
+                let self_fields: Vec<_> = field_names
+                    .iter()
+                    .map(|name| Ident::new(&format!("self_{}", name), name.span()))
+                    .collect();
+                let other_fields: Vec<_> = field_names
+                    .iter()
+                    .map(|name| Ident::new(&format!("other_{}", name), name.span()))
+                    .collect();
+                quote! {
+                    (Self::#variant_name { #(#field_names: #self_fields,)* #unknown_rest },
+                     Self::#variant_name { #(#field_names: #other_fields,)* #unknown_rest }) => {
+                        true #(&& ::impex::ImpexDefaults::<TW>::value_eq(#self_fields, #other_fields))*
+                    }
+                }
+            }
+            Fields::Unnamed(fields) => {
+                let self_fields: Vec<Ident> = (0..fields.unnamed.len())
+                    .map(|i| Ident::new(&format!("self_{}", i + 1), variant_name.span()))
+                    .collect();
+                let other_fields: Vec<Ident> = (0..fields.unnamed.len())
+                    .map(|i| Ident::new(&format!("other_{}", i + 1), variant_name.span()))
+                    .collect();
+                quote! {
+                    (Self::#variant_name(#(#self_fields),*),
+                     Self::#variant_name(#(#other_fields),*)) => {
+                        true #(&& ::impex::ImpexDefaults::<TW>::value_eq(#self_fields, #other_fields))*
+                    }
+                }
+            }
+            Fields::Unit => quote! {
+                (Self::#variant_name(_), Self::#variant_name(_)) => true
+            },
+        }
+    });
+
+    // Different variants keep their explicitness, as they can't equal the default
+    let prune_defaults_arms = data_enum.variants.iter().map(|variant| {
+        let variant_name = &variant.ident;
+        match &variant.fields {
+            Fields::Named(fields) => {
+                let field_names: Vec<_> = fields
+                    .named
+                    .iter()
+                    .map(|f| f.ident.as_ref().unwrap())
+                    .collect();
+                let self_fields: Vec<_> = field_names
+                    .iter()
+                    .map(|name| Ident::new(&format!("self_{}", name), name.span()))
+                    .collect();
+                let default_fields: Vec<_> = field_names
+                    .iter()
+                    .map(|name| Ident::new(&format!("default_{}", name), name.span()))
+                    .collect();
+                quote! {
+                    (Self::#variant_name { #(#field_names: #self_fields,)* #unknown_rest },
+                     Self::#variant_name { #(#field_names: #default_fields,)* #unknown_rest }) => {
+                        #(::impex::ImpexDefaults::<TW>::prune_defaults_with(#self_fields, #default_fields);)*
+                    }
+                }
+            }
+            Fields::Unnamed(fields) => {
+                let self_fields: Vec<Ident> = (0..fields.unnamed.len())
+                    .map(|i| Ident::new(&format!("self_{}", i + 1), variant_name.span()))
+                    .collect();
+                let default_fields: Vec<Ident> = (0..fields.unnamed.len())
+                    .map(|i| Ident::new(&format!("default_{}", i + 1), variant_name.span()))
+                    .collect();
+                quote! {
+                    (Self::#variant_name(#(#self_fields),*),
+                     Self::#variant_name(#(#default_fields),*)) => {
+                        #(::impex::ImpexDefaults::<TW>::prune_defaults_with(#self_fields, #default_fields);)*
+                    }
+                }
+            }
+            Fields::Unit => quote! {
+                (Self::#variant_name(v), Self::#variant_name(_)) => v.is_explicit = false
+            },
+        }
+    });
+
+    let fmt_value_arms = data_enum.variants.iter().map(|variant| {
+        let variant_name = &variant.ident;
+        let variant_str = variant_name.to_string();
+        match &variant.fields {
+            Fields::Named(fields) => {
+                let field_names: Vec<_> = fields
+                    .named
+                    .iter()
+                    .map(|f| f.ident.as_ref().unwrap())
+                    .collect();
+                let field_strs = field_names.iter().map(|name| field_name_str(name));
+                quote! {
+                    Self::#variant_name { #(#field_names,)* #unknown_rest } => f
+                        .debug_struct(#variant_str)
+                        #(.field(#field_strs, &::impex::DebugValue::<TW, _>::new(#field_names)))*
+                        .finish()
+                }
+            }
+            Fields::Unnamed(fields) => {
+                let field_names: Vec<Ident> = (0..fields.unnamed.len())
+                    .map(|i| Ident::new(&format!("x{}", i + 1), variant_name.span()))
+                    .collect();
+                quote! {
+                    Self::#variant_name(#(#field_names),*) => f
+                        .debug_tuple(#variant_str)
+                        #(.field(&::impex::DebugValue::<TW, _>::new(#field_names)))*
+                        .finish()
+                }
+            }
+            Fields::Unit => quote! {
+                Self::#variant_name(_) => f.write_str(#variant_str)
+            },
+        }
+    });
+
+    // Same variants are rebased field by field, different variants are replaced if they are implicit
+    let rebase_defaults_arms = data_enum.variants.iter().map(|variant| {
+        let variant_name = &variant.ident;
+        let variant_str = variant_name.to_string();
+        match &variant.fields {
+            Fields::Named(fields) => {
+                let field_names: Vec<_> = fields
+                    .named
+                    .iter()
+                    .map(|f| f.ident.as_ref().unwrap())
+                    .collect();
+                let field_strs: Vec<_> = field_names.iter().map(|name| field_name_str(name)).collect();
+                let self_fields: Vec<_> = field_names
+                    .iter()
+                    .map(|name| Ident::new(&format!("self_{}", name), name.span()))
+                    .collect();
+                let default_fields: Vec<_> = field_names
+                    .iter()
+                    .map(|name| Ident::new(&format!("default_{}", name), name.span()))
+                    .collect();
+                quote! {
+                    (Self::#variant_name { #(#field_names: #self_fields,)* #unknown_rest },
+                     Self::#variant_name { #(#field_names: #default_fields,)* #unknown_rest }) => {
+                        let path = path.join(#variant_str);
+                        #(::impex::ImpexDefaults::<TW>::rebase_defaults_at(#self_fields, #default_fields, &path.join(#field_strs), changes);)*
+                    }
+                }
+            }
+            Fields::Unnamed(fields) => {
+                let field_strs: Vec<_> = (0..fields.unnamed.len()).map(|i| i.to_string()).collect();
+                let self_fields: Vec<Ident> = (0..fields.unnamed.len())
+                    .map(|i| Ident::new(&format!("self_{}", i + 1), variant_name.span()))
+                    .collect();
+                let default_fields: Vec<Ident> = (0..fields.unnamed.len())
+                    .map(|i| Ident::new(&format!("default_{}", i + 1), variant_name.span()))
+                    .collect();
+                quote! {
+                    (Self::#variant_name(#(#self_fields),*),
+                     Self::#variant_name(#(#default_fields),*)) => {
+                        let path = path.join(#variant_str);
+                        #(::impex::ImpexDefaults::<TW>::rebase_defaults_at(#self_fields, #default_fields, &path.join(#field_strs), changes);)*
+                    }
+                }
+            }
+            Fields::Unit => quote! {
+                (Self::#variant_name(_), Self::#variant_name(_)) => {}
+            },
+        }
+    });
+
+    let defaults_impl = quote! {
+        impl<TW: ::impex::WrapperSettings> ::impex::ImpexDefaults<TW> for #impex_name<TW>
+        #defaults_where_clause
+        {
+            fn value_eq(&self, other: &Self) -> bool {
+                match (self, other) {
+                    #(#value_eq_arms,)*
+                    _ => false,
+                }
+            }
+
+            fn prune_defaults_with(&mut self, default: &Self) {
+                match (self, default) {
+                    #(#prune_defaults_arms,)*
+                    _ => {}
+                }
+            }
+
+            fn fmt_value(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                match self {
+                    #(#fmt_value_arms),*
+                }
+            }
+
+            fn rebase_defaults_at(
+                &mut self,
+                new_default: Self,
+                path: &::impex::ImpexPath,
+                changes: &mut Vec<::impex::DefaultChange>,
+            ) {
+                match (self, new_default) {
+                    #(#rebase_defaults_arms,)*
+                    (this, new_default) => ::impex::rebase_implicit_value(this, new_default, path, changes),
+                }
+            }
+        }
+    };
+
+    // Generate ImpexMerge implementation. Same variants are merged field by field,
+    // different variants are replaced if the overlay is explicit
+    let merge_where_clauses: Vec<_> = defaults_field_types
+        .iter()
+        .map(|ty| {
+            quote! {
+                <#ty as ::impex::IntoImpex<TW>>::Impex: ::impex::ImpexMerge<TW>
+            }
+        })
+        .collect();
+    let merge_where_clause = if merge_where_clauses.is_empty() {
+        quote! {}
+    } else {
+        quote! { where #(#merge_where_clauses),* }
+    };
+    let merge_arms = generate_same_variant_arms(
+        data_enum,
+        quote! { ::impex::ImpexMerge::<TW>::merge },
+        preserve_unknown.then(|| quote! { __self_unknown.merge(__other_unknown); }),
+    );
+    let fill_defaults_arms = generate_same_variant_arms(
+        data_enum,
+        quote! { ::impex::Impex::<TW>::fill_defaults },
+        preserve_unknown.then(|| quote! {}),
+    );
+    let merge_impl = quote! {
+        impl<TW: ::impex::WrapperSettings> ::impex::ImpexMerge<TW> for #impex_name<TW>
+        #merge_where_clause
+        {
+            fn merge(&mut self, overlay: Self) {
+                match (self, overlay) {
+                    #(#merge_arms,)*
+                    (this, overlay) => ::impex::merge_explicit_value(this, overlay),
+                }
+            }
+        }
+    };
+
+    // Generate VisitPath implementation. Paths contain the variant name, which must be the active one
+    let visit_path_where_clauses: Vec<_> = defaults_field_types
+        .iter()
+        .map(|ty| {
+            quote! {
+                <#ty as ::impex::IntoImpex<TW>>::Impex: ::impex::VisitPath<TW>
+            }
+        })
+        .collect();
+    let visit_path_where_clause = if visit_path_where_clauses.is_empty() {
+        quote! {}
+    } else {
+        quote! { where #(#visit_path_where_clauses),* }
+    };
+    let is_active_variant_arms = data_enum.variants.iter().map(|variant| {
+        let variant_name = &variant.ident;
+        let variant_str = variant_name.to_string();
+        match &variant.fields {
+            Fields::Named(_) => quote! { Self::#variant_name { .. } => *variant == #variant_str },
+            Fields::Unnamed(_) | Fields::Unit => {
+                quote! { Self::#variant_name(..) => *variant == #variant_str }
+            }
+        }
+    });
+    let visit_path_arms = data_enum.variants.iter().filter_map(|variant| {
         let variant_name = &variant.ident;
+        let variant_str = variant_name.to_string();
         match &variant.fields {
             Fields::Named(fields) => {
-                let field_names: Vec<_> = fields.named.iter().map(|f| &f.ident).collect();
-                let field_conversions = field_names.iter().map(|name| {
-                    quote! {
-                        #name: ::impex::IntoImpex::<TW>::into_implicit(#name)
-                    }
-                });
-                quote! {
-                    #original_name::#variant_name { #(#field_names),* } => Self::#variant_name {
-                        #(#field_conversions),*
+                let field_names: Vec<_> = fields
+                    .named
+                    .iter()
+                    .map(|f| f.ident.as_ref().unwrap())
+                    .collect();
+                let field_strs = field_names.iter().map(|name| field_name_str(name));
+                Some(quote! {
+                    Self::#variant_name { #(#field_names,)* #unknown_rest } if *variant == #variant_str => match rest.split_first() {
+                        #(Some((&#field_strs, rest)) => ::impex::VisitPath::<TW>::visit_path(#field_names, rest, visitor),)*
+                        _ => false,
                     }
-                }
+                })
             }
             Fields::Unnamed(fields) => {
                 let field_names: Vec<Ident> = (0..fields.unnamed.len())
                     .map(|i| Ident::new(&format!("x{}", i + 1), variant_name.span()))
                     .collect();
-                let field_conversions = field_names.iter().map(|name| {
-                    quote! {
-                        ::impex::IntoImpex::<TW>::into_implicit(#name)
+                let field_strs = (0..fields.unnamed.len()).map(|i| i.to_string());
+                Some(quote! {
+                    Self::#variant_name(#(#field_names),*) if *variant == #variant_str => match rest.split_first() {
+                        #(Some((&#field_strs, rest)) => ::impex::VisitPath::<TW>::visit_path(#field_names, rest, visitor),)*
+                        _ => false,
                     }
-                });
-                quote! {
-                    #original_name::#variant_name(#(#field_names),*) => Self::#variant_name(
-                        #(#field_conversions),*
-                    )
-                }
+                })
             }
-            // Unit variants use default visibility (is_explicit: false)
-            Fields::Unit => quote! {
-                #original_name::#variant_name => Self::#variant_name(Default::default())
-            },
+            Fields::Unit => None,
         }
     });
-
-    let visitor_impl = if cfg!(feature = "visitor") {
-        let mut visitor_field_types = Vec::new();
-        for variant in &data_enum.variants {
-            match &variant.fields {
-                Fields::Named(fields) => {
-                    visitor_field_types.extend(fields.named.iter().map(|f| &f.ty));
-                }
-                Fields::Unnamed(fields) => {
-                    visitor_field_types.extend(fields.unnamed.iter().map(|f| &f.ty));
-                }
-                Fields::Unit => {}
-            }
-        }
-
-        let visitor_where_clauses = visitor_field_types.iter().map(|ty| {
-            quote! {
-                <#ty as ::impex::IntoImpex<TW>>::Impex: ::impex::Visitor<T>
-            }
-        });
-
-        // Generate Visitor match arms
-        let visitor_match_arms = data_enum.variants.iter().map(|variant| {
-            let variant_name = &variant.ident;
-            match &variant.fields {
-                Fields::Named(fields) => {
-                    let field_names: Vec<_> = fields.named.iter().map(|f| &f.ident).collect();
-                    let visit_calls = field_names.iter().map(|name| {
-                        quote! {
-                            ::impex::Visitor::<T>::visit(#name, ctx);
-                        }
-                    });
-                    quote! {
-                        Self::#variant_name { #(#field_names),* } => {
-                            #(#visit_calls)*
-                        }
-                    }
-                }
-                Fields::Unnamed(fields) => {
-                    let field_names: Vec<Ident> = (0..fields.unnamed.len())
-                        .map(|i| Ident::new(&format!("x{}", i + 1), variant_name.span()))
-                        .collect();
-                    let visit_calls = field_names.iter().map(|name| {
-                        quote! {
-                            ::impex::Visitor::<T>::visit(#name, ctx);
-                        }
-                    });
-                    quote! {
-                        Self::#variant_name(#(#field_names),*) => {
-                            #(#visit_calls)*
-                        }
+    let visit_path_impl = quote! {
+        impl<TW: ::impex::WrapperSettings> ::impex::VisitPath<TW> for #impex_name<TW>
+        #visit_path_where_clause
+        {
+            fn visit_path<V: ::impex::PathVisitor<TW>>(&mut self, path: &[&str], visitor: &mut V) -> bool {
+                let Some((variant, rest)) = path.split_first() else {
+                    visitor.visit(self);
+                    return true;
+                };
+                if rest.is_empty() {
+                    let is_active = match self {
+                        #(#is_active_variant_arms),*
+                    };
+                    if is_active {
+                        visitor.visit(self);
                     }
+                    return is_active;
                 }
-                // Unit variants (now tuple variants with visibility) have no fields to visit
-                Fields::Unit => quote! {
-                    Self::#variant_name(_) => {}
-                },
-            }
-        });
-
-        let visitor_where_clauses: Vec<_> = visitor_where_clauses.collect();
-        let where_clause = if visitor_where_clauses.is_empty() {
-            quote! {}
-        } else {
-            quote! { where #(#visitor_where_clauses),* }
-        };
-        quote! {
-            impl<T, TW: ::impex::WrapperSettings> ::impex::Visitor<T> for #impex_name<TW>
-            #where_clause
-            {
-                fn visit(&mut self, ctx: &mut T) {
-                    match self {
-                        #(#visitor_match_arms),*
-                    }
+                match self {
+                    #(#visit_path_arms,)*
+                    _ => false,
                 }
             }
         }
-    } else {
-        quote! {}
     };
 
     let mut eq_impl = quote! {};
@@ -1076,12 +2782,23 @@ fn generate_enum(ctx: GenerateContext, data_enum: &syn::DataEnum) -> proc_macro2
                         .iter()
                         .map(|name| Ident::new(&format!("other_{}", name), name.span()))
                         .collect();
-                    let comparisons = self_fields.iter().zip(other_fields.iter()).map(|(s, o)| {
-                        quote! { #s == #o }
-                    });
+                    let mut comparisons: Vec<_> = self_fields
+                        .iter()
+                        .zip(other_fields.iter())
+                        .map(|(s, o)| quote! { #s == #o })
+                        .collect();
+                    let (self_unknown, other_unknown) = if preserve_unknown {
+                        comparisons.push(quote! { __self_unknown == __other_unknown });
+                        (
+                            quote! { _unknown: __self_unknown },
+                            quote! { _unknown: __other_unknown },
+                        )
+                    } else {
+                        (quote! {}, quote! {})
+                    };
                     quote! {
-                        (Self::#variant_name { #(#field_names: #self_fields),* },
-                         Self::#variant_name { #(#field_names: #other_fields),* }) => {
+                        (Self::#variant_name { #(#field_names: #self_fields,)* #self_unknown },
+                         Self::#variant_name { #(#field_names: #other_fields,)* #other_unknown }) => {
                             #(#comparisons)&&*
                         }
                     }
@@ -1170,11 +2887,13 @@ fn generate_enum(ctx: GenerateContext, data_enum: &syn::DataEnum) -> proc_macro2
         })
         .collect();
 
+    // Fields written as `null` are implicit, with a placeholder from the default of their type
     let deserialize_where_clauses: Vec<_> = serde_field_types
         .iter()
         .map(|ty| {
             quote! {
-                <#ty as ::impex::IntoImpex<TW>>::Impex: ::serde::de::DeserializeOwned
+                <#ty as ::impex::IntoImpex<TW>>::Impex: ::serde::de::DeserializeOwned,
+                #ty: ::std::default::Default
             }
         })
         .collect();
@@ -1192,6 +2911,28 @@ fn generate_enum(ctx: GenerateContext, data_enum: &syn::DataEnum) -> proc_macro2
         let variant_str = variant_name.to_string();
         let idx_u32 = idx as u32;
         match &variant.fields {
+            // Unknown keys can't be written by a struct variant, so the fields are written as a map
+            Fields::Named(fields) if preserve_unknown => {
+                let field_names: Vec<_> = fields.named.iter().map(|f| &f.ident).collect();
+                let field_types: Vec<_> = fields.named.iter().map(|f| &f.ty).collect();
+                quote! {
+                    Self::#variant_name { #(#field_names,)* _unknown } => {
+                        #[derive(::serde::Serialize)]
+                        #[serde(bound = "")]
+                        struct __Fields<'a, TW: ::impex::WrapperSettings> {
+                            #(#field_names: &'a <#field_types as ::impex::IntoImpex<TW>>::Impex,)*
+                            #[serde(flatten)]
+                            _unknown: &'a ::impex::UnknownFields,
+                        }
+                        serializer.serialize_newtype_variant(
+                            stringify!(#impex_name),
+                            #idx_u32,
+                            #variant_str,
+                            &__Fields::<TW> { #(#field_names,)* _unknown },
+                        )
+                    }
+                }
+            }
             Fields::Named(fields) => {
                 let field_names: Vec<_> = fields.named.iter().map(|f| &f.ident).collect();
                 let field_count = field_names.len();
@@ -1270,15 +3011,32 @@ fn generate_enum(ctx: GenerateContext, data_enum: &syn::DataEnum) -> proc_macro2
             Fields::Named(fields) => {
                 let field_names: Vec<_> = fields.named.iter().map(|f| &f.ident).collect();
                 let field_types: Vec<_> = fields.named.iter().map(|f| &f.ty).collect();
+                let (unknown_field, unknown_value) = if preserve_unknown {
+                    (
+                        quote! {
+                            #[serde(flatten)]
+                            _unknown: ::impex::UnknownFields,
+                        },
+                        quote! { _unknown: fields._unknown, },
+                    )
+                } else {
+                    (quote! {}, quote! {})
+                };
                 quote! {
                     #variant_str => {
                         #[derive(::serde::Deserialize)]
                         #[serde(bound = "")]
                         struct __Fields<TW: ::impex::WrapperSettings> {
-                            #(#field_names: <#field_types as ::impex::IntoImpex<TW>>::Impex),*
+                            #(#field_names: Option<<#field_types as ::impex::IntoImpex<TW>>::Impex>,)*
+                            #unknown_field
                         }
                         let fields: __Fields<TW> = map.next_value()?;
-                        Ok(#impex_name::#variant_name { #(#field_names: fields.#field_names),* })
+                        Ok(#impex_name::#variant_name {
+                            #(#field_names: fields.#field_names.unwrap_or_else(|| {
+                                ::impex::IntoImpex::<TW>::into_implicit(<#field_types as ::std::default::Default>::default())
+                            }),)*
+                            #unknown_value
+                        })
                     }
                 }
             }
@@ -1287,8 +3045,10 @@ fn generate_enum(ctx: GenerateContext, data_enum: &syn::DataEnum) -> proc_macro2
                     let ty = &fields.unnamed[0].ty;
                     quote! {
                         #variant_str => {
-                            let value: <#ty as ::impex::IntoImpex<TW>>::Impex = map.next_value()?;
-                            Ok(#impex_name::#variant_name(value))
+                            let value: Option<<#ty as ::impex::IntoImpex<TW>>::Impex> = map.next_value()?;
+                            Ok(#impex_name::#variant_name(value.unwrap_or_else(|| {
+                                ::impex::IntoImpex::<TW>::into_implicit(<#ty as ::std::default::Default>::default())
+                            })))
                         }
                     }
                 } else {
@@ -1298,8 +3058,10 @@ fn generate_enum(ctx: GenerateContext, data_enum: &syn::DataEnum) -> proc_macro2
                         .collect();
                     quote! {
                         #variant_str => {
-                            let value: (#(<#field_types as ::impex::IntoImpex<TW>>::Impex),*,) = map.next_value()?;
-                            Ok(#impex_name::#variant_name(#(value.#field_indices),*))
+                            let value: (#(Option<<#field_types as ::impex::IntoImpex<TW>>::Impex>),*,) = map.next_value()?;
+                            Ok(#impex_name::#variant_name(#(value.#field_indices.unwrap_or_else(|| {
+                                ::impex::IntoImpex::<TW>::into_implicit(<#field_types as ::std::default::Default>::default())
+                            })),*))
                         }
                     }
                 }
@@ -1320,6 +3082,22 @@ fn generate_enum(ctx: GenerateContext, data_enum: &syn::DataEnum) -> proc_macro2
         }
     }).collect();
 
+    // Pruning with `#[impex(prune_defaults)]` is only supported for the whole enum
+    let (prune_where_clauses, prune_statement) =
+        generate_serialize_pruning(prune_defaults, std::iter::empty());
+    let (prune_binding, serialized_value) = if prune_defaults {
+        let binding = quote! {
+            let value = Clone::clone(self);
+            #prune_statement
+        };
+        (binding, quote! { &value })
+    } else {
+        (quote! {}, quote! { self })
+    };
+    let serialize_where_clauses: Vec<_> = serialize_where_clauses
+        .into_iter()
+        .chain(prune_where_clauses)
+        .collect();
     let serialize_where_clause = if serialize_where_clauses.is_empty() {
         quote! {}
     } else {
@@ -1331,6 +3109,279 @@ fn generate_enum(ctx: GenerateContext, data_enum: &syn::DataEnum) -> proc_macro2
         quote! { where #(#deserialize_where_clauses),* }
     };
 
+    // Compact form: the variant index, then a presence bitmap and all fields of the variant.
+    // Fields can't be left out, as there is no default to take them from for other variants.
+    let compact_where_clauses: Vec<_> = serde_field_types
+        .iter()
+        .map(|ty| {
+            quote! {
+                <#ty as ::impex::IntoImpex<TW>>::Impex: ::impex::ImpexCompact<TW>
+            }
+        })
+        .collect();
+    let compact_where_clause = if compact_where_clauses.is_empty() {
+        quote! {}
+    } else {
+        quote! { where #(#compact_where_clauses),* }
+    };
+    // Generate ImpexSchema implementation (only if schema feature is enabled).
+    // Unit variants are strings, the others are tagged with the name of the variant
+    let schema_impl = if cfg!(feature = "schema") {
+        let schema_where_clauses = defaults_field_types.iter().map(|ty| {
+            quote! {
+                <#ty as ::impex::IntoImpex<TW>>::Impex: ::impex::schema::ImpexSchema<TW>
+            }
+        });
+        let field_schema = |f: &syn::Field| {
+            let ty = &f.ty;
+            let field_description = doc_description(&f.attrs);
+            quote! {
+                ::impex::schema::describe(
+                    <<#ty as ::impex::IntoImpex<TW>>::Impex as ::impex::schema::ImpexSchema<TW>>::json_schema(),
+                    #field_description,
+                )
+            }
+        };
+        let variant_schemas = data_enum.variants.iter().map(|variant| {
+            let variant_str = field_name_str(&variant.ident);
+            let variant_description = doc_description(&variant.attrs);
+            let content = match &variant.fields {
+                Fields::Unit => quote! { ::impex::schema::VariantSchema::Unit },
+                Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                    let schema = field_schema(&fields.unnamed[0]);
+                    quote! { ::impex::schema::VariantSchema::Newtype(#schema) }
+                }
+                Fields::Unnamed(fields) => {
+                    let schemas = fields.unnamed.iter().map(field_schema);
+                    quote! { ::impex::schema::VariantSchema::Tuple(vec![#(#schemas),*]) }
+                }
+                Fields::Named(fields) => {
+                    let schemas = fields.named.iter().map(|f| {
+                        let field_str = field_name_str(f.ident.as_ref().unwrap());
+                        let schema = field_schema(f);
+                        quote! { (#field_str, #schema) }
+                    });
+                    quote! { ::impex::schema::VariantSchema::Struct(vec![#(#schemas),*]) }
+                }
+            };
+            quote! { (#variant_str, #variant_description, #content) }
+        });
+
+        quote! {
+            impl<TW: ::impex::WrapperSettings> ::impex::schema::ImpexSchema<TW> for #impex_name<TW>
+            where
+                #(#schema_where_clauses),*
+            {
+                fn json_schema() -> ::impex::schema::Schema {
+                    let schema = ::impex::schema::enum_schema(
+                        vec![#(#variant_schemas),*],
+                        #preserve_unknown,
+                    );
+                    ::impex::schema::describe(schema, #description)
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // Generate ImpexCatalog implementation (only if catalog feature is enabled).
+    // An enum is a single setting, described by its own doc comment if the field has none
+    let catalog_impl = if cfg!(feature = "catalog") {
+        quote! {
+            impl<TW: ::impex::WrapperSettings> ::impex::catalog::ImpexCatalog<TW> for #impex_name<TW>
+            where
+                Self: ::serde::Serialize,
+            {
+                fn add_settings(
+                    &self,
+                    path: &::impex::ImpexPath,
+                    description: Option<&str>,
+                    catalog: &mut ::impex::catalog::SettingsCatalog,
+                ) {
+                    catalog.add_setting::<TW, Self>(path, description.or(#description), self);
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // Generate ImpexMeta implementation. An enum has no metadata of its own
+    let meta_impl = quote! {
+        impl<TW: ::impex::WrapperSettings> ::impex::ImpexMeta<TW> for #impex_name<TW> {}
+    };
+
+    let compact_impl_where_clause = if prune_defaults {
+        quote! { where #(#compact_where_clauses,)* Self: ::impex::ImpexDefaults<TW> }
+    } else {
+        compact_where_clause.clone()
+    };
+    let max_field_count = data_enum
+        .variants
+        .iter()
+        .map(|variant| variant.fields.len())
+        .max()
+        .unwrap_or(0);
+    let variant_bindings = |variant: &syn::Variant| -> Vec<Ident> {
+        match &variant.fields {
+            Fields::Named(fields) => fields
+                .named
+                .iter()
+                .map(|f| f.ident.clone().unwrap())
+                .collect(),
+            Fields::Unnamed(fields) => (0..fields.unnamed.len())
+                .map(|i| Ident::new(&format!("x{}", i + 1), variant.ident.span()))
+                .collect(),
+            Fields::Unit => Vec::new(),
+        }
+    };
+    let variant_pattern =
+        |variant: &syn::Variant, bindings: &[Ident], unknown| match &variant.fields {
+            Fields::Named(_) => {
+                let variant_name = &variant.ident;
+                quote! { #impex_name::<TW>::#variant_name { #(#bindings,)* #unknown } }
+            }
+            Fields::Unnamed(_) => {
+                let variant_name = &variant.ident;
+                quote! { #impex_name::<TW>::#variant_name(#(#bindings),*) }
+            }
+            Fields::Unit => unreachable!("Unit variants have no fields"),
+        };
+    let serialize_compact_arms = data_enum.variants.iter().enumerate().map(|(idx, variant)| {
+        let variant_name = &variant.ident;
+        let idx_u32 = idx as u32;
+        if matches!(variant.fields, Fields::Unit) {
+            return quote! {
+                Self::#variant_name(_) => {
+                    let mut tuple = serializer.serialize_tuple(1)?;
+                    tuple.serialize_element(&#idx_u32)?;
+                    tuple.end()
+                }
+            };
+        }
+        let bindings = variant_bindings(variant);
+        let pattern = variant_pattern(variant, &bindings, &unknown_rest);
+        let field_count = bindings.len();
+        quote! {
+            #pattern => {
+                let presence = ::impex::compact::Presence::new([#(::impex::Impex::<TW>::is_explicit(#bindings)),*]);
+                let mut tuple = serializer.serialize_tuple(2 + #field_count)?;
+                tuple.serialize_element(&#idx_u32)?;
+                tuple.serialize_element(&presence)?;
+                #(tuple.serialize_element(&::impex::Compact::<_, TW>::new(#bindings))?;)*
+                tuple.end()
+            }
+        }
+    });
+    let deserialize_compact_arms = data_enum.variants.iter().enumerate().map(|(idx, variant)| {
+        let variant_name = &variant.ident;
+        let idx_u32 = idx as u32;
+        if matches!(variant.fields, Fields::Unit) {
+            let visibility_name = Ident::new(
+                &format!("{}{}Visibility", impex_name, variant_name),
+                variant_name.span(),
+            );
+            return quote! {
+                #idx_u32 => Ok(#impex_name::#variant_name(#visibility_name { is_explicit: true, _phantom: ::std::marker::PhantomData }))
+            };
+        }
+        let bindings = variant_bindings(variant);
+        let pattern = variant_pattern(variant, &bindings, &unknown_empty);
+        let field_types: Vec<_> = variant.fields.iter().map(|f| &f.ty).collect();
+        let field_count = bindings.len();
+        let field_indices = (0..field_count).map(Index::from);
+        quote! {
+            #idx_u32 => {
+                let presence: ::impex::compact::Presence<#field_count> = seq
+                    .next_element()?
+                    .ok_or_else(|| ::serde::de::Error::invalid_length(1, &self))?;
+                let mut index = 2;
+                #(
+                    let mut #bindings: <#field_types as ::impex::IntoImpex<TW>>::Impex =
+                        ::impex::compact::next_element::<_, _, TW>(&mut seq, &mut index, &self)?;
+                    if !presence.is_present(#field_indices) {
+                        #bindings = ::impex::compact::into_implicit::<#field_types, TW>(#bindings);
+                    }
+                )*
+                Ok(#pattern)
+            }
+        }
+    });
+    let compact_impl = quote! {
+        impl<TW: ::impex::WrapperSettings> ::impex::ImpexCompact<TW> for #impex_name<TW>
+        #compact_impl_where_clause
+        {
+            fn serialize_compact<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                use ::serde::ser::SerializeTuple;
+                #prune_binding
+                match #serialized_value {
+                    #(#serialize_compact_arms),*
+                }
+            }
+
+            fn deserialize_compact<'de, D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct __CompactVisitor<TW>(::std::marker::PhantomData<TW>);
+
+                impl<'de, TW: ::impex::WrapperSettings> ::serde::de::Visitor<'de> for __CompactVisitor<TW>
+                #compact_where_clause
+                {
+                    type Value = #impex_name<TW>;
+
+                    fn expecting(&self, formatter: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                        formatter.write_str(concat!("a compact ", stringify!(#impex_name)))
+                    }
+
+                    fn visit_seq<A: ::serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                        let variant: u32 = seq
+                            .next_element()?
+                            .ok_or_else(|| ::serde::de::Error::invalid_length(0, &self))?;
+                        match variant {
+                            #(#deserialize_compact_arms,)*
+                            _ => Err(::serde::de::Error::invalid_value(
+                                ::serde::de::Unexpected::Unsigned(variant.into()),
+                                &self,
+                            )),
+                        }
+                    }
+                }
+
+                deserializer.deserialize_tuple(2 + #max_field_count, __CompactVisitor(::std::marker::PhantomData))
+            }
+        }
+    };
+
+    let unknown_accessors = if preserve_unknown {
+        let struct_variants: Vec<_> = data_enum
+            .variants
+            .iter()
+            .filter(|variant| matches!(variant.fields, Fields::Named(_)))
+            .map(|variant| &variant.ident)
+            .collect();
+        quote! {
+            impl<TW: ::impex::WrapperSettings> #impex_name<TW> {
+                /// Keys which are not fields of the active struct variant, kept to be saved again
+                pub fn unknown_fields(&self) -> Option<&::impex::UnknownFields> {
+                    match self {
+                        #(Self::#struct_variants { _unknown, .. } => Some(_unknown),)*
+                        #[allow(unreachable_patterns)]
+                        _ => None,
+                    }
+                }
+
+                pub fn unknown_fields_mut(&mut self) -> Option<&mut ::impex::UnknownFields> {
+                    match self {
+                        #(Self::#struct_variants { _unknown, .. } => Some(_unknown),)*
+                        #[allow(unreachable_patterns)]
+                        _ => None,
+                    }
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
     quote! {
         // Visibility structs for unit variants
         #(#visibility_structs)*
@@ -1348,7 +3399,8 @@ fn generate_enum(ctx: GenerateContext, data_enum: &syn::DataEnum) -> proc_macro2
             where
                 S: ::serde::Serializer,
             {
-                match self {
+                #prune_binding
+                match #serialized_value {
                     #(#serialize_arms),*
                 }
             }
@@ -1400,7 +3452,9 @@ fn generate_enum(ctx: GenerateContext, data_enum: &syn::DataEnum) -> proc_macro2
                         if map.next_key::<String>()?.is_some() {
                             return Err(de::Error::custom("expected single variant key"));
                         }
-                        result
+                        let mut value = result?;
+                        ::impex::Impex::<TW>::fill_defaults(&mut value, #impex_name::default());
+                        Ok(value)
                     }
                 }
 
@@ -1438,6 +3492,17 @@ fn generate_enum(ctx: GenerateContext, data_enum: &syn::DataEnum) -> proc_macro2
                     #(#set_impex_arms),*
                 };
             }
+
+            fn fill_defaults(&mut self, default: Self) {
+                match (self, default) {
+                    #(#fill_defaults_arms,)*
+                    (this, default) => {
+                        if ::impex::Impex::<TW>::is_implicit(this) {
+                            *this = default;
+                        }
+                    }
+                }
+            }
         }
 
         impl<TW: ::impex::WrapperSettings> Default for #impex_name<TW> {
@@ -1449,7 +3514,15 @@ fn generate_enum(ctx: GenerateContext, data_enum: &syn::DataEnum) -> proc_macro2
             }
         }
 
+        #unknown_accessors
         #visitor_impl
+        #defaults_impl
+        #merge_impl
+        #visit_path_impl
+        #meta_impl
+        #schema_impl
+        #catalog_impl
+        #compact_impl
         #eq_impl
         #partial_eq_impl
     }